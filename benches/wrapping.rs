@@ -0,0 +1,44 @@
+//! Measures the time taken to compute the wrapped lines of large buffers.
+//!
+//! Run with `cargo bench --bench wrapping`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use copyrat::ui::wrap::compute_wrapped_lines;
+
+const ITERATIONS: u32 = 20;
+
+fn bench(name: &str, content: &str) {
+    let lines: Vec<&str> = content.split('\n').collect();
+
+    // Warm up.
+    black_box(compute_wrapped_lines(&lines, 80));
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(compute_wrapped_lines(black_box(&lines), black_box(80)));
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{name:<8} {lines:>7} lines {bytes:>9} bytes: {elapsed:?}/iter",
+        lines = lines.len(),
+        bytes = content.len(),
+    );
+}
+
+fn main() {
+    let ascii_line = "2021-03-04T12:23:34 e006b06 /usr/local/bin/git https://example.com/a/b?c=d";
+    let cjk_line = "日志: 文件 /usr/local/bin/git 未找到。エラー: ファイルが見つかりません 🦀";
+    let tabs_line = "col1\tcol2\t\tcol3\te\u{301}te\u{301}\t0x7ffd5c3a";
+
+    for (name, line) in [
+        ("ascii", ascii_line),
+        ("cjk", cjk_line),
+        ("tabs", tabs_line),
+    ] {
+        let content = vec![line; 100_000].join("\n");
+        bench(name, &content);
+    }
+}
@@ -0,0 +1,69 @@
+//! Measures the time taken to find the spans of a large capture, such as an
+//! entire pane history.
+//!
+//! Run with `cargo bench -p copyrat-core --bench matching`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use regex::{Regex, RegexSet};
+
+use copyrat_core::textbuf::find_raw_spans;
+use copyrat_core::textbuf::regexes::{parse_pattern_name, NamedPattern};
+
+const ITERATIONS: u32 = 5;
+
+const LINES: usize = 50_000;
+
+fn bench(name: &str, lines: &[&str], named_patterns: &[NamedPattern], use_all_patterns: bool) {
+    let find = || {
+        find_raw_spans(
+            black_box(lines),
+            named_patterns,
+            &[],
+            use_all_patterns,
+            None,
+            &[],
+            &mut Regex::new,
+            &mut |patterns| RegexSet::new(patterns),
+        )
+        .unwrap()
+    };
+
+    // Warm up.
+    let spans = find().len();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(find());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{name:<10} {lines:>7} lines {spans:>7} spans: {elapsed:?}/iter",
+        lines = lines.len(),
+    );
+}
+
+fn main() {
+    let log_lines = [
+        "2021-03-04T12:23:34 e006b06 GET https://example.com/a/b?c=d 200 10.0.0.1:8080",
+        "commit 8a288f5c2f8d3b1e7b1c2f0e6d7b9a4c3e2f1d0a",
+        "    at main (/home/me/src/main.rs:42:7) id=7f3c9e2a-1b4d-4c8e-9f2a-3b5d7e9f1a2c",
+        "drwxr-xr-x  2 me staff 4096 Mar  4 12:23 Documents",
+        "",
+        "Compiling copyrat v0.5.7 (/home/me/tmux-copyrat) in 12.3s, 0x7ffd5c3a",
+    ];
+    let plain_lines = [
+        "The quick brown fox jumps over the lazy dog, again and again and again.",
+        "    nothing to see here, move along",
+    ];
+
+    let ipv4 = [parse_pattern_name("ipv4").unwrap()];
+
+    for (name, sample) in [("logs", &log_lines[..]), ("plain", &plain_lines[..])] {
+        let lines: Vec<&str> = sample.iter().copied().cycle().take(LINES).collect();
+        bench(&format!("{name}/all"), &lines, &[], true);
+        bench(&format!("{name}/ipv4"), &lines, &ipv4, false);
+    }
+}
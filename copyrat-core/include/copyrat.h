@@ -0,0 +1,34 @@
+/*
+ * C interface to the copyrat pattern matching.
+ *
+ * Build the shared library with
+ *
+ *     cargo rustc -p copyrat-core --release --lib --features ffi --crate-type cdylib
+ *
+ * See the documentation of the `copyrat_core::ffi` module for the format of the
+ * options and of the returned spans.
+ */
+
+#ifndef COPYRAT_H
+#define COPYRAT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Find the spans in the UTF-8 `text` with the JSON `options_json`, and return
+ * them as a JSON string, to be released with `copyrat_free_string`.
+ *
+ * Returns NULL if an argument is NULL or not valid UTF-8.
+ */
+char *copyrat_find_spans(const char *text, const char *options_json);
+
+/* Release a string returned by `copyrat_find_spans`. */
+void copyrat_free_string(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* COPYRAT_H */
@@ -0,0 +1,22 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unknown alphabet")]
+    UnknownAlphabet,
+
+    #[error("Invalid alphabet `{0}`: expected distinct lowercase letters or symbols")]
+    InvalidAlphabet(String),
+
+    #[error("Unknown pattern name")]
+    UnknownPatternName,
+
+    #[error(
+        "Pattern `{0}` has no capture group: wrap the text to copy in `(...)` or `(?P<match>...)`"
+    )]
+    PatternWithoutGroup(String),
+
+    #[error("Invalid pattern `{pattern}`: {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+
+    #[error("Unknown pattern flag `{0}`: expected i, m, s, U, w or S")]
+    UnknownPatternFlag(char),
+}
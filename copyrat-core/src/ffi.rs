@@ -0,0 +1,253 @@
+//! C-compatible interface to the pattern matching, so that other tools
+//! (editors, daemons) highlight exactly the same spans as copyrat.
+//!
+//! This module is enabled by the `ffi` feature. Build the shared library with
+//!
+//! ```sh
+//! cargo rustc -p copyrat-core --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! and declare the functions as in `include/copyrat.h`.
+//!
+//! The options are passed as a JSON object, in which all members are
+//! optional:
+//!
+//! ```json
+//! {
+//!   "patterns": ["url", "path"],
+//!   "custom_patterns": ["(JIRA-\\d+)"],
+//!   "pattern_priority": ["url", "path"],
+//!   "all_patterns": false,
+//!   "alphabet": "dvorak",
+//!   "reverse": false,
+//!   "unique_hint": false
+//! }
+//! ```
+//!
+//! If neither `patterns` nor `custom_patterns` are provided, all patterns
+//! are used. The spans are returned as a JSON object such as
+//!
+//! ```json
+//! {"spans":[{"x":6,"y":0,"pattern":"url","text":"https://example.com","hint":"a"}]}
+//! ```
+//!
+//! where `y` is the index of the line, and `x` the byte offset of the span in
+//! the line. If the options are invalid, an object such as
+//! `{"error":"unknown pattern name `foo`"}` is returned instead.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic;
+
+use regex::Regex;
+
+use crate::json::{self, Value};
+use crate::textbuf::{self, alphabet, regexes};
+
+/// Options of the pattern matching, as parsed from the options JSON.
+struct Options {
+    named_patterns: Vec<regexes::NamedPattern>,
+    custom_patterns: Vec<String>,
+    pattern_priority: Vec<String>,
+    use_all_patterns: bool,
+    alphabet: alphabet::Alphabet,
+    reverse: bool,
+    unique_hint: bool,
+}
+
+impl Options {
+    fn parse(options_json: &str) -> Result<Options, String> {
+        let options = if options_json.trim().is_empty() {
+            Value::Object(vec![])
+        } else {
+            json::parse(options_json).map_err(|err| format!("invalid options: {err}"))?
+        };
+        if !matches!(options, Value::Object(_)) {
+            return Err("options must be a JSON object".to_string());
+        }
+
+        let named_patterns = json::strings(&options, "patterns")?
+            .iter()
+            .map(|name| {
+                regexes::parse_pattern_name(name)
+                    .map_err(|_| format!("unknown pattern name `{name}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let custom_patterns = json::strings(&options, "custom_patterns")?;
+        for pattern in &custom_patterns {
+            let regex =
+                Regex::new(pattern).map_err(|err| format!("invalid custom pattern: {err}"))?;
+            regexes::check_capture_group(&regex)
+                .map_err(|err| format!("invalid custom pattern: {err}"))?;
+        }
+
+        let use_all_patterns = match options.get("all_patterns") {
+            None => named_patterns.is_empty() && custom_patterns.is_empty(),
+            Some(_) => json::boolean(&options, "all_patterns")?,
+        };
+
+        let alphabet = match options.get("alphabet") {
+            None => alphabet::parse_alphabet("dvorak"),
+            Some(Value::String(name)) => alphabet::parse_alphabet(name),
+            Some(_) => return Err("`alphabet` must be a string".to_string()),
+        }
+        .map_err(|err| format!("invalid alphabet: {err}"))?;
+
+        Ok(Options {
+            named_patterns,
+            custom_patterns,
+            pattern_priority: json::strings(&options, "pattern_priority")?,
+            use_all_patterns,
+            alphabet,
+            reverse: json::boolean(&options, "reverse")?,
+            unique_hint: json::boolean(&options, "unique_hint")?,
+        })
+    }
+}
+
+/// Find the spans in the `text` with the options in `options_json`, and
+/// format them as JSON, see the module documentation.
+///
+/// This is the safe counterpart of `copyrat_find_spans`.
+pub fn find_spans_json(text: &str, options_json: &str) -> String {
+    let options = match Options::parse(options_json) {
+        Ok(options) => options,
+        Err(err) => return format!(r#"{{"error":{}}}"#, json::string(Some(&err))),
+    };
+
+    let lines: Vec<&str> = text.split('\n').collect();
+    let model = match textbuf::Model::new(
+        &lines,
+        &options.alphabet,
+        options.use_all_patterns,
+        &options.named_patterns,
+        &options.custom_patterns,
+        options.reverse,
+        options.unique_hint,
+        None,
+        &options.pattern_priority,
+    ) {
+        Ok(model) => model,
+        Err(err) => return format!(r#"{{"error":{}}}"#, json::string(Some(&err.to_string()))),
+    };
+
+    let spans: Vec<String> = model
+        .spans
+        .iter()
+        .map(|span| {
+            format!(
+                r#"{{"x":{},"y":{},"pattern":{},"text":{},"hint":{}}}"#,
+                span.x,
+                span.y,
+                json::string(Some(span.pattern)),
+                json::string(Some(span.text)),
+                json::string(Some(&span.hint)),
+            )
+        })
+        .collect();
+
+    format!(r#"{{"spans":[{}]}}"#, spans.join(","))
+}
+
+/// Find the spans in the UTF-8 `text` with the UTF-8 `options_json`, and
+/// return them as a JSON string, see the module documentation.
+///
+/// The returned string must be released with `copyrat_free_string`. A null
+/// pointer is returned if an argument is null, or not valid UTF-8.
+///
+/// # Safety
+///
+/// `text` and `options_json` must be null or point to nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn copyrat_find_spans(
+    text: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    if text.is_null() || options_json.is_null() {
+        return std::ptr::null_mut();
+    }
+    let (Ok(text), Ok(options_json)) = (
+        CStr::from_ptr(text).to_str(),
+        CStr::from_ptr(options_json).to_str(),
+    ) else {
+        return std::ptr::null_mut();
+    };
+
+    // Unwinding into the caller is undefined behavior.
+    let output = panic::catch_unwind(|| find_spans_json(text, options_json))
+        .unwrap_or_else(|_| r#"{"error":"internal error"}"#.to_string());
+
+    CString::new(output)
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Release a string returned by `copyrat_find_spans`.
+///
+/// # Safety
+///
+/// `s` must be null or have been returned by `copyrat_find_spans`, and must
+/// not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn copyrat_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_spans() {
+        let text = "see https://example.com\nand /tmp/x";
+        let options = r#"{"patterns": ["url", "path"], "alphabet": "qwerty"}"#;
+
+        assert_eq!(
+            find_spans_json(text, options),
+            concat!(
+                r#"{"spans":["#,
+                r#"{"x":4,"y":0,"pattern":"url","text":"https://example.com","hint":"a"},"#,
+                r#"{"x":4,"y":1,"pattern":"path","text":"/tmp/x","hint":"s"}"#,
+                r#"]}"#,
+            )
+        );
+    }
+
+    #[test]
+    fn report_invalid_options() {
+        let error = |options| find_spans_json("", options);
+
+        assert_eq!(
+            error(r#"{"patterns": ["foo"]}"#),
+            r#"{"error":"unknown pattern name `foo`"}"#
+        );
+        assert!(error(r#"{"custom_patterns": ["(a"]}"#).starts_with(r#"{"error":"invalid custom"#));
+        assert!(error(r#"{"custom_patterns": ["a"]}"#).contains("has no capture group"));
+        assert_eq!(
+            error(r#"{"reverse": "yes"}"#),
+            r#"{"error":"`reverse` must be a boolean"}"#
+        );
+        assert_eq!(error("[]"), r#"{"error":"options must be a JSON object"}"#);
+        assert!(error("{").starts_with(r#"{"error":"invalid options"#));
+    }
+
+    #[test]
+    fn call_through_c_abi() {
+        let text = CString::new("lorem 127.0.0.1").unwrap();
+        let options = CString::new(r#"{"patterns": ["ipv4"]}"#).unwrap();
+
+        unsafe {
+            let output = copyrat_find_spans(text.as_ptr(), options.as_ptr());
+            assert!(!output.is_null());
+            assert!(CStr::from_ptr(output)
+                .to_str()
+                .unwrap()
+                .contains(r#""text":"127.0.0.1""#));
+            copyrat_free_string(output);
+
+            assert!(copyrat_find_spans(std::ptr::null(), options.as_ptr()).is_null());
+        }
+    }
+}
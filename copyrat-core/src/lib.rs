@@ -0,0 +1,21 @@
+//! The pattern matching and hinting of [tmux-copyrat], without its terminal
+//! UI, tmux integration and their dependencies.
+//!
+//! The [`textbuf::Model`] holds the spans found in some lines, along with
+//! their hints. With the `ffi` feature, the same is available from C, see the
+//! `ffi` module. This crate also compiles to WebAssembly:
+//!
+//! ```sh
+//! cargo build -p copyrat-core --features ffi --target wasm32-unknown-unknown
+//! ```
+//!
+//! [tmux-copyrat]: https://github.com/graelo/tmux-copyrat
+
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod json;
+pub mod textbuf;
+
+pub use error::Error;
+pub type Result<T> = std::result::Result<T, Error>;
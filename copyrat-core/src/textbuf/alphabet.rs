@@ -0,0 +1,355 @@
+use crate::{Error, Result};
+
+/// Catalog of available alphabets.
+///
+/// # Note
+///
+/// Keep in mind letters 'n' and 'y' are systematically removed at runtime to
+/// prevent conflict with navigation and yank/copy keys.
+const ALPHABETS: [(&str, &str); 22] = [
+    // ("abcd", "abcd"),
+    ("qwerty", "asdfqwerzxcvjklmiuopghtybn"),
+    ("qwerty-homerow", "asdfjklgh"),
+    ("qwerty-left-hand", "asdfqwerzcxv"),
+    ("qwerty-right-hand", "jkluiopmyhn"),
+    ("azerty", "qsdfazerwxcvjklmuiopghtybn"),
+    ("azerty-homerow", "qsdfjkmgh"),
+    ("azerty-left-hand", "qsdfazerwxcv"),
+    ("azerty-right-hand", "jklmuiophyn"),
+    ("qwertz", "asdfqweryxcvjkluiopmghtzbn"),
+    ("qwertz-homerow", "asdfghjkl"),
+    ("qwertz-left-hand", "asdfqweryxcv"),
+    ("qwertz-right-hand", "jkluiopmhzn"),
+    ("dvorak", "aoeuqjkxpyhtnsgcrlmwvzfidb"),
+    ("dvorak-homerow", "aoeuhtnsid"),
+    ("dvorak-left-hand", "aoeupqjkyix"),
+    ("dvorak-right-hand", "htnsgcrlmwvz"),
+    ("colemak", "arstqwfpzxcvneioluymdhgjbk"),
+    ("colemak-homerow", "arstneiodh"),
+    ("colemak-left-hand", "arstqwfpzxcv"),
+    ("colemak-right-hand", "neioluymjhk"),
+    ("numeric", NUMERIC_LETTERS),
+    (
+        "longest",
+        "aoeuqjkxpyhtnsgcrlmwvzfidb-;,~<>'@!#$%^&*+1234567890",
+    ),
+];
+
+/// Letters of the `numeric` alphabet, whose hints are the numbers from 1, see
+/// [`Alphabet::make_hints`].
+const NUMERIC_LETTERS: &str = "1234567890";
+
+/// Keys reserved for navigation ('n', 'N' and arrows) and yank/copy ('y',
+/// 'Y'), which are never used as hint letters.
+const RESERVED_LETTERS: [char; 4] = ['n', 'N', 'y', 'Y'];
+
+/// Parse a name string into `Alphabet`, used during CLI parsing.
+///
+/// # Note
+///
+/// Letters 'n' and 'N' are systematically removed to prevent conflict with
+/// navigation keys (arrows and 'n' 'N'). Letters 'y' and 'Y' are also removed
+/// to prevent conflict with yank/copy.
+pub fn parse_alphabet(src: &str) -> Result<Alphabet> {
+    let alphabet_pair = ALPHABETS.iter().find(|&(name, _letters)| name == &src);
+
+    match alphabet_pair {
+        Some((_name, letters)) => Alphabet::from_custom(letters),
+        None => Err(Error::UnknownAlphabet),
+    }
+}
+
+/// Type-safe string alphabet (newtype).
+///
+/// Use [`parse_alphabet`] to get one of the alphabets known by copyrat, or
+/// [`Alphabet::from_custom`] to provide your own letters.
+#[derive(Debug, Clone)]
+pub struct Alphabet(pub String);
+
+impl Alphabet {
+    /// Create an `Alphabet` from custom `letters`, normalized exactly like
+    /// copyrat normalizes its own alphabets.
+    ///
+    /// The reserved letters 'n' and 'y' are removed (see [`parse_alphabet`]),
+    /// as well as duplicate letters, keeping the first occurrence.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidAlphabet` if a letter is uppercase (those select
+    /// the alternate action), a whitespace or a control char, or if no letter
+    /// remains after normalization.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use copyrat_core::textbuf::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::from_custom("abcnda").unwrap();
+    /// assert_eq!(alphabet.0, "abcd");
+    ///
+    /// assert!(Alphabet::from_custom("aBc").is_err());
+    /// ```
+    pub fn from_custom(letters: &str) -> Result<Alphabet> {
+        let mut normalized = String::new();
+
+        for letter in letters.chars() {
+            if letter.is_uppercase() && !RESERVED_LETTERS.contains(&letter)
+                || letter.is_whitespace()
+                || letter.is_control()
+            {
+                return Err(Error::InvalidAlphabet(letters.to_string()));
+            }
+            if !RESERVED_LETTERS.contains(&letter) && !normalized.contains(letter) {
+                normalized.push(letter);
+            }
+        }
+
+        if normalized.is_empty() {
+            return Err(Error::InvalidAlphabet(letters.to_string()));
+        }
+
+        Ok(Alphabet(normalized))
+    }
+
+    /// Create `n` hints from the Alphabet.
+    ///
+    /// This is the hint assignment used by copyrat: given the same alphabet
+    /// and the same number of spans, this always returns the same hints, so
+    /// other tools can produce hints identical to copyrat's.
+    ///
+    /// # Guarantees
+    ///
+    /// - Exactly `n` hints are returned.
+    /// - Non-empty hints are unique and prefix-free: no hint is the prefix of
+    ///   another one, so a hint is selected as soon as its last letter is
+    ///   typed.
+    /// - Hints are ordered from the shortest to the longest: single letters
+    ///   first, in the alphabet's order, then two-letter hints grouped by
+    ///   their first letter.
+    ///
+    /// # Capacity
+    ///
+    /// An Alphabet of `m` letters can produce at most `m^2` hints. In case
+    /// this limit is exceeded, this function will generate the `n` hints from
+    /// an Alphabet which has more letters (50). This will ensure 2500 hints
+    /// can be generated, which should cover all use cases (I think even
+    /// easymotion has less).
+    ///
+    /// If more hints are needed, unfortunately, this will keep producing
+    /// empty (`""`) hints.
+    ///
+    /// # Numeric hints
+    ///
+    /// The `numeric` alphabet, `1234567890`, numbers the hints from 1
+    /// instead: `1`, `2`, ... `10`, `11`. These hints are not prefix-free:
+    /// with 12 hints, `1` is selected by typing `1` then `Enter`, while `2`
+    /// is selected as soon as it is typed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use copyrat_core::textbuf::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::from_custom("abcd").unwrap();
+    /// assert_eq!(alphabet.make_hints(6), ["a", "b", "c", "da", "db", "dc"]);
+    /// ```
+    ///
+    /// ```text
+    /// // The algorithm works as follows:
+    /// //                                  --- lead ----
+    /// // initial state                 |  a   b   c   d
+    ///
+    /// // along as we need more hints, and still have capacity, do the following
+    ///
+    /// //                                  --- lead ----  --- gen --- -------------- prev ---------------
+    /// // pick d, generate da db dc dd  |  a   b   c  (d) da db dc dd
+    /// // pick c, generate ca cb cc cd  |  a   b  (c) (d) ca cb cc cd da db dc dd
+    /// // pick b, generate ba bb bc bd  |  a  (b) (c) (d) ba bb bc bd ca cb cc cd da db dc dd
+    /// // pick a, generate aa ab ac ad  | (a) (b) (c) (d) aa ab ac ad ba bb bc bd ca cb cc cd da db dc dd
+    /// ```
+    pub fn make_hints(&self, n: usize) -> Vec<String> {
+        self.make_hints_without(n, &[])
+    }
+
+    /// Create `n` hints like [`Alphabet::make_hints`], except that the hints
+    /// made from the `longest` alphabet do not use the `excluded` letters,
+    /// such as the keys bound to the commands of the UI.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use copyrat_core::textbuf::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::from_custom("ab").unwrap();
+    /// let hints = alphabet.make_hints_without(100, &['o']);
+    /// assert!(hints.iter().all(|hint| !hint.contains('o')));
+    /// ```
+    pub fn make_hints_without(&self, n: usize, excluded: &[char]) -> Vec<String> {
+        if self.0 == NUMERIC_LETTERS {
+            return (1..=n).map(|number| number.to_string()).collect();
+        }
+
+        // Shortcut if we have enough letters in the Alphabet.
+        if self.0.len() >= n {
+            return self.0.chars().take(n).map(|c| c.to_string()).collect();
+        }
+
+        // Use the "longest" alphabet if the current alphabet cannot produce as
+        // many hints as asked.
+        let letters: Vec<char> = if self.0.len().pow(2) >= n {
+            self.0.chars().collect()
+        } else {
+            let alt_alphabet = parse_alphabet("longest").unwrap();
+            alt_alphabet
+                .0
+                .chars()
+                .filter(|letter| !excluded.contains(letter))
+                .collect()
+        };
+
+        let mut lead = letters.clone();
+        let mut prev: Vec<String> = Vec::new();
+
+        loop {
+            if lead.len() + prev.len() >= n {
+                break;
+            }
+
+            if lead.is_empty() {
+                break;
+            }
+            let prefix = lead.pop().unwrap();
+
+            // generate characters pairs
+            let gen: Vec<String> = letters
+                .iter()
+                .take(n - lead.len() - prev.len())
+                .map(|c| format!("{prefix}{c}"))
+                .collect();
+
+            // Insert gen in front of prev
+            prev.splice(..0, gen);
+        }
+
+        // Finalize by concatenating the lead and prev components, filling
+        // with "" as necessary.
+        let lead: Vec<String> = lead.iter().map(|c| c.to_string()).collect();
+
+        let filler: Vec<String> = vec![String::new(); n - lead.len() - prev.len()];
+
+        [lead, prev, filler].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_hints() {
+        let alphabet = Alphabet("abcd".to_string());
+        let hints = alphabet.make_hints(3);
+        assert_eq!(hints, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn composed_hints() {
+        let alphabet = Alphabet("abcd".to_string());
+        let hints = alphabet.make_hints(6);
+        assert_eq!(hints, ["a", "b", "c", "da", "db", "dc"]);
+    }
+
+    #[test]
+    fn composed_hints_multiple() {
+        let alphabet = Alphabet("abcd".to_string());
+        let hints = alphabet.make_hints(8);
+        assert_eq!(hints, ["a", "b", "ca", "cb", "da", "db", "dc", "dd"]);
+    }
+
+    #[test]
+    fn composed_hints_max_2() {
+        let alphabet = Alphabet("ab".to_string());
+        let hints = alphabet.make_hints(4);
+        assert_eq!(hints, ["aa", "ab", "ba", "bb"]);
+    }
+
+    #[test]
+    fn composed_hints_max_4() {
+        let alphabet = Alphabet("abcd".to_string());
+        let hints = alphabet.make_hints(13);
+        assert_eq!(
+            hints,
+            ["a", "ba", "bb", "bc", "bd", "ca", "cb", "cc", "cd", "da", "db", "dc", "dd"]
+        );
+    }
+
+    #[test]
+    fn hints_with_longest_alphabet() {
+        let alphabet = Alphabet("ab".to_string());
+        let hints = alphabet.make_hints(2500);
+        assert_eq!(hints.len(), 2500);
+        assert_eq!(&hints[..3], ["aa", "ao", "ae"]);
+        assert_eq!(&hints[2497..], ["08", "09", "00"]);
+    }
+
+    #[test]
+    fn hints_are_prefix_free() {
+        for name in ["qwerty", "dvorak-homerow", "colemak-right-hand"] {
+            let alphabet = parse_alphabet(name).unwrap();
+
+            for n in [1, 5, 10, 30, 100, 500, 2500] {
+                let hints = alphabet.make_hints(n);
+                assert_eq!(hints.len(), n);
+
+                for (i, hint) in hints.iter().enumerate() {
+                    for (j, other) in hints.iter().enumerate() {
+                        assert!(
+                            i == j || !other.starts_with(hint.as_str()),
+                            "{name}, {n} hints: `{hint}` is a prefix of `{other}`"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_hints() {
+        let alphabet = parse_alphabet("numeric").unwrap();
+        assert_eq!(alphabet.make_hints(3), ["1", "2", "3"]);
+
+        let hints = alphabet.make_hints(12);
+        assert_eq!(&hints[8..], ["9", "10", "11", "12"]);
+        assert_eq!(alphabet.make_hints(3000)[2999], "3000");
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let alphabet = Alphabet::from_custom("aaybcN").unwrap();
+        assert_eq!(alphabet.0, "abc");
+
+        assert!(Alphabet::from_custom("").is_err());
+        assert!(Alphabet::from_custom("ny").is_err());
+        assert!(Alphabet::from_custom("a b").is_err());
+        assert!(Alphabet::from_custom("aB").is_err());
+    }
+
+    #[test]
+    fn hints_without_letters() {
+        let alphabet = Alphabet("ab".to_string());
+        let hints = alphabet.make_hints_without(1000, &['o', '+']);
+        assert_eq!(&hints[..3], ["a", "e", "u"]);
+        assert!(hints.iter().all(|hint| !hint.contains(['o', '+'])));
+        assert!(hints.iter().all(|hint| !hint.is_empty()));
+    }
+
+    #[test]
+    fn hints_exceed_longest_alphabet() {
+        let alphabet = Alphabet("ab".to_string());
+        let hints = alphabet.make_hints(10000);
+        // 2500 unique hints are produced from the longest alphabet
+        // The 7500 last ones come from the filler ("" empty hints).
+        assert_eq!(hints.len(), 10000);
+        assert!(&hints[2500..].iter().all(|s| s.is_empty()));
+    }
+}
@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// SGR escape sequences (colors and attributes) of the lines of a buffer
+/// captured with them, such as the output of `tmux capture-pane -e`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Styles {
+    /// Sequences of each line, with the number of chars before them in the
+    /// line without escape sequences.
+    lines: Vec<Vec<(usize, String)>>,
+}
+
+impl Styles {
+    /// Remove the escape sequences of the `buffer`, and return the result
+    /// along with its SGR sequences.
+    ///
+    /// The other escape sequences, such as hyperlinks, are dropped.
+    pub fn parse(buffer: &str) -> (String, Styles) {
+        let mut plain = String::with_capacity(buffer.len());
+        let mut lines = vec![vec![]];
+        let mut column = 0;
+        let mut chars = buffer.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '\x1b' => match chars.next() {
+                    // Control Sequence: parameters up to a final byte.
+                    Some((_, '[')) => {
+                        let end = chars
+                            .by_ref()
+                            .find(|(_, c)| ('\x40'..='\x7e').contains(c))
+                            .map(|(i, c)| (i + c.len_utf8(), c));
+                        if let Some((end, 'm')) = end {
+                            let sequence = buffer[start..end].to_string();
+                            lines.last_mut().unwrap().push((column, sequence));
+                        }
+                    }
+                    // Operating System Command: up to BEL or ST (`ESC \`).
+                    Some((_, ']')) => {
+                        while let Some((_, c)) = chars.next() {
+                            if c == '\x07'
+                                || (c == '\x1b' && chars.next_if(|(_, c)| *c == '\\').is_some())
+                            {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                '\n' => {
+                    plain.push(c);
+                    column = 0;
+                    lines.push(vec![]);
+                }
+                c => {
+                    plain.push(c);
+                    column += 1;
+                }
+            }
+        }
+
+        (plain, Styles { lines })
+    }
+
+    /// Returns the `range` of the `line` at index `y`, without escape
+    /// sequences, preceded by the SGR sequences in effect at its start and
+    /// interleaved with those within it.
+    ///
+    /// Only the number of chars of the `line` must be the same as when it was
+    /// parsed, so that its secrets can be masked.
+    pub fn apply<'l>(&self, y: usize, line: &'l str, range: Range<usize>) -> Cow<'l, str> {
+        let styles = match self.lines.get(y) {
+            Some(styles) if !styles.is_empty() => styles,
+            _ => return Cow::Borrowed(&line[range]),
+        };
+
+        let mut styled = String::new();
+        let mut sequences = styles.iter().peekable();
+        for (column, (i, c)) in line.char_indices().enumerate() {
+            if i >= range.end {
+                break;
+            }
+            while let Some((_, sequence)) = sequences.next_if(|(offset, _)| *offset <= column) {
+                styled.push_str(sequence);
+            }
+            if i >= range.start {
+                styled.push(c);
+            }
+        }
+
+        Cow::Owned(styled)
+    }
+
+    /// Returns `true` if the line at index `y` has SGR sequences.
+    pub fn is_styled(&self, y: usize) -> bool {
+        self.lines.get(y).is_some_and(|styles| !styles.is_empty())
+    }
+}
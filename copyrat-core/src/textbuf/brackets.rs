@@ -0,0 +1,114 @@
+//! Scanner finding the content of balanced brackets, such as the arguments of
+//! a function call in a stack trace, which a regex cannot match.
+
+use std::ops::Range;
+
+/// The balanced-bracket patterns. Their pattern is the pair of brackets,
+/// followed by `inner` to capture the content only, or `outer` to capture
+/// the brackets too.
+///
+/// These are not part of `--all-patterns`, because brackets enclose the
+/// spans of the other patterns.
+pub(super) const BRACKET_PATTERNS: [(&str, &str); 3] = [
+    ("parens", "() inner"),
+    ("brackets", "[] inner"),
+    ("braces", "{} inner"),
+];
+
+/// Finds the first pair of balanced brackets in a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Brackets {
+    open: char,
+    close: char,
+    outer: bool,
+}
+
+impl Brackets {
+    /// Returns the scanner of the pattern `name`, if it is one of the
+    /// `BRACKET_PATTERNS`.
+    pub(super) fn parse(name: &str, pattern: &str) -> Option<Brackets> {
+        BRACKET_PATTERNS.iter().find(|&(n, _)| n == &name)?;
+
+        let mut chars = pattern.chars();
+        let (open, close) = (chars.next()?, chars.next()?);
+        let outer = match chars.as_str() {
+            " inner" => false,
+            " outer" => true,
+            _ => return None,
+        };
+
+        Some(Brackets { open, close, outer })
+    }
+
+    /// Returns the byte range of the first balanced pair of brackets in the
+    /// `chunk`, and the range of the captured text.
+    ///
+    /// Opening brackets without a closing one in the `chunk`, and empty
+    /// pairs, are skipped.
+    pub(super) fn find(&self, chunk: &str) -> Option<(Range<usize>, Range<usize>)> {
+        let mut starts = chunk.match_indices(self.open).map(|(start, _)| start);
+
+        starts.find_map(|start| {
+            let content_start = start + self.open.len_utf8();
+            let mut depth = 0;
+
+            for (offset, ch) in chunk[content_start..].char_indices() {
+                if ch == self.open {
+                    depth += 1;
+                } else if ch == self.close {
+                    if depth > 0 {
+                        depth -= 1;
+                        continue;
+                    }
+                    let content_end = content_start + offset;
+                    if content_end == content_start {
+                        return None;
+                    }
+                    let end = content_end + self.close.len_utf8();
+                    let capture = if self.outer {
+                        start..end
+                    } else {
+                        content_start..content_end
+                    };
+                    return Some((start..end, capture));
+                }
+            }
+
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bracket_patterns() {
+        assert_eq!(
+            Brackets::parse("parens", "() outer"),
+            Some(Brackets {
+                open: '(',
+                close: ')',
+                outer: true,
+            })
+        );
+        assert_eq!(Brackets::parse("custom", "() inner"), None);
+        assert_eq!(Brackets::parse("braces", r"(\{.*\})"), None);
+    }
+
+    #[test]
+    fn find_balanced_brackets() {
+        let parens = Brackets::parse("parens", "() inner").unwrap();
+        let line = "at foo(bar(1, 2), baz) in main()";
+
+        let (range, capture) = parens.find(line).unwrap();
+        assert_eq!(&line[range], "(bar(1, 2), baz)");
+        assert_eq!(&line[capture], "bar(1, 2), baz");
+
+        // Unbalanced and empty pairs are skipped.
+        let (_, capture) = parens.find("f(a, () g(b)").unwrap();
+        assert_eq!(capture, 10..11);
+        assert_eq!(parens.find("main() (a"), None);
+    }
+}
@@ -0,0 +1,1469 @@
+pub mod alphabet;
+mod ansi;
+mod brackets;
+mod model;
+mod raw_span;
+pub mod regexes;
+mod sections;
+mod span;
+
+pub use ansi::Styles;
+pub use model::Model;
+pub use model::{find_raw_spans, join_wrapped_lines};
+pub use raw_span::RawSpan;
+pub use sections::Sections;
+pub use span::Span;
+
+#[cfg(test)]
+mod tests {
+    use super::alphabet::Alphabet;
+    use super::model::Model;
+    use super::{Sections, Styles};
+
+    #[test]
+    fn join_wrapped_url() {
+        // The first line is 20 characters wide.
+        let buffer = "see https://example.\ncom/a/long/path ok\nshort\n";
+        let joined = super::join_wrapped_lines(buffer, 20);
+        assert_eq!(joined, "see https://example.com/a/long/path ok\nshort\n");
+
+        let lines = joined.split('\n').collect::<Vec<_>>();
+        let named_pat = vec![super::regexes::parse_pattern_name("url").unwrap()];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            false,
+            &named_pat,
+            &custom,
+            false,
+            false,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "https://example.com/a/long/path");
+    }
+
+    #[test]
+    fn match_reverse() {
+        let buffer = "lorem 127.0.0.1 lorem 255.255.255.255 lorem 127.0.0.1 lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().hint, "a");
+        assert_eq!(spans.last().unwrap().hint, "c");
+    }
+
+    #[test]
+    fn match_unique() {
+        let buffer = "lorem 127.0.0.1 lorem 255.255.255.255 lorem 127.0.0.1 lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = true;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().hint, "a");
+        assert_eq!(spans.last().unwrap().hint, "a");
+    }
+
+    #[test]
+    fn invalid_patterns_are_errors() {
+        let lines = ["$ ls", "a1b2c3d"];
+        let alphabet = Alphabet("abcd".to_string());
+        let model = |custom: &[String], prompt: Option<&str>| {
+            Model::new(
+                &lines,
+                &alphabet,
+                true,
+                &[],
+                custom,
+                false,
+                false,
+                prompt,
+                &[],
+            )
+            .map(|model| model.spans.len())
+        };
+
+        assert_eq!(model(&[], Some("^\\$ ")).unwrap(), 1);
+        let err = model(&[], Some("^[$")).unwrap_err();
+        assert!(matches!(err, crate::Error::InvalidPattern { .. }));
+        let err = model(&["(a".to_string()], None).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid pattern `(a`: unclosed group");
+        let err = model(&["a".to_string()], None).unwrap_err();
+        assert!(matches!(err, crate::Error::PatternWithoutGroup(_)));
+    }
+
+    #[test]
+    fn match_deterministic_order() {
+        let buffer = "abc 127.0.0.1\n/tmp/x abc\nlorem 10.0.0.1 abc";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let named_pat = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let find = |custom: &[String]| {
+            Model::new(
+                &lines,
+                &alphabet,
+                true,
+                &named_pat,
+                custom,
+                false,
+                false,
+                None,
+                &[],
+            )
+            .unwrap()
+            .spans
+            .into_iter()
+            .map(|span| (span.y, span.x, span.text.to_string(), span.hint))
+            .collect::<Vec<_>>()
+        };
+
+        // Both custom patterns match at the same offset: the first one wins.
+        let custom = vec!["(a)bc".to_string(), "(ab)c".to_string()];
+        let spans = find(&custom);
+        assert_eq!(spans, find(&custom));
+        assert!(spans
+            .windows(2)
+            .all(|pair| (pair[0].0, pair[0].1) < (pair[1].0, pair[1].1)));
+        let texts: Vec<&str> = spans.iter().map(|span| span.2.as_str()).collect();
+        assert_eq!(texts, ["a", "127.0.0.1", "/tmp/x", "a", "10.0.0.1", "a"]);
+
+        let reversed = vec!["(ab)c".to_string(), "(a)bc".to_string()];
+        assert!(find(&reversed).iter().all(|span| span.2 != "a"));
+    }
+
+    #[test]
+    fn match_docker() {
+        let buffer = "latest sha256:30557a29d5abc51e5f1d5b472e79b7e296f595abcf19fe6b9199dbbc809c6ff4 20 hours ago";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans.first().unwrap().text,
+            "30557a29d5abc51e5f1d5b472e79b7e296f595abcf19fe6b9199dbbc809c6ff4"
+        );
+    }
+
+    #[test]
+    fn match_ansi_colors() {
+        let buffer =
+        "path: [32m/var/log/nginx.log[m\npath: [32mtest/log/nginx-2.log:32[mfolder/.nginx@4df2.log";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = true;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().text, "/var/log/nginx.log");
+        assert_eq!(spans.get(1).unwrap().text, "test/log/nginx-2.log:32");
+        assert_eq!(spans.get(2).unwrap().text, "folder/.nginx@4df2.log");
+    }
+
+    #[test]
+    fn match_paths() {
+        let buffer =
+        "Lorem /tmp/foo/bar_lol, lorem\n Lorem /var/log/boot-strap.log lorem ../log/kern.log lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().text, "/tmp/foo/bar_lol");
+        assert_eq!(spans.get(1).unwrap().text, "/var/log/boot-strap.log");
+        assert_eq!(spans.get(2).unwrap().text, "../log/kern.log");
+    }
+
+    #[test]
+    fn match_home() {
+        let buffer = "Lorem ~/.gnu/.config.txt, lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.first().unwrap().text, "~/.gnu/.config.txt");
+    }
+
+    #[test]
+    fn match_uuids() {
+        let buffer = "Lorem ipsum 123e4567-e89b-12d3-a456-426655440000 lorem\n Lorem lorem lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+    }
+
+    #[test]
+    fn match_shas() {
+        let buffer = "Lorem fd70b5695 5246ddf f924213 lorem\n Lorem 973113963b491874ab2e372ee60d4b4cb75f717c lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans.first().unwrap().text, "fd70b5695");
+        assert_eq!(spans.get(1).unwrap().text, "5246ddf");
+        assert_eq!(spans.get(2).unwrap().text, "f924213");
+        assert_eq!(
+            spans.get(3).unwrap().text,
+            "973113963b491874ab2e372ee60d4b4cb75f717c"
+        );
+    }
+
+    #[test]
+    fn match_ipv4s() {
+        let buffer = "Lorem ipsum 127.0.0.1 lorem\n Lorem 255.255.10.255 lorem 127.0.0.1 lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().pattern, "ipv4");
+        assert_eq!(spans.first().unwrap().text, "127.0.0.1");
+        assert_eq!(spans.get(1).unwrap().pattern, "ipv4");
+        assert_eq!(spans.get(1).unwrap().text, "255.255.10.255");
+        assert_eq!(spans.get(2).unwrap().pattern, "ipv4");
+        assert_eq!(spans.get(2).unwrap().text, "127.0.0.1");
+    }
+
+    #[test]
+    fn match_socket_addrs() {
+        let buffer = "ip 127.0.0.1:8080 v6 [::1]:443 [fe80::1%eth0]:22\n host.example.com:9000 localhost:3000";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let texts: Vec<(&str, &str)> = spans.iter().map(|span| (span.pattern, span.text)).collect();
+        assert_eq!(
+            texts,
+            [
+                ("socket-addr", "127.0.0.1:8080"),
+                ("socket-addr", "[::1]:443"),
+                ("socket-addr", "[fe80::1%eth0]:22"),
+                ("socket-addr", "host.example.com:9000"),
+                ("socket-addr", "localhost:3000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_ipv6s() {
+        let buffer = "Lorem ipsum fe80::2:202:fe4 lorem\n Lorem 2001:67c:670:202:7ba8:5e41:1591:d723 lorem fe80::2:1 lorem ipsum fe80:22:312:fe::1%eth0";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans.first().unwrap().text, "fe80::2:202:fe4");
+        assert_eq!(
+            spans.get(1).unwrap().text,
+            "2001:67c:670:202:7ba8:5e41:1591:d723"
+        );
+        assert_eq!(spans.get(2).unwrap().text, "fe80::2:1");
+        assert_eq!(spans.get(3).unwrap().text, "fe80:22:312:fe::1%eth0");
+    }
+
+    #[test]
+    fn match_markdown_urls() {
+        let buffer =
+            "Lorem ipsum [link](https://github.io?foo=bar) ![](http://cdn.com/img.jpg) lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans.first().unwrap().pattern, "markdown-url");
+        assert_eq!(spans.first().unwrap().text, "https://github.io?foo=bar");
+        assert_eq!(spans.get(1).unwrap().pattern, "markdown-url");
+        assert_eq!(spans.get(1).unwrap().text, "http://cdn.com/img.jpg");
+    }
+
+    #[test]
+    fn match_urls() {
+        let buffer = "Lorem ipsum https://www.rust-lang.org/tools lorem\
+                      Lorem ipsumhttps://crates.io lorem https://github.io?foo=bar \
+                      lorem ssh://github.io lorem s3://mybucket/mypath \
+                      lorem gs://mybucket.domain/mypath lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 6);
+        assert_eq!(
+            spans.first().unwrap().text,
+            "https://www.rust-lang.org/tools"
+        );
+        assert_eq!(spans.first().unwrap().pattern, "url");
+        assert_eq!(spans.get(1).unwrap().text, "https://crates.io");
+        assert_eq!(spans.get(1).unwrap().pattern, "url");
+        assert_eq!(spans.get(2).unwrap().text, "https://github.io?foo=bar");
+        assert_eq!(spans.get(2).unwrap().pattern, "url");
+        assert_eq!(spans.get(3).unwrap().text, "ssh://github.io");
+        assert_eq!(spans.get(3).unwrap().pattern, "url");
+        assert_eq!(spans.get(4).unwrap().text, "s3://mybucket/mypath");
+        assert_eq!(spans.get(4).unwrap().pattern, "url");
+        assert_eq!(spans.get(5).unwrap().text, "gs://mybucket.domain/mypath");
+        assert_eq!(spans.get(5).unwrap().pattern, "url");
+    }
+
+    #[test]
+    fn match_git_patterns() {
+        let buffer = "On branch feature/foo-bar\nYour branch is ahead of 'origin/feature/foo-bar' by 1 commit.\n* main\n  fix/v1.2\n  remotes/upstream/main\nab12cd3 (HEAD -> main, origin/main) Merge\nremote: git@github.com:org/repo.git";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let texts: Vec<(&str, &str)> = spans.iter().map(|span| (span.pattern, span.text)).collect();
+        assert_eq!(
+            texts,
+            [
+                ("git-branch", "feature/foo-bar"),
+                // The quoted string starts first.
+                ("quoted-single", "origin/feature/foo-bar"),
+                ("git-branch", "main"),
+                ("git-branch", "fix/v1.2"),
+                // In `git branch --all`, the remote refs are listed too.
+                ("git-branch", "upstream/main"),
+                ("sha", "ab12cd3"),
+                ("git-branch", "main"),
+                ("git-remote-ref", "origin/main"),
+                // `url` has a higher priority.
+                ("url", "git@github.com:org/repo.git"),
+            ]
+        );
+
+        // Otherwise, `git-ssh-url` stops before a trailing dot.
+        let lines = ["clone git@gitlab.example.com:group/sub/repo.git."];
+        let named_pat = vec![super::regexes::parse_pattern_name("git-ssh-url").unwrap()];
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            false,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "git@gitlab.example.com:group/sub/repo.git");
+    }
+
+    #[test]
+    fn match_k8s_patterns() {
+        let buffer = "web-7d4f8c9b6f-x2kqp   1/1   Running   0   3d\npod/nginx-12345 deleted\ndeployment.apps/web-api restarted\n    Image: registry.example.com:5000/team/api:1.4.2\n    Image ID: docker.io/library/nginx@sha256:30557a29d5abc51e5f1d5b472e79b7e296f595abcf19fe6b9199dbbc809c6ff4\nsrc/main.rs:12 some-long-hyphenated-words";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let texts: Vec<(&str, &str)> = spans.iter().map(|span| (span.pattern, span.text)).collect();
+        assert_eq!(
+            texts,
+            [
+                ("k8s-pod", "web-7d4f8c9b6f-x2kqp"),
+                ("path", "1/1"),
+                ("k8s-resource", "pod/nginx-12345"),
+                ("k8s-resource", "deployment.apps/web-api"),
+                (
+                    "container-image",
+                    "registry.example.com:5000/team/api:1.4.2"
+                ),
+                (
+                    "container-image",
+                    "docker.io/library/nginx@sha256:30557a29d5abc51e5f1d5b472e79b7e296f595abcf19fe6b9199dbbc809c6ff4"
+                ),
+                // Neither an image nor a pod.
+                ("path-line", "src/main.rs:12"),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_emails() {
+        let buffer =
+            "Lorem ipsum <first.last+social@example.com> john@server.department.company.com lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans.first().unwrap().pattern, "email");
+        assert_eq!(spans.first().unwrap().text, "first.last+social@example.com");
+        assert_eq!(spans.get(1).unwrap().pattern, "email");
+        assert_eq!(
+            spans.get(1).unwrap().text,
+            "john@server.department.company.com"
+        );
+    }
+
+    #[test]
+    fn match_pointer_addresses() {
+        let buffer = "Lorem 0xfd70b5695 0x5246ddf lorem\n Lorem 0x973113tlorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().pattern, "pointer-address");
+        assert_eq!(spans.first().unwrap().text, "0xfd70b5695");
+        assert_eq!(spans.get(1).unwrap().pattern, "pointer-address");
+        assert_eq!(spans.get(1).unwrap().text, "0x5246ddf");
+        assert_eq!(spans.get(2).unwrap().pattern, "pointer-address");
+        assert_eq!(spans.get(2).unwrap().text, "0x973113");
+    }
+
+    #[test]
+    fn match_hex_colors() {
+        let buffer = "Lorem #fd7b56 lorem #FF00FF\n Lorem #00fF05 lorem #abcd00 lorem #afRR00";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans.first().unwrap().text, "#fd7b56");
+        assert_eq!(spans.get(1).unwrap().text, "#FF00FF");
+        assert_eq!(spans.get(2).unwrap().text, "#00fF05");
+        assert_eq!(spans.get(3).unwrap().text, "#abcd00");
+    }
+
+    #[test]
+    fn match_ipfs() {
+        let buffer = "Lorem QmRdbNSxDJBXmssAc9fvTtux4duptMvfSGiGuq6yHAQVKQ lorem Qmfoobar";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(
+            spans.first().unwrap().text,
+            "QmRdbNSxDJBXmssAc9fvTtux4duptMvfSGiGuq6yHAQVKQ"
+        );
+    }
+
+    #[test]
+    fn match_process_port() {
+        let buffer = "Lorem 5695 52463 lorem\n Lorem 973113 lorem 99999 lorem 8888 lorem\n   23456 lorem 5432 lorem 23444";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 8);
+    }
+
+    #[test]
+    fn match_diff_a() {
+        let buffer = "Lorem lorem\n--- a/src/main.rs";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.first().unwrap().pattern, "diff-a");
+        assert_eq!(spans.first().unwrap().text, "src/main.rs");
+    }
+
+    #[test]
+    fn match_diff_b() {
+        let buffer = "Lorem lorem\n+++ b/src/main.rs";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.first().unwrap().pattern, "diff-b");
+        assert_eq!(spans.first().unwrap().text, "src/main.rs");
+    }
+
+    #[test]
+    fn match_datetime() {
+        let buffer = "12 days ago = 2021-03-04T12:23:34 text";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans.first().unwrap().pattern, "datetime");
+        assert_eq!(spans.first().unwrap().text, "2021-03-04T12:23:34");
+    }
+
+    #[test]
+    fn match_quoted_string() {
+        let buffer =
+            r#"Lorem 'first string' and "second string" and `rustc --explain E0223` ipsum."#;
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        let use_all_patterns = false;
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat = vec![
+            parse_pattern_name("quoted-single").unwrap(),
+            parse_pattern_name("quoted-double").unwrap(),
+            parse_pattern_name("quoted-backtick").unwrap(),
+        ];
+
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans.first().unwrap().text, "first string");
+        assert_eq!(spans.get(1).unwrap().text, "second string");
+        assert_eq!(spans.get(2).unwrap().text, "rustc --explain E0223");
+    }
+
+    #[test]
+    fn match_quoted_string_with_escapes() {
+        let buffer = r#"echo "a \"quoted\" word" 'it\'s' `a \` b`"#;
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        use crate::textbuf::regexes::parse_pattern_name;
+        let names = ["quoted-single", "quoted-double", "quoted-backtick"];
+        let named_pat: Vec<_> = names
+            .iter()
+            .map(|name| parse_pattern_name(name).unwrap())
+            .collect();
+        let with_quotes: Vec<_> = named_pat
+            .iter()
+            .map(|pattern| pattern.clone().including_quotes())
+            .collect();
+
+        let alphabet = Alphabet("abcd".to_string());
+        let texts = |named_pat| {
+            Model::new(
+                &lines,
+                &alphabet,
+                false,
+                named_pat,
+                &[],
+                false,
+                false,
+                None,
+                &[],
+            )
+            .unwrap()
+            .spans
+            .iter()
+            .map(|span| span.text)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            texts(&named_pat),
+            [r#"a \"quoted\" word"#, r"it\'s", r"a \` b"]
+        );
+        assert_eq!(
+            texts(&with_quotes),
+            [r#""a \"quoted\" word""#, r"'it\'s'", r"`a \` b`"]
+        );
+
+        // The composite pattern matches the three quotes.
+        let quoted_any = [parse_pattern_name("quoted-any").unwrap()];
+        let quoted_any_with_quotes = [quoted_any[0].clone().including_quotes()];
+        assert_eq!(
+            texts(&quoted_any),
+            [r#"a \"quoted\" word"#, r"it\'s", r"a \` b"]
+        );
+        assert_eq!(
+            texts(&quoted_any_with_quotes),
+            [r#""a \"quoted\" word""#, r"'it\'s'", r"`a \` b`"]
+        );
+    }
+
+    #[test]
+    fn apply_custom_pattern_flags() {
+        use crate::textbuf::regexes::{apply_pattern_flags, parse_pattern_flags};
+
+        assert_eq!(apply_pattern_flags(r"(a\d+)", "im"), r"(?im)(a\d+)");
+        assert_eq!(apply_pattern_flags("(id)", "w"), r"\b(?:(id))\b");
+        // Smart case ignores the escapes and the names of the groups.
+        assert_eq!(
+            apply_pattern_flags(r"(?P<Id>\S+\p{Greek})", "S"),
+            r"(?i)(?P<Id>\S+\p{Greek})"
+        );
+        assert_eq!(apply_pattern_flags("(Id)", "S"), "(Id)");
+
+        assert!(parse_pattern_flags("iwS").is_ok());
+        assert!(matches!(
+            parse_pattern_flags("ix"),
+            Err(crate::Error::UnknownPatternFlag('x'))
+        ));
+    }
+
+    #[test]
+    fn match_pattern_examples() {
+        use crate::textbuf::regexes::{all_patterns, parse_pattern_name, PATTERN_EXAMPLES};
+
+        let alphabet = Alphabet("abcd".to_string());
+
+        for (name, line, expected) in PATTERN_EXAMPLES {
+            let named_pat = vec![parse_pattern_name(name).unwrap()];
+            let lines = [line];
+            let spans = Model::new(
+                &lines,
+                &alphabet,
+                false,
+                &named_pat,
+                &[],
+                false,
+                false,
+                None,
+                &[],
+            )
+            .unwrap()
+            .spans;
+
+            let texts: Vec<&str> = spans.iter().map(|span| span.text).collect();
+            assert_eq!(texts, [expected], "example of the `{name}` pattern");
+        }
+
+        // Every pattern has an example, and a row in the README table.
+        let readme = include_str!("../../../README.md");
+        let names = all_patterns()
+            .into_iter()
+            .map(|pattern| pattern.0)
+            .chain(super::brackets::BRACKET_PATTERNS.map(|(name, _)| name.to_string()))
+            .chain(super::regexes::COMPOSITE_PATTERNS.map(|(name, _)| name.to_string()));
+        for name in names {
+            assert!(
+                PATTERN_EXAMPLES.iter().any(|(n, _, _)| n == &name),
+                "no example for the `{name}` pattern"
+            );
+            assert!(
+                readme
+                    .lines()
+                    .any(|line| line.starts_with('|') && line.contains(&format!("`{name}`"))),
+                "no row for the `{name}` pattern in the README"
+            );
+        }
+    }
+
+    #[test]
+    fn match_pattern_priority() {
+        let buffer = "see [docs](https://example.com) or deadbeef1234";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat =
+            ["markdown-url", "url", "sha", "digits"].map(|name| parse_pattern_name(name).unwrap());
+        let alphabet = Alphabet("abcd".to_string());
+        let find = |priority: &[String]| {
+            Model::new(
+                &lines,
+                &alphabet,
+                false,
+                &named_pat,
+                &[],
+                false,
+                false,
+                None,
+                priority,
+            )
+            .unwrap()
+            .spans
+            .iter()
+            .map(|span| (span.pattern, span.text))
+            .collect::<Vec<_>>()
+        };
+
+        // By default, the match starting first wins.
+        assert_eq!(
+            find(&[]),
+            [
+                ("markdown-url", "https://example.com"),
+                ("sha", "deadbeef1234")
+            ]
+        );
+        assert_eq!(
+            find(&["url".to_string(), "digits".to_string()]),
+            [("url", "https://example.com"), ("digits", "1234")]
+        );
+    }
+
+    #[test]
+    fn match_named_group() {
+        let buffer = "ticket id=42 (see id=7)";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let custom = vec![
+            r"(?P<key>id)=(?P<match>\d+)".to_string(),
+            r"\((see) id=\d+\)".to_string(),
+        ];
+        let alphabet = Alphabet("abcd".to_string());
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            false,
+            &[],
+            &custom,
+            false,
+            false,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let texts: Vec<&str> = spans.iter().map(|span| span.text).collect();
+        assert_eq!(texts, ["42", "see"]);
+        assert_eq!(spans[0].x, 10);
+
+        use crate::textbuf::regexes::check_capture_group;
+        assert!(check_capture_group(&regex::Regex::new(r"id=\d+").unwrap()).is_err());
+        assert!(check_capture_group(&regex::Regex::new(r"id=(?P<match>\d+)").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn match_version_groups() {
+        let buffer = "release v1.2.3-rc1+build5, then 2.0.1";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat = vec![parse_pattern_name("version").unwrap()];
+        let alphabet = Alphabet("abcd".to_string());
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            false,
+            &named_pat,
+            &[],
+            false,
+            false,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let found: Vec<_> = spans
+            .iter()
+            .map(|span| (span.text, span.groups.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "v1.2.3-rc1+build5",
+                    vec![("version".to_string(), "1.2.3-rc1")]
+                ),
+                ("2.0.1", vec![("version".to_string(), "2.0.1")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_balanced_brackets() {
+        let buffer = "  at parse(Config { name: [1, 2] }, opts) in src/main.rs";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat = vec![
+            parse_pattern_name("parens").unwrap(),
+            parse_pattern_name("path").unwrap(),
+        ];
+        let outer: Vec<_> = named_pat
+            .iter()
+            .map(|pattern| pattern.clone().including_brackets())
+            .collect();
+
+        let alphabet = Alphabet("abcd".to_string());
+        let spans = |named_pat| {
+            Model::new(
+                &lines,
+                &alphabet,
+                false,
+                named_pat,
+                &[],
+                false,
+                false,
+                None,
+                &[],
+            )
+            .unwrap()
+            .spans
+            .iter()
+            .map(|span| (span.x, span.pattern, span.text))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            spans(&named_pat),
+            [
+                (11, "parens", "Config { name: [1, 2] }, opts"),
+                (45, "path", "src/main.rs"),
+            ]
+        );
+        assert_eq!(
+            spans(&outer)[0],
+            (10, "parens", "(Config { name: [1, 2] }, opts)")
+        );
+    }
+
+    #[test]
+    fn match_commandline_args() {
+        let buffer =
+            "command --arg arg1 --arg=arg2 --arg arg3-long -x hashes -a -u -l -x others\n'";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        let use_all_patterns = false;
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat = vec![parse_pattern_name("command-line-args").unwrap()];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 5);
+        assert_eq!(spans.first().unwrap().text, "arg1");
+        assert_eq!(spans.get(1).unwrap().text, "arg2");
+        assert_eq!(spans.get(2).unwrap().text, "arg3-long");
+        assert_eq!(spans.get(3).unwrap().text, "hashes");
+        assert_eq!(spans.get(4).unwrap().text, "others");
+    }
+
+    #[test]
+    fn priority_between_regexes() {
+        let buffer = "Lorem [link](http://foo.bar) ipsum CUSTOM-52463 lorem ISSUE-123 lorem\nLorem /var/fd70b569/9999.log 52463 lorem\n Lorem 973113 lorem 123e4567-e89b-12d3-a456-426655440000 lorem 8888 lorem\n  https://crates.io/23456/fd70b569 lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom: Vec<String> = ["(CUSTOM-[0-9]{4,})", "(ISSUE-[0-9]{3})"]
+            .iter()
+            .map(|&s| s.to_string())
+            .collect();
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 9);
+        assert_eq!(spans.first().unwrap().text, "http://foo.bar");
+        assert_eq!(spans.get(1).unwrap().text, "CUSTOM-52463");
+        assert_eq!(spans.get(2).unwrap().text, "ISSUE-123");
+        assert_eq!(spans.get(3).unwrap().text, "/var/fd70b569/9999.log");
+        assert_eq!(spans.get(4).unwrap().text, "52463");
+        assert_eq!(spans.get(5).unwrap().text, "973113");
+        assert_eq!(
+            spans.get(6).unwrap().text,
+            "123e4567-e89b-12d3-a456-426655440000"
+        );
+        assert_eq!(spans.get(7).unwrap().text, "8888");
+        assert_eq!(
+            spans.get(8).unwrap().text,
+            "https://crates.io/23456/fd70b569"
+        );
+    }
+
+    #[test]
+    fn named_patterns() {
+        let buffer = "Lorem [link](http://foo.bar) ipsum CUSTOM-52463 lorem ISSUE-123 lorem\nLorem /var/fd70b569/9999.log 52463 lorem\n Lorem 973113 lorem 123e4567-e89b-12d3-a456-426655440000 lorem 8888 lorem\n  https://crates.io/23456/fd70b569 lorem";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+
+        let use_all_patterns = false;
+        use crate::textbuf::regexes::parse_pattern_name;
+        let named_pat = vec![parse_pattern_name("url").unwrap()];
+
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans.first().unwrap().text, "http://foo.bar");
+        assert_eq!(
+            spans.get(1).unwrap().text,
+            "https://crates.io/23456/fd70b569"
+        );
+    }
+
+    #[test]
+    fn match_anchors_in_whole_line() {
+        let buffer = "key value other\nnext";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = false;
+        let named_pat = vec![];
+        // The first word of each line only, even after a span.
+        let custom = vec![r"^(\w+)".to_string()];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            None,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        let texts: Vec<&str> = spans.iter().map(|span| span.text).collect();
+        assert_eq!(texts, ["key", "next"]);
+    }
+
+    #[test]
+    fn match_outside_prompt_lines() {
+        let buffer = "user@host ~ $ curl https://example.com/a
+https://example.com/b
+user@host ~ $ ";
+        let lines = buffer.split('\n').collect::<Vec<_>>();
+        let use_all_patterns = true;
+        let named_pat = vec![];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let reverse = false;
+        let unique_hint = false;
+        let prompt_pattern = Some(r"^\S+ \S+ \$ ");
+        let spans = Model::new(
+            &lines,
+            &alphabet,
+            use_all_patterns,
+            &named_pat,
+            &custom,
+            reverse,
+            unique_hint,
+            prompt_pattern,
+            &[],
+        )
+        .unwrap()
+        .spans;
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].text, "https://example.com/b");
+        assert_eq!(spans[0].hint, "a");
+    }
+
+    #[test]
+    fn filter_spans() {
+        let lines = [
+            "lorem 127.0.0.1 10.0.0.1",
+            "lorem 10.0.0.2 https://Example.com",
+        ];
+        let custom = vec![];
+        let alphabet = Alphabet("abcd".to_string());
+        let model = Model::new(
+            &lines,
+            &alphabet,
+            true,
+            &[],
+            &custom,
+            false,
+            false,
+            None,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(model.spans.len(), 4);
+
+        let filtered = model.filtered("10.0", &alphabet, false);
+        let texts: Vec<&str> = filtered.spans.iter().map(|span| span.text).collect();
+        assert_eq!(texts, ["10.0.0.1", "10.0.0.2"]);
+        // The hints are recomputed for the remaining spans.
+        let hints: Vec<&str> = filtered
+            .spans
+            .iter()
+            .map(|span| span.hint.as_str())
+            .collect();
+        assert_eq!(hints, ["a", "b"]);
+        assert!(filtered.lookup_trie.get(&['b']).is_some());
+
+        let filtered = model.filtered("example", &alphabet, false);
+        assert_eq!(filtered.spans[0].text, "https://Example.com");
+        assert!(model.filtered("nope", &alphabet, false).spans.is_empty());
+    }
+
+    #[test]
+    fn locate_pane_lines() {
+        let buffers = [
+            ("── %1 ──".to_string(), "a\nb\n".to_string()),
+            ("── %2 ──".to_string(), "c".to_string()),
+        ];
+        let (buffer, sections) = Sections::concat(&buffers);
+        assert_eq!(buffer, "── %1 ──\na\nb\n── %2 ──\nc");
+
+        assert_eq!(sections.locate(0), None);
+        assert_eq!(sections.locate(1), Some((0, 0)));
+        assert_eq!(sections.locate(2), Some((0, 1)));
+        assert_eq!(sections.locate(3), None);
+        assert_eq!(sections.locate(4), Some((1, 0)));
+        assert_eq!(Sections::default().locate(0), None);
+    }
+
+    #[test]
+    fn parse_styles() {
+        let buffer = "\x1b[1;31merror\x1b[0m: see \x1b]8;;https://a.b\x1b\\link\x1b]8;;\x07\nplain\x1b[K\n\x1b[32m✓ ok";
+        let (plain, styles) = Styles::parse(buffer);
+        assert_eq!(plain, "error: see link\nplain\n✓ ok");
+
+        let lines = plain.split('\n').collect::<Vec<_>>();
+        assert_eq!(
+            styles.apply(0, lines[0], 0..lines[0].len()),
+            "\x1b[1;31merror\x1b[0m: see link"
+        );
+        // The sequences before the range are in effect at its start.
+        assert_eq!(styles.apply(0, lines[0], 2..7), "\x1b[1;31mror\x1b[0m: ");
+        assert_eq!(styles.apply(0, lines[0], 7..10), "\x1b[1;31m\x1b[0msee");
+        assert!(!styles.is_styled(1));
+        assert_eq!(styles.apply(1, lines[1], 0..5), "plain");
+        assert_eq!(styles.apply(2, lines[2], 4..6), "\x1b[32mok");
+        // With the same number of chars, such as a masked secret.
+        assert_eq!(styles.apply(2, "* **", 0..4), "\x1b[32m* **");
+    }
+}
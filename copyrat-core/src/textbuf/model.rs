@@ -0,0 +1,519 @@
+use std::collections;
+use std::ops::Range;
+
+use regex::{Regex, RegexSet};
+use sequence_trie::SequenceTrie;
+
+use super::alphabet::Alphabet;
+use super::brackets::Brackets;
+use super::raw_span::RawSpan;
+use super::regexes::{self, NamedPattern, EXCLUDE_PATTERNS, MATCH_GROUP, PATTERNS};
+use super::span::Span;
+use crate::Result;
+
+/// Holds data for the `Ui`.
+pub struct Model<'a> {
+    pub lines: &'a [&'a str],
+    pub reverse: bool,
+    pub spans: Vec<Span<'a>>,
+    pub lookup_trie: SequenceTrie<char, usize>,
+    /// Letters which no hint uses, see [`Model::from_raw_spans`].
+    excluded_letters: &'a [char],
+}
+
+impl<'a> Model<'a> {
+    #[allow(clippy::too_many_arguments)] // one per search option
+    pub fn new(
+        lines: &'a [&'a str],
+        alphabet: &'a Alphabet,
+        use_all_patterns: bool,
+        named_patterns: &'a [NamedPattern],
+        custom_patterns: &'a [String],
+        reverse: bool,
+        unique_hint: bool,
+        prompt_pattern: Option<&str>,
+        pattern_priority: &[String],
+    ) -> Result<Model<'a>> {
+        let raw_spans = find_raw_spans(
+            lines,
+            named_patterns,
+            custom_patterns,
+            use_all_patterns,
+            prompt_pattern,
+            pattern_priority,
+            &mut Regex::new,
+            &mut |patterns| RegexSet::new(patterns),
+        )?;
+
+        Ok(Model::from_raw_spans(
+            lines,
+            raw_spans,
+            alphabet,
+            &[],
+            reverse,
+            unique_hint,
+        ))
+    }
+
+    /// Build the model from the `raw_spans` found in the `lines`, for instance
+    /// by the copyrat server.
+    ///
+    /// The hints do not use the `excluded_letters`, such as the keys bound to
+    /// the commands of the UI, even when made from the `longest` alphabet
+    /// (see [`Alphabet::make_hints_without`]).
+    pub fn from_raw_spans(
+        lines: &'a [&'a str],
+        mut raw_spans: Vec<RawSpan<'a>>,
+        alphabet: &'a Alphabet,
+        excluded_letters: &'a [char],
+        reverse: bool,
+        unique_hint: bool,
+    ) -> Model<'a> {
+        if reverse {
+            raw_spans.reverse();
+        }
+
+        let mut spans = associate_hints(&raw_spans, alphabet, excluded_letters, unique_hint);
+
+        if reverse {
+            spans.reverse();
+        }
+
+        let lookup_trie = build_lookup_trie(&spans);
+
+        Model {
+            // buffer,
+            lines,
+            reverse,
+            spans,
+            lookup_trie,
+            excluded_letters,
+        }
+    }
+
+    /// Returns the model of the spans whose text contains the `query`,
+    /// ignoring case, with new hints.
+    pub fn filtered(&self, query: &str, alphabet: &'a Alphabet, unique_hint: bool) -> Model<'a> {
+        let query = query.to_lowercase();
+        let raw_spans = self
+            .spans
+            .iter()
+            .filter(|span| span.text.to_lowercase().contains(&query))
+            .map(|span| RawSpan {
+                x: span.x,
+                y: span.y,
+                pattern: span.pattern,
+                text: span.text,
+                groups: span.groups.clone(),
+            })
+            .collect();
+
+        Model::from_raw_spans(
+            self.lines,
+            raw_spans,
+            alphabet,
+            self.excluded_letters,
+            self.reverse,
+            unique_hint,
+        )
+    }
+}
+
+/// Minimum number of regexes for which a `RegexSet` first tells which
+/// patterns match each line, see [`find_raw_spans`].
+const MIN_SET_PATTERNS: usize = 4;
+
+/// Internal function that searches the model's lines for pattern matches.
+/// Returns a vector of `RawSpan` (text, location, pattern id) without
+/// an associated hint. The hint is attached to `Span`, not to `RawSpan`.
+///
+/// # Notes
+///
+/// When the matches of several patterns overlap, the one ranked first in
+/// `pattern_priority` wins (the custom patterns are named `custom`), then the
+/// one starting first. Custom regexes have priority over other regexes
+/// starting at the same position.
+///
+/// If no named patterns were specified, it will search for all available
+/// patterns from the `PATTERNS` catalog.
+///
+/// Lines matching the `prompt_pattern` (the user's prompt and command line)
+/// are not searched.
+///
+/// The regexes are run on the whole line: after a span, `^` does not match and
+/// `\b` sees the text before.
+///
+/// The regexes are obtained from `compile`, and the set of all the regexes
+/// from `compile_set`, so that a long-lived process can cache them. A regex
+/// failing to compile, or a custom regex without capture group, is returned as
+/// an error.
+///
+/// # Ordering
+///
+/// The spans are sorted by line, then by position in the line. Of the matches
+/// starting first, the one of the highest priority is kept, then the one of
+/// the first pattern: the excluded patterns, the custom patterns, then the
+/// named patterns, each in the given order. The same lines and patterns thus
+/// always give the same spans, in the same order.
+///
+/// # Performance
+///
+/// With several patterns, a `RegexSet` first tells which patterns match each
+/// line, so that the lines without spans are scanned once, and only the
+/// matching patterns are run on the others. Each pattern then scans a line
+/// once: its next match is kept until the spans found before overtake it.
+#[allow(clippy::too_many_arguments)] // one per search option
+pub fn find_raw_spans<'a>(
+    lines: &'a [&'a str],
+    named_patterns: &'a [NamedPattern],
+    custom_patterns: &'a [String],
+    use_all_patterns: bool,
+    prompt_pattern: Option<&str>,
+    pattern_priority: &[String],
+    compile: &mut dyn FnMut(&str) -> std::result::Result<Regex, regex::Error>,
+    compile_set: &mut dyn FnMut(&[&str]) -> std::result::Result<RegexSet, regex::Error>,
+) -> Result<Vec<RawSpan<'a>>> {
+    let mut compile =
+        |pattern: &str| compile(pattern).map_err(|err| regexes::invalid_pattern(pattern, &err));
+
+    let prompt_regex = prompt_pattern.map(&mut compile).transpose()?;
+
+    let exclude_regexes = EXCLUDE_PATTERNS
+        .iter()
+        .map(|&(name, pattern)| Ok((name, pattern, Matcher::Regex(compile(pattern)?))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let custom_regexes = custom_patterns
+        .iter()
+        .map(|pattern| {
+            let regex = compile(pattern)?;
+            regexes::check_capture_group(&regex)?;
+            Ok(("custom", pattern.as_str(), Matcher::Regex(regex)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let regexes = if use_all_patterns {
+        PATTERNS
+            .iter()
+            .map(|&(name, pattern)| Ok((name, pattern, Matcher::Regex(compile(pattern)?))))
+            .collect::<Result<Vec<(&str, &str, Matcher)>>>()?
+    } else {
+        named_patterns
+            .iter()
+            .map(|NamedPattern(name, pattern)| {
+                let matcher = match Brackets::parse(name, pattern) {
+                    Some(brackets) => Matcher::Brackets(brackets),
+                    None => Matcher::Regex(compile(pattern)?),
+                };
+                Ok((name.as_str(), pattern.as_str(), matcher))
+            })
+            .collect::<Result<Vec<(&str, &str, Matcher)>>>()?
+    };
+
+    let all_regexes = [exclude_regexes, custom_regexes, regexes].concat();
+
+    // The brackets are not regexes: they are always run. With few regexes,
+    // running each of them is faster than running the set.
+    let set_patterns: Vec<&str> = all_regexes
+        .iter()
+        .filter(|(_, _, matcher)| matches!(matcher, Matcher::Regex(_)))
+        .map(|&(_, pattern, _)| pattern)
+        .collect();
+    let regex_set = (set_patterns.len() >= MIN_SET_PATTERNS)
+        .then(|| compile_set(&set_patterns).expect("The regexes were compiled above."));
+
+    let mut raw_spans = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if prompt_regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(line))
+        {
+            continue;
+        }
+
+        let set_matches = regex_set.as_ref().map(|regex_set| regex_set.matches(line));
+        if set_matches
+            .as_ref()
+            .is_some_and(|set_matches| !set_matches.matched_any())
+            && set_patterns.len() == all_regexes.len()
+        {
+            continue;
+        }
+
+        // The patterns which may match in the line.
+        let mut set_index = 0;
+        let matchers = all_regexes
+            .iter()
+            .filter(|(_, _, matcher)| match matcher {
+                Matcher::Regex(_) => {
+                    set_index += 1;
+                    match &set_matches {
+                        Some(set_matches) => set_matches.matched(set_index - 1),
+                        None => true,
+                    }
+                }
+                Matcher::Brackets(_) => true,
+            })
+            .map(|(pat_name, _, matcher)| (pat_name, matcher))
+            .collect::<Vec<_>>();
+
+        // Next match of each pattern, at or after `offset`, as byte ranges
+        // within the line.
+        let mut next_matches: Vec<Option<Found>> = matchers
+            .iter()
+            .map(|(_, matcher)| matcher.find_at(line, 0))
+            .collect();
+
+        // Offset of the remainder of the line to be searched for matches.
+        // This advances iteratively, until no matches can be found.
+        let mut offset = 0;
+
+        // Use all the matching patterns and select the match occuring the
+        // earliest after the offset. Save its matched text and position in a
+        // `RawSpan` struct.
+        loop {
+            // Search again for the matches overtaken by the previous span.
+            for ((_, matcher), next_match) in matchers.iter().zip(next_matches.iter_mut()) {
+                if next_match
+                    .as_ref()
+                    .is_some_and(|found| found.range.start < offset)
+                {
+                    *next_match = matcher.find_at(line, offset);
+                }
+            }
+
+            let line_matches = matchers
+                .iter()
+                .zip(next_matches.iter())
+                .filter_map(|((pat_name, _), next_match)| {
+                    next_match.as_ref().map(|found| (**pat_name, found))
+                })
+                .collect::<Vec<_>>();
+
+            if line_matches.is_empty() {
+                break;
+            }
+
+            // First match after the offset, unless an overlapping match has a
+            // higher priority.
+            let first_end = line_matches
+                .iter()
+                .min_by_key(|element| element.1.range.start)
+                .map(|element| element.1.range.end)
+                .unwrap();
+            let (
+                pat_name,
+                Found {
+                    range,
+                    capture,
+                    groups,
+                },
+            ) = line_matches
+                .into_iter()
+                .filter(|element| element.1.range.start < first_end)
+                .min_by_key(|element| {
+                    (priority(element.0, pattern_priority), element.1.range.start)
+                })
+                .unwrap();
+
+            // Never hint or break ansi color sequences.
+            if pat_name != "ansi_colors" {
+                raw_spans.push(RawSpan {
+                    x: capture.start as i32,
+                    y: index as i32,
+                    pattern: pat_name,
+                    text: &line[capture.clone()],
+                    groups: groups
+                        .iter()
+                        .map(|(name, group)| (name.clone(), &line[group.clone()]))
+                        .collect(),
+                });
+            }
+
+            offset = range.end;
+        }
+    }
+
+    Ok(raw_spans)
+}
+
+/// Returns the `buffer` with the lines wrapped by the terminal joined back:
+/// a line exactly `width` characters wide is deemed to continue on the next
+/// line.
+///
+/// Text piped from a terminal has lost which lines were wrapped, unlike the
+/// lines captured with `tmux capture-pane -J`. Joining them lets a span
+/// longer than the terminal width, such as a long URL, be matched whole.
+pub fn join_wrapped_lines(buffer: &str, width: usize) -> String {
+    let mut joined = String::with_capacity(buffer.len());
+    let mut lines = buffer.split('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        joined.push_str(line);
+        let wrapped = width > 0 && line.chars().count() == width;
+        if lines.peek().is_some() && !wrapped {
+            joined.push('\n');
+        }
+    }
+
+    joined
+}
+
+/// Returns the rank of the pattern `name` in the `pattern_priority`, the
+/// excluded patterns coming first and the unranked patterns last.
+fn priority(name: &str, pattern_priority: &[String]) -> usize {
+    if EXCLUDE_PATTERNS
+        .iter()
+        .any(|&(excluded, _)| excluded == name)
+    {
+        return 0;
+    }
+    match pattern_priority.iter().position(|ranked| ranked == name) {
+        Some(position) => position + 1,
+        None => pattern_priority.len() + 1,
+    }
+}
+
+/// Match of a pattern in a line, as byte ranges within the line.
+struct Found {
+    range: Range<usize>,
+    /// Text to copy.
+    capture: Range<usize>,
+    /// Named capture groups which participated in the match.
+    groups: Vec<(String, Range<usize>)>,
+}
+
+/// Finds the matches of a pattern in a line.
+#[derive(Clone)]
+enum Matcher {
+    Regex(Regex),
+    Brackets(Brackets),
+}
+
+impl Matcher {
+    /// Returns the first match in the `line` starting at or after the byte
+    /// offset `start`.
+    fn find_at(&self, line: &str, start: usize) -> Option<Found> {
+        match self {
+            Matcher::Regex(regex) => {
+                // Finding the match first is faster than capturing while
+                // scanning the line.
+                let reg_match = regex.find_at(line, start)?;
+                let captures = regex
+                    .captures_at(line, reg_match.start())
+                    .expect("This regex is guaranteed to match.");
+
+                // All patterns must have a capturing group: try obtaining
+                // its range within the match, preferring the `match` group,
+                // else the first group which participated, as in the
+                // alternatives of the composite patterns.
+                let group = if regex.capture_names().any(|name| name == Some(MATCH_GROUP)) {
+                    captures.name(MATCH_GROUP)
+                } else {
+                    captures.iter().skip(1).flatten().next()
+                };
+                let capture = match group {
+                    Some(capture) => capture.range(),
+                    // Only the groups of the excluded patterns are optional.
+                    None if regex.captures_len() > 1 => reg_match.start()..reg_match.start(),
+                    None => panic!("This regex should have a capture group."),
+                };
+
+                let groups = regex
+                    .capture_names()
+                    .flatten()
+                    .filter_map(|name| Some((name.to_string(), captures.name(name)?.range())))
+                    .collect();
+
+                Some(Found {
+                    range: reg_match.range(),
+                    capture,
+                    groups,
+                })
+            }
+            Matcher::Brackets(brackets) => {
+                let (range, capture) = brackets.find(&line[start..])?;
+                Some(Found {
+                    range: start + range.start..start + range.end,
+                    capture: start + capture.start..start + capture.end,
+                    groups: vec![],
+                })
+            }
+        }
+    }
+}
+
+/// Associate a hint to each `RawSpan`, returning a vector of `Span`.
+///
+/// If `unique` is `true`, all duplicate spans will have the same hint.
+/// For copying text spans, this seems easier and more natural.
+/// If `unique` is `false`, duplicate spans will have their own hint.
+fn associate_hints<'a>(
+    raw_spans: &[RawSpan<'a>],
+    alphabet: &'a Alphabet,
+    excluded_letters: &[char],
+    unique: bool,
+) -> Vec<Span<'a>> {
+    let hints = alphabet.make_hints_without(raw_spans.len(), excluded_letters);
+    let mut hints_iter = hints.iter();
+
+    let mut result: Vec<Span<'a>> = vec![];
+
+    if unique {
+        // Map (text, hint)
+        let mut known: collections::HashMap<&str, &str> = collections::HashMap::new();
+
+        for raw_span in raw_spans {
+            let hint: &str = known.entry(raw_span.text).or_insert_with(|| {
+                hints_iter
+                    .next()
+                    .expect("We should have as many hints as necessary, even invisible ones.")
+            });
+
+            result.push(Span {
+                x: raw_span.x,
+                y: raw_span.y,
+                pattern: raw_span.pattern,
+                text: raw_span.text,
+                hint: hint.to_string(),
+                groups: raw_span.groups.clone(),
+            });
+        }
+    } else {
+        for raw_span in raw_spans {
+            let hint = hints_iter
+                .next()
+                .expect("We should have as many hints as necessary, even invisible ones.");
+
+            result.push(Span {
+                x: raw_span.x,
+                y: raw_span.y,
+                pattern: raw_span.pattern,
+                text: raw_span.text,
+                hint: hint.to_string(),
+                groups: raw_span.groups.clone(),
+            });
+        }
+    }
+
+    result
+}
+
+/// Builds a `SequenceTrie` that helps determine if a sequence of keys
+/// entered by the user corresponds to a match. This kind of lookup
+/// directly returns a reference to the corresponding `Span` if any.
+fn build_lookup_trie<'a>(spans: &'a [Span<'a>]) -> SequenceTrie<char, usize> {
+    let mut trie = SequenceTrie::new();
+
+    for (index, span) in spans.iter().enumerate() {
+        let hint_chars = span.hint.chars().collect::<Vec<char>>();
+
+        // no need to insert twice the same hint
+        if trie.get(&hint_chars).is_none() {
+            trie.insert_owned(hint_chars, index);
+        }
+    }
+
+    trie
+}
@@ -0,0 +1,10 @@
+/// Surrogate for `Span`, before a Hint has been associated.
+#[derive(Debug)]
+pub struct RawSpan<'a> {
+    pub x: i32,
+    pub y: i32,
+    pub pattern: &'a str,
+    pub text: &'a str,
+    /// Named capture groups of the match, see `Span::groups`.
+    pub groups: Vec<(String, &'a str)>,
+}
@@ -0,0 +1,46 @@
+/// Origin of the lines of a buffer concatenating the buffers of several
+/// sources, such as tmux panes, each preceded by a header line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Sections {
+    /// Index of the header line of each source.
+    starts: Vec<usize>,
+}
+
+impl Sections {
+    /// Concatenate the `buffers`, each preceded by its `header` line, and
+    /// return the lines of the result along with their origin.
+    ///
+    /// The trailing newlines of each buffer are dropped.
+    pub fn concat(buffers: &[(String, String)]) -> (String, Sections) {
+        let mut concatenated = String::new();
+        let mut starts = Vec::with_capacity(buffers.len());
+        let mut line_count = 0;
+
+        for (header, buffer) in buffers {
+            if !concatenated.is_empty() {
+                concatenated.push('\n');
+            }
+            starts.push(line_count);
+
+            let buffer = buffer.trim_end_matches('\n');
+            concatenated.push_str(header);
+            concatenated.push('\n');
+            concatenated.push_str(buffer);
+            line_count += 2 + buffer.matches('\n').count();
+        }
+
+        (concatenated, Sections { starts })
+    }
+
+    /// Returns the index of the source of the line `y` of the concatenated
+    /// buffer, and the index of the line in that source, or `None` for a
+    /// header line.
+    pub fn locate(&self, y: usize) -> Option<(usize, usize)> {
+        let source = self
+            .starts
+            .partition_point(|&start| start <= y)
+            .checked_sub(1)?;
+        let line = y - self.starts[source];
+        line.checked_sub(1).map(|line| (source, line))
+    }
+}
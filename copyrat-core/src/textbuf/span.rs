@@ -7,4 +7,7 @@ pub struct Span<'a> {
     pub pattern: &'a str,
     pub text: &'a str,
     pub hint: String,
+    /// Named capture groups of the pattern which matched, such as `version`
+    /// for the `version` pattern, along with their text.
+    pub groups: Vec<(String, &'a str)>,
 }
@@ -0,0 +1,31 @@
+# This script inserts a span of the output of a previous command on the
+# command line, picked with `copyrat pick-last`, outside of tmux.
+#
+# Source it from your `config.fish`:
+#
+#   source /path/to/copyrat.fish
+#
+# Unlike zsh, fish cannot capture the output of each command as it is
+# displayed: run the command with `copyrat-capture`, such as
+# `copyrat-capture git status`, then press Ctrl-X p. The hints of `copyrat`
+# are configured with `$COPYRAT_PICK_ARGS`, such as `--alphabet qwerty`, or
+# its config file.
+
+set -q COPYRAT_LAST_OUTPUT
+or set -gx COPYRAT_LAST_OUTPUT (string trim -r -c / -- (set -q TMPDIR; and echo $TMPDIR; or echo /tmp))/copyrat-last-output-(id -u)
+set -q COPYRAT_PICK_KEY
+or set -g COPYRAT_PICK_KEY \cxp
+
+function copyrat-capture --description 'Run a command, capturing its output for copyrat pick-last'
+    $argv 2>&1 | tee $COPYRAT_LAST_OUTPUT
+end
+
+function copyrat-pick-last --description 'Insert a span of the captured output on the command line'
+    set -l picked (copyrat pick-last --shell fish --output-fd 3 $COPYRAT_PICK_ARGS 3>&1 >/dev/tty </dev/tty | string collect)
+    if test -n "$picked"
+        commandline -i -- $picked
+    end
+    commandline -f repaint
+end
+
+bind $COPYRAT_PICK_KEY copyrat-pick-last
@@ -0,0 +1,141 @@
+//! Commands run on the selected text instead of copying it.
+//!
+//! Each pattern can have its own command template, such as `xdg-open {}` for
+//! `url` or `tmux new-window "$EDITOR {}"` for `path`, configured with
+//! `--action url='xdg-open {}'` or the `@copyrat-action-url` tmux option.
+//! Pressing `o` then runs the action of the focused span. The actions are
+//! `template::PatternTemplate`s.
+//!
+//! The post-hook is a command run after every selection, see [`run_hook`].
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Error, Result};
+
+/// Replace the `{}` placeholders in the command `template` by the
+/// single-quoted `text`, so it is passed verbatim to the shell.
+pub fn fill_template(template: &str, text: &str) -> String {
+    let quoted = format!("'{}'", text.replace('\'', r"'\''"));
+    template.replace("{}", &quoted)
+}
+
+/// Replace the `{}` placeholders in the edit command `template` by the
+/// single-quoted `text`, or by `+LINE` and the path if the `text` is a
+/// `path:line[:column]` of the `path-line` pattern, so that the editor opens
+/// the file at that line.
+pub fn fill_edit_template(template: &str, text: &str, pattern: &str) -> String {
+    let path_line = (pattern == "path-line")
+        .then(|| text.split_once(':'))
+        .flatten()
+        .and_then(|(path, rest)| Some((path, rest.split(':').next()?)));
+
+    match path_line {
+        Some((path, line)) => {
+            let quoted = format!("+{line} '{}'", path.replace('\'', r"'\''"));
+            template.replace("{}", &quoted)
+        }
+        None => fill_template(template, text),
+    }
+}
+
+/// Run the command `template` on the `text` with `sh`, waiting for it to
+/// exit.
+pub fn run(template: &str, text: &str) -> Result<()> {
+    let command = fill_template(template, text);
+    duct::cmd!("sh", "-c", command).run()?;
+    Ok(())
+}
+
+/// Run the post-hook `command` with `sh`, passing the selected `text` on its
+/// stdin and in `$COPYRAT_TEXT`, and the name of its `pattern` in
+/// `$COPYRAT_PATTERN`.
+///
+/// The command is killed if it is still running after `timeout`. A timeout or
+/// a non-zero exit status is returned as an error, along with the first line
+/// the command wrote to stderr.
+pub fn run_hook(command: &str, text: &str, pattern: &str, timeout: Duration) -> Result<()> {
+    let handle = duct::cmd!("sh", "-c", command)
+        .env("COPYRAT_TEXT", text)
+        .env("COPYRAT_PATTERN", pattern)
+        .stdin_bytes(text)
+        .stdout_null()
+        .stderr_capture()
+        .unchecked()
+        .start()?;
+
+    let started = Instant::now();
+    let output = loop {
+        if let Some(output) = handle.try_wait()? {
+            break output;
+        }
+        if started.elapsed() >= timeout {
+            handle.kill()?;
+            let reason = format!("timed out after {:?}", timeout);
+            return Err(Error::PostHook(reason));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut reason = match output.status.code() {
+        Some(code) => format!("exited with status {code}"),
+        None => "killed by a signal".to_string(),
+    };
+    if let Some(line) = stderr.lines().find(|line| !line.trim().is_empty()) {
+        reason = format!("{reason}: {line}");
+    }
+    Err(Error::PostHook(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_text_in_template() {
+        assert_eq!(fill_template("open {}", "a b"), "open 'a b'");
+        assert_eq!(
+            fill_template("echo {} {}", "it's"),
+            r"echo 'it'\''s' 'it'\''s'"
+        );
+    }
+
+    #[test]
+    fn edit_path_at_line() {
+        let template = "${EDITOR:-vi} {}";
+        assert_eq!(
+            fill_edit_template(template, "src/main.rs:42:7", "path-line"),
+            "${EDITOR:-vi} +42 'src/main.rs'"
+        );
+        assert_eq!(
+            fill_edit_template(template, "src/main.rs", "path"),
+            "${EDITOR:-vi} 'src/main.rs'"
+        );
+    }
+
+    #[test]
+    fn run_hook_with_selection() {
+        let timeout = Duration::from_secs(5);
+        let command = r#"test "$(cat)" = "$COPYRAT_TEXT" && test "$COPYRAT_PATTERN" = url"#;
+        assert!(run_hook(command, "https://a b", "url", timeout).is_ok());
+
+        let err = run_hook("echo oops >&2; exit 3", "text", "url", timeout).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Post-hook failed: exited with status 3: oops"
+        );
+    }
+
+    #[test]
+    fn kill_hook_after_timeout() {
+        let started = Instant::now();
+        let err = run_hook("sleep 5", "text", "url", Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(err, Error::PostHook(reason) if reason.starts_with("timed out")));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,155 @@
+//! Audit trail of the selections, for `--emit-audit-record FILE`.
+//!
+//! Every selection appends a JSON line to the file, recording when it was
+//! made, the pattern of the selected span, the SHA-256 hash of its text (not
+//! the text itself), where it went, and the id of the tmux pane it was
+//! selected in:
+//!
+//! ```json
+//! {"timestamp":"2024-05-01T09:30:00Z","pattern":"url","text_sha256":"0f3d…","destination":"clipboard","pane_id":"%3"}
+//! ```
+//!
+//! The destination is `tmux-buffer` or `clipboard` for a copy, followed by
+//! `+paste` if the text was also pasted to the pane, or the action run on the
+//! text: `open`, `edit`, `run` or `action`.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{extended::OutputDestination, trust::sha256_hex};
+use crate::ui::{Action, Selection};
+use crate::{json, Result};
+
+/// Record of a selection.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// UTC time of the selection, in RFC 3339 format.
+    pub timestamp: String,
+    pub pattern: String,
+    /// SHA-256 hash of the selected text, in lowercase hexadecimal.
+    pub text_sha256: String,
+    pub destination: String,
+    pub pane_id: String,
+}
+
+impl AuditRecord {
+    /// Returns the record of the `selection` made now in the pane `pane_id`.
+    pub fn new(selection: &Selection, pane_id: &str) -> AuditRecord {
+        AuditRecord::at(&utc_time(), selection, pane_id)
+    }
+
+    fn at(time: &libc::tm, selection: &Selection, pane_id: &str) -> AuditRecord {
+        let destination = match (selection.action, &selection.output_destination) {
+            (Some(Action::Open), _) => "open",
+            (Some(Action::Edit), _) => "edit",
+            (Some(Action::Run), _) => "run",
+            (Some(Action::Pattern), _) => "action",
+            (None, OutputDestination::Tmux) => "tmux-buffer",
+            (None, OutputDestination::Clipboard) => "clipboard",
+            (None, OutputDestination::TypeIntoPane) => "pane",
+        };
+        let destination = if selection.action.is_none() && selection.uppercased {
+            format!("{destination}+paste")
+        } else {
+            destination.to_string()
+        };
+
+        AuditRecord {
+            timestamp: rfc3339(time),
+            pattern: selection.pattern.clone(),
+            text_sha256: sha256_hex(selection.text.as_bytes()),
+            destination,
+            pane_id: pane_id.to_string(),
+        }
+    }
+
+    /// Format the record as a JSON object, on one line.
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"timestamp":{},"pattern":{},"text_sha256":{},"destination":{},"pane_id":{}}}"#,
+            json::string(Some(&self.timestamp)),
+            json::string(Some(&self.pattern)),
+            json::string(Some(&self.text_sha256)),
+            json::string(Some(&self.destination)),
+            json::string(Some(&self.pane_id)),
+        )
+    }
+
+    /// Append the record to the file at `path`, creating it if needed.
+    pub fn append_to(&self, path: &Path) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        // A single write, so that concurrent records are not interleaved.
+        file.write_all(format!("{}\n", self.to_json()).as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Returns the current UTC time.
+pub(crate) fn utc_time() -> libc::tm {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs()) as libc::time_t;
+
+    // SAFETY: `gmtime_r` only writes to the `tm` it is given, and an all-zero
+    // `tm` is valid.
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        libc::gmtime_r(&seconds, &mut tm);
+        tm
+    }
+}
+
+/// Format the UTC `time` in RFC 3339 format, such as `2024-05-01T09:30:00Z`.
+pub(crate) fn rfc3339(time: &libc::tm) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        time.tm_year + 1900,
+        time.tm_mon + 1,
+        time.tm_mday,
+        time.tm_hour,
+        time.tm_min,
+        time.tm_sec,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn may_first() -> libc::tm {
+        // SAFETY: an all-zero `tm` is valid.
+        let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+        tm.tm_year = 124;
+        tm.tm_mon = 4;
+        tm.tm_mday = 1;
+        tm.tm_hour = 9;
+        tm.tm_min = 30;
+        tm
+    }
+
+    #[test]
+    fn audit_record_to_json() {
+        let mut selection = Selection {
+            text: "abc".to_string(),
+            texts: vec!["abc".to_string()],
+            pattern: "sha".to_string(),
+            uppercased: true,
+            output_destination: OutputDestination::Clipboard,
+            position: (1, 1),
+            hint: "a".to_string(),
+            action: None,
+        };
+
+        let record = AuditRecord::at(&may_first(), &selection, "%3");
+        assert_eq!(
+            record.to_json(),
+            r#"{"timestamp":"2024-05-01T09:30:00Z","pattern":"sha","text_sha256":"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad","destination":"clipboard+paste","pane_id":"%3"}"#
+        );
+
+        selection.action = Some(Action::Open);
+        let record = AuditRecord::at(&may_first(), &selection, "%3");
+        assert_eq!(record.destination, "open");
+    }
+}
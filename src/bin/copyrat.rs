@@ -1,28 +1,353 @@
-use clap::Parser;
-use std::io::{self, Read};
+use clap::{CommandFactory, FromArgMatches};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use termion::color;
 
-use copyrat::{config::basic, run, ui::Selection};
+use copyrat::{
+    actions,
+    config::{
+        basic::{self, Command, CommandLine, MainConfig, OutputFormat, PatternsCommand},
+        dump,
+        file::FileConfig,
+        patterns_file, schema,
+    },
+    engine,
+    error::exit_code,
+    history::{self, History},
+    log, run, run_loop, server,
+    shell::{self, Shell},
+    template, textbuf, transforms,
+    ui::{colors::UiColors, Action, Selection},
+    Error, Result,
+};
 
 fn main() {
-    let opt = basic::Config::parse();
+    if let Err(err) = try_main() {
+        log::debug(|| format!("failed: {err}"));
+        err.exit();
+    }
+}
+
+fn try_main() -> Result<()> {
+    let matches = MainConfig::command().get_matches();
+    let main_config = MainConfig::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    match main_config.command {
+        Some(Command::Patterns(PatternsCommand::Export {
+            custom_patterns,
+            patterns_files,
+        })) => export_patterns(custom_patterns, &patterns_files),
+        Some(Command::Serve { socket }) => server::serve(&socket),
+        Some(Command::ConfigSchema { json }) => {
+            config_schema(json);
+            Ok(())
+        }
+        Some(Command::PickLast {
+            file,
+            shell,
+            output_fd,
+            config,
+        }) => {
+            let matches = matches
+                .subcommand_matches("pick-last")
+                .expect("The pick-last command was parsed.");
+            let command_line = CommandLine::from_matches(matches);
+            pick_last(config, &command_line, file, shell, output_fd)
+        }
+        Some(Command::History) => print_history(),
+        Some(Command::Repeat { nth }) => repeat(nth.get()),
+        None => {
+            let mut opt = main_config.config;
+            let command_line = CommandLine::from_matches(&matches);
+            let file_config = FileConfig::load_default()?;
+            if main_config.dump_config {
+                let options = &file_config.options;
+                print!(
+                    "{}",
+                    dump::to_toml("copyrat", &command_line, options, &BTreeMap::new())
+                );
+                return Ok(());
+            }
+            file_config.merge_into(&mut opt, &command_line)?;
+            let opt = opt.build()?;
+            start_debug_log(&opt);
+            let output = match main_config.output_format {
+                _ if main_config.emit_position => Output::Template(POSITION_FORMAT.to_string()),
+                OutputFormat::Json => Output::Json,
+                OutputFormat::Text => match main_config.format {
+                    Some(template) => Output::Template(template),
+                    None => Output::Text,
+                },
+            };
+            let mut out: Box<dyn Write> = match main_config.output_fd {
+                Some(fd) => Box::new(open_output_fd(fd)?),
+                None => Box::new(io::stdout()),
+            };
+            let mut buffer = read_buffer(main_config.file.as_deref())?;
+            if let Some(width) = main_config.join_wrapped_lines {
+                buffer = textbuf::join_wrapped_lines(&buffer, width);
+            }
+
+            if main_config.list_only {
+                list(&buffer, &opt, &output, &mut out)
+            } else {
+                select(&buffer, &opt, main_config.confirm, &output, &mut out)
+            }
+        }
+    }
+}
+
+/// Start the debug log of `--debug-log`, if set.
+fn start_debug_log(opt: &basic::Config) {
+    if let Some(path) = &opt.debug_log {
+        log::start_debug_log(path);
+    }
+}
+
+fn export_patterns(
+    mut custom_patterns: Vec<String>,
+    patterns_files: &[std::path::PathBuf],
+) -> Result<()> {
+    for path in patterns_files {
+        custom_patterns.extend(patterns_file::load(path)?);
+    }
+    print!("{}", patterns_file::export(&custom_patterns));
+    Ok(())
+}
+
+fn config_schema(json: bool) {
+    let options = schema::options();
+    if json {
+        println!("{}", schema::to_json(&options));
+    } else {
+        for option in &options {
+            println!("{option}");
+        }
+    }
+}
 
-    // Copy the pane contents (piped in via stdin) into a buffer, and split lines.
-    let stdin = io::stdin();
-    let mut handle = stdin.lock();
+/// Pick a span of the captured output of the previous shell command, in the
+/// `file` or the default one, and print it quoted for the `shell`.
+fn pick_last(
+    mut opt: basic::Config,
+    command_line: &CommandLine,
+    file: Option<PathBuf>,
+    shell: Shell,
+    output_fd: Option<RawFd>,
+) -> Result<()> {
+    FileConfig::load_default()?.merge_into(&mut opt, command_line)?;
+    let opt = opt.build()?;
+    start_debug_log(&opt);
+
+    let path = file.unwrap_or_else(shell::last_output_path);
+    let buffer = read_buffer(Some(&path))?;
+    let lines = buffer.split('\n').collect::<Vec<_>>();
+
+    let Some(selection) = run(&lines, &opt, None)? else {
+        log::debug(|| "nothing selected".to_string());
+        std::process::exit(exit_code::NO_SELECTION);
+    };
+    log::debug(|| format!("selected a {} span", selection.pattern));
+
+    let mut out: Box<dyn Write> = match output_fd {
+        Some(fd) => Box::new(open_output_fd(fd)?),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(out, "{}", shell.quote(&selection.text))?;
+    history::record(&selection, opt.history_size)
+}
+
+/// Print the selections of the history, from the most recent.
+fn print_history() -> Result<()> {
+    let Some(path) = history::default_path() else {
+        return Ok(());
+    };
+    for entry in History::load(&path)?.recent() {
+        println!("{}\t{}", entry.pattern, history::escape(&entry.text));
+    }
+    Ok(())
+}
+
+/// Print the `nth` most recent selection of the history, counted from 1.
+fn repeat(nth: usize) -> Result<()> {
+    let history = match history::default_path() {
+        Some(path) => History::load(&path)?,
+        None => History::default(),
+    };
+    // Signal that there is no such selection, like `select`.
+    let Some(entry) = history.recent().nth(nth - 1) else {
+        std::process::exit(exit_code::NO_SELECTION);
+    };
+    println!("{}", entry.text);
+    Ok(())
+}
+
+/// Output format of `--emit-position`.
+const POSITION_FORMAT: &str = "{line}:{col}";
+
+/// How each selection is printed on stdout, or on the `--output-fd`.
+enum Output {
+    Text,
+    /// See `--format`.
+    Template(String),
+    /// See `--output-format json`.
+    Json,
+}
+
+/// Print all the spans found in the `buffer`, without the UI.
+fn list(buffer: &str, opt: &basic::Config, output: &Output, out: &mut dyn Write) -> Result<()> {
+    // The spans are found in the text without its colors.
+    let plain;
+    let buffer = if opt.preserve_colors {
+        plain = textbuf::Styles::parse(buffer).0;
+        &plain
+    } else {
+        buffer
+    };
+    let lines = buffer.split('\n').collect::<Vec<_>>();
+
+    let spans = engine::scan(&lines, opt)?;
+
+    // Signal that no spans were found, like `select`.
+    if spans.is_empty() {
+        std::process::exit(exit_code::NO_SELECTION);
+    }
+
+    for span in &spans {
+        let text = template::span_text(&opt.capture_templates, span);
+        let text = transforms::apply(&opt.transforms, span.pattern, text);
+        let selection = Selection::from_span(&lines, span, text);
+        print_selection(&selection, opt, false, output, out)?;
+    }
+
+    Ok(())
+}
+
+fn select(
+    buffer: &str,
+    opt: &basic::Config,
+    confirm: bool,
+    output: &Output,
+    out: &mut dyn Write,
+) -> Result<()> {
+    if let Some(warning) = opt.contrast_warning() {
+        eprintln!("{warning}");
+    }
 
-    let mut buffer = String::new();
-    handle.read_to_string(&mut buffer).unwrap();
     let lines = buffer.split('\n').collect::<Vec<_>>();
 
     // Execute copyrat over the buffer (will take control over stdout).
-    // This returns the selected span of text.
-    let selection: Option<Selection> = run(&lines, &opt);
+    // This returns the selected spans of text.
+    let selections: Vec<Selection> = if opt.loop_mode || opt.stay_open {
+        let mut selections = vec![];
+        run_loop(&lines, opt, None, None, &mut |selection| {
+            selections.push(selection);
+            Ok(())
+        })?;
+        selections
+    } else {
+        run(&lines, opt, None)?.into_iter().collect()
+    };
 
     // Early exit, signaling no selections were found.
-    if selection.is_none() {
-        std::process::exit(1);
+    if selections.is_empty() {
+        log::debug(|| "nothing selected".to_string());
+        std::process::exit(exit_code::NO_SELECTION);
+    }
+    log::debug(|| {
+        let patterns: Vec<&str> = selections.iter().map(|s| s.pattern.as_str()).collect();
+        format!("selected spans of the patterns: {}", patterns.join(" "))
+    });
+
+    for selection in &selections {
+        print_selection(selection, opt, confirm, output, out)?;
+        history::record(selection, opt.history_size)?;
     }
 
-    let Selection { text, .. } = selection.unwrap();
-    println!("{text}");
+    Ok(())
+}
+
+/// Returns the file descriptor `fd` opened by the parent process, such as
+/// with `3> >(consumer)`, see `--output-fd`.
+///
+/// The descriptor is duplicated, which fails right away if it is not open.
+fn open_output_fd(fd: RawFd) -> Result<File> {
+    let dup = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    if dup == -1 {
+        return Err(Error::OutputFd {
+            fd,
+            source: io::Error::last_os_error(),
+        });
+    }
+    Ok(unsafe { File::from_raw_fd(dup) })
+}
+
+/// Returns the content of the `file`, or the pane contents piped in via stdin
+/// if there is no file or it is `-`.
+///
+/// Invalid UTF-8 sequences, such as in binary log lines, are replaced.
+fn read_buffer(file: Option<&Path>) -> Result<String> {
+    let mut bytes = vec![];
+    match file {
+        Some(path) if path != Path::new("-") => File::open(path)?.read_to_end(&mut bytes)?,
+        _ => io::stdin().lock().read_to_end(&mut bytes)?,
+    };
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Print the `selection` on `out` as configured by `output`, or run the
+/// action of its pattern.
+fn print_selection(
+    selection: &Selection,
+    opt: &basic::Config,
+    confirm: bool,
+    output: &Output,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let Selection {
+        text,
+        pattern,
+        action,
+        ..
+    } = selection;
+
+    // The action of the pattern replaces printing the text.
+    if *action == Some(Action::Pattern) {
+        if let Some(template) = template::find(&opt.actions, pattern) {
+            return actions::run(template, text);
+        }
+    }
+
+    match output {
+        Output::Text => writeln!(out, "{text}")?,
+        Output::Template(format) => {
+            writeln!(out, "{}", template::render_output(format, selection))?
+        }
+        Output::Json => writeln!(out, "{}", selection.to_json())?,
+    }
+    out.flush()?;
+
+    if confirm {
+        eprintln!("{}", confirmation_line(text, pattern, &opt.colors));
+    }
+
+    Ok(())
+}
+
+/// Format the confirmation line echoed to stderr, colorized with the span and
+/// hint colors if stderr is a terminal.
+fn confirmation_line(text: &str, pattern: &str, colors: &UiColors) -> String {
+    if termion::is_tty(&io::stderr()) {
+        format!(
+            "copied: {span_fg}{text}{fg_reset} [pattern: {hint_fg}{pattern}{fg_reset}]",
+            span_fg = color::Fg(colors.span_fg),
+            hint_fg = color::Fg(colors.hint_fg),
+            fg_reset = color::Fg(color::Reset),
+        )
+    } else {
+        format!("copied: {text} [pattern: {pattern}]")
+    }
 }
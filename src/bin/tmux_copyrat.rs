@@ -1,18 +1,95 @@
-use clap::Parser;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use clap::{CommandFactory, FromArgMatches};
 use copyrat::{
-    config::extended::{ConfigExt, MainConfig, OutputDestination},
+    actions,
+    audit::AuditRecord,
+    clipboard,
+    config::{
+        basic::CommandLine,
+        dump,
+        extended::{self, ConfigExt, Handoff, MainConfig, OutputDestination, SwapStrategy, UiMode},
+        file::FileConfig,
+        local::{self, LocalConfig},
+        trust::{self, TrustStore},
+    },
+    doctor, history, log,
+    release::ReleaseInfo,
+    template,
+    textbuf::{regexes, Sections},
     tmux,
-    ui::Selection,
-    Result,
+    ui::{Action, Selection},
+    Error, Result,
 };
 
-fn main() -> Result<()> {
-    let main_config = MainConfig::parse();
+/// Target of the temporary pane while it is swapped with the active pane, so
+/// that they are swapped back whenever copyrat fails, including on a panic,
+/// where nothing is unwound.
+static SWAPPED_PANE: Mutex<Option<String>> = Mutex::new(None);
+
+fn main() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_swapped_pane();
+        log::record(&info.to_string());
+        default_hook(info);
+    }));
+
+    if let Err(err) = try_main() {
+        let _ = restore_swapped_pane();
+        // The temporary window running copyrat closes right away.
+        log::debug(|| format!("failed: {err}"));
+        if !matches!(err, Error::NotInTmux) {
+            log::record(&err.to_string());
+            let _ = tmux::display_message(&err.status_message());
+        }
+        err.exit();
+    }
+}
+
+fn try_main() -> Result<()> {
+    let matches = MainConfig::command().get_matches();
+    let main_config = MainConfig::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     match main_config {
         MainConfig::Init => init(),
-        MainConfig::Run { config_ext } => {
-            let config = config_ext.build()?;
+        MainConfig::Doctor { config_ext } => {
+            let command_line = match matches.subcommand() {
+                Some(("doctor", matches)) => CommandLine::from_matches(matches),
+                _ => CommandLine::default(),
+            };
+            doctor(config_ext, &command_line);
+            Ok(())
+        }
+        MainConfig::Bindings => bindings(),
+        MainConfig::Trust { path } => trust(&path),
+        MainConfig::ReleaseInfo { json } => {
+            release_info(json);
+            Ok(())
+        }
+        #[cfg(feature = "updater")]
+        MainConfig::SelfUpdate { check } => self_update(check),
+        MainConfig::Run {
+            config_ext,
+            dump_config,
+        } => {
+            // Rather than failing on the first tmux command.
+            if !tmux::is_inside_session() {
+                return Err(Error::NotInTmux);
+            }
+
+            let command_line = match matches.subcommand() {
+                Some(("run", matches)) => CommandLine::from_matches(matches),
+                _ => CommandLine::default(),
+            };
+            let file_config = FileConfig::load_default()?;
+            if dump_config {
+                return print_config(&config_ext, &command_line, &file_config);
+            }
+            let config = config_ext.merge(file_config, &command_line)?;
             run(config)
         }
     }
@@ -24,55 +101,515 @@ fn init() -> Result<()> {
     Ok(())
 }
 
-fn run(config: ConfigExt) -> Result<()> {
+/// Print the effective configuration of `tmux-copyrat run`, for
+/// `--dump-config`.
+fn print_config(
+    config_ext: &ConfigExt,
+    command_line: &CommandLine,
+    file_config: &FileConfig,
+) -> Result<()> {
+    let tmux_options = if config_ext.ignore_tmux_options {
+        BTreeMap::new()
+    } else {
+        tmux::get_options("@copyrat-")?
+    };
+    print!(
+        "{}",
+        dump::to_toml(
+            "tmux-copyrat",
+            command_line,
+            &file_config.options,
+            &tmux_options
+        )
+    );
+    Ok(())
+}
+
+/// Print the result of each check of `tmux-copyrat doctor`, and exit with 1
+/// if one failed.
+fn doctor(config_ext: ConfigExt, command_line: &CommandLine) {
+    let checks = doctor::run(config_ext, command_line);
+    for check in &checks {
+        println!("{check}");
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == doctor::Status::Failed)
+        .count();
+    if failed > 0 {
+        println!("\n{failed} problem(s) found");
+        std::process::exit(1);
+    }
+}
+
+fn bindings() -> Result<()> {
+    for binding in FileConfig::load_default()?.bindings {
+        println!("{}", binding.key);
+    }
+    Ok(())
+}
+
+/// Approve the local config file at `path` in the trust store.
+fn trust(path: &Path) -> Result<()> {
+    let Some(store_path) = trust::default_path() else {
+        return Ok(());
+    };
+    let path = path.canonicalize()?;
+    let content = std::fs::read_to_string(&path)?;
+
+    let mut store = TrustStore::load(&store_path)?;
+    store.approve(&path, &content);
+    store.save(&store_path)
+}
+
+fn release_info(json: bool) {
+    let info = ReleaseInfo::current();
+    if json {
+        println!("{}", info.to_json());
+    } else {
+        println!("{info}");
+    }
+}
+
+/// Install the latest release if it is newer than the running binary, or
+/// only report it if `check` is set.
+#[cfg(feature = "updater")]
+fn self_update(check: bool) -> Result<()> {
+    use copyrat::updater;
+
+    let release = updater::latest_release()?;
+
+    if !release.is_newer() {
+        println!("tmux-copyrat {} is up to date", updater::CURRENT_VERSION);
+    } else if check {
+        println!(
+            "tmux-copyrat {} is available (current: {})",
+            release.version,
+            updater::CURRENT_VERSION
+        );
+    } else {
+        let path = updater::install(&release)?;
+        println!(
+            "tmux-copyrat updated to {} at {}",
+            release.version,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Run copyrat with the `config`, whose options and patterns are merged, and
+/// which is finalized once the local config file is merged.
+fn run(mut config: ConfigExt) -> Result<()> {
+    if config.swap_strategy != SwapStrategy::None {
+        // The temporary window must close with copyrat, whatever the user's
+        // `remain-on-exit`. If the window cannot be found, the popup fallback
+        // below does not need it.
+        let _ = tmux::set_window_option(&config.window_name, "remain-on-exit", "off");
+    }
+
+    // In popup mode, copyrat runs again in a popup, with the `none` swap
+    // strategy.
+    let popup = config.ui_mode == UiMode::Popup && config.swap_strategy != SwapStrategy::None;
+
+    // Once the temporary window is selected, the active pane is in the
+    // previous window.
+    let selected_window = !popup
+        && config.swap_strategy == SwapStrategy::SelectWindow
+        && tmux::select_window(&config.window_name).is_ok();
+
     // Identify active pane and capture its content.
-    let panes: Vec<tmux::Pane> = tmux::available_panes()?;
+    let panes: Vec<tmux::Pane> = if selected_window {
+        tmux::window_panes("{last}")?
+    } else {
+        tmux::available_panes()?
+    };
 
     let active_pane = panes
-        .into_iter()
+        .iter()
         .find(|p| p.is_active)
-        .expect("Exactly one tmux pane should be active in the current window.");
+        .ok_or(Error::NoActivePane)?;
+    log::debug(|| format!("{} panes, active pane {}", panes.len(), active_pane.id));
 
-    let buffer = active_pane.capture(&config.capture_region)?;
-    let lines = buffer.split('\n').collect::<Vec<_>>();
+    if popup {
+        // Reported here rather than in the popup.
+        config.finalize()?;
+        return display_in_popup(active_pane);
+    }
+
+    merge_local_config(&mut config, active_pane)?;
+    let config = config.finalize()?;
+
+    if let Some(warning) = config.basic_config.contrast_warning() {
+        tmux::display_message(&warning)?;
+    }
+
+    let (buffer, sections) = if config.capture_all_panes {
+        capture_all(&panes, &config)?
+    } else {
+        (
+            active_pane.capture(&config.capture_region, config.basic_config.preserve_colors)?,
+            Sections::default(),
+        )
+    };
+    log::debug(|| format!("captured {} lines", buffer.split('\n').count()));
+    let mut handle = |mut selection: Selection| {
+        let source_pane =
+            locate_selection(&mut selection, &panes, &sections).unwrap_or(active_pane);
+        handle_selection(&selection, &config, active_pane, source_pane)
+    };
 
     // We have to dance a little with Panes, because this process' i/o streams
     // are connected to the pane in the window newly created for us, instead
     // of the active current pane.
     let temp_pane_spec = format!("{}.0", config.window_name);
-    tmux::swap_pane_with(&temp_pane_spec)?;
+    let swapped = config.swap_strategy == SwapStrategy::Swap && swap_pane(&temp_pane_spec);
+    let displayed = match config.swap_strategy {
+        SwapStrategy::Swap => swapped,
+        SwapStrategy::SelectWindow => selected_window,
+        SwapStrategy::None => true,
+    };
+    if !displayed {
+        log::debug(|| "temporary window not displayed, using a popup".to_string());
+        return display_in_popup(active_pane);
+    }
 
-    let selection = copyrat::run(&lines, &config.basic_config);
+    // Wrap the lines against the dimensions of the captured pane, rather than
+    // those of the temporary window.
+    let loop_mode = config.basic_config.loop_mode || config.basic_config.stay_open;
 
-    tmux::swap_pane_with(&temp_pane_spec)?;
+    // The spans of a single pane, found by this process, can be refreshed.
+    if !config.capture_all_panes && config.server_socket.is_none() {
+        let mut selected = None;
+        let mut capture =
+            || active_pane.capture(&config.capture_region, config.basic_config.preserve_colors);
+        let result = copyrat::run_refreshing(
+            buffer,
+            &config.basic_config,
+            active_pane.size(),
+            &mut capture,
+            &mut |selection| {
+                if loop_mode {
+                    return handle(selection);
+                }
+                selected = Some(selection);
+                Ok(())
+            },
+        );
+        restore_swapped_pane()?;
+        result?;
+
+        return match selected {
+            Some(selection) => handle(selection),
+            None => {
+                log::debug(|| "nothing selected".to_string());
+                Ok(())
+            }
+        };
+    }
 
-    // Finally copy selection to the output destination (tmux buffer or
-    // clipboard), and paste it to the active buffer if it was uppercased.
+    let lines = buffer.split('\n').collect::<Vec<_>>();
+    if loop_mode {
+        let result = copyrat::run_loop(
+            &lines,
+            &config.basic_config,
+            active_pane.size(),
+            config.server_socket.as_deref(),
+            &mut handle,
+        );
+        restore_swapped_pane()?;
+        return result;
+    }
+
+    let selection = match &config.server_socket {
+        Some(socket) => {
+            copyrat::run_with_server(&lines, &config.basic_config, active_pane.size(), socket)
+        }
+        None => copyrat::run(&lines, &config.basic_config, active_pane.size()),
+    };
 
+    restore_swapped_pane()?;
+
+    match selection? {
+        Some(selection) => handle(selection),
+        None => {
+            log::debug(|| "nothing selected".to_string());
+            Ok(())
+        }
+    }
+}
+
+/// Swap the temporary pane at `temp_pane_spec` with the active pane.
+///
+/// Returns `false` if they could not be swapped.
+fn swap_pane(temp_pane_spec: &str) -> bool {
+    let swapped = tmux::swap_pane_with(temp_pane_spec).is_ok();
+    if swapped {
+        let mut swapped_pane = SWAPPED_PANE.lock().unwrap_or_else(PoisonError::into_inner);
+        *swapped_pane = Some(temp_pane_spec.to_string());
+    }
+    swapped
+}
+
+/// Swap the temporary pane back with the active pane, if they are swapped.
+fn restore_swapped_pane() -> Result<()> {
+    let swapped_pane = SWAPPED_PANE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    if let Some(temp_pane_spec) = swapped_pane {
+        tmux::swap_pane_with(&temp_pane_spec)?;
+    }
+
+    Ok(())
+}
+
+/// Capture the content of all the `panes`, each preceded by a line naming
+/// it, such as `── %3 ──`.
+fn capture_all(panes: &[tmux::Pane], config: &ConfigExt) -> Result<(String, Sections)> {
+    let buffers = panes
+        .iter()
+        .map(|pane| {
+            let buffer =
+                pane.capture(&config.capture_region, config.basic_config.preserve_colors)?;
+            Ok((format!("── {} ──", pane.id), buffer))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Sections::concat(&buffers))
+}
+
+/// Returns the pane of the `panes` the `selection` was found in, whose
+/// position is changed to be within that pane, or `None` if a single pane was
+/// captured.
+fn locate_selection<'p>(
+    selection: &mut Selection,
+    panes: &'p [tmux::Pane],
+    sections: &Sections,
+) -> Option<&'p tmux::Pane> {
+    let (index, line) = sections.locate(selection.position.0 - 1)?;
+    selection.position.0 = line + 1;
+    panes.get(index)
+}
+
+/// Run copyrat again in a tmux popup over the active `pane`, in popup mode or
+/// when the temporary window cannot be swapped or selected.
+fn display_in_popup(pane: &tmux::Pane) -> Result<()> {
+    let program = std::env::current_exe()?;
+    let mut command = actions::fill_template("{}", &program.to_string_lossy());
+    for arg in extended::popup_args(std::env::args().skip(1)) {
+        command.push(' ');
+        command.push_str(&actions::fill_template("{}", &arg));
+    }
+
+    let size = pane.size().unwrap_or((80, 24));
+    tmux::display_popup(size, &command)
+}
+
+/// Copy the `selection` to the output destination (tmux buffer or
+/// clipboard) or type it into the active `pane`, and paste it to the pane if
+/// it was uppercased, or run its action. Then record it in the history and
+/// the audit file, and run the post-hook, if any.
+fn handle_selection(
+    selection: &Selection,
+    config: &ConfigExt,
+    active_pane: &tmux::Pane,
+    source_pane: &tmux::Pane,
+) -> Result<()> {
     match selection {
-        None => return Ok(()),
-        Some(Selection {
+        Selection {
             text,
+            pattern,
+            action: Some(action),
+            ..
+        } => {
+            run_action(*action, text, pattern, config, active_pane)?;
+        }
+        Selection {
+            text,
+            pattern,
             uppercased,
             output_destination,
-        }) => {
-            if uppercased {
+            action: None,
+            ..
+        } => {
+            // The typed text is not pasted again.
+            let typed = *output_destination == OutputDestination::TypeIntoPane;
+            if *uppercased && !typed {
                 if active_pane.is_copy_mode {
                     // break out of copy mode
                     duct::cmd!("tmux", "copy-mode", "-t", active_pane.id.as_str(), "-q").run()?;
                 }
-                duct::cmd!("tmux", "send-keys", "-t", active_pane.id.as_str(), &text).run()?;
+                duct::cmd!("tmux", "send-keys", "-t", active_pane.id.as_str(), text).run()?;
             }
 
+            // The uppercased selection was pasted already.
+            let paste_buffer = config.paste_on_select
+                && config.handoff != Handoff::Stdout
+                && *output_destination == OutputDestination::Tmux
+                && !*uppercased;
+
             match output_destination {
-                OutputDestination::Tmux => {
-                    duct::cmd!("tmux", "set-buffer", &text).run()?;
-                }
+                OutputDestination::Tmux => handoff(text, config)?,
                 OutputDestination::Clipboard => {
-                    duct::cmd!("echo", "-n", &text)
-                        .pipe(duct::cmd!(config.clipboard_exe))
-                        .read()?;
+                    clipboard::copy(&config.clipboard_backend, text, &config.clipboard_exe)?;
                 }
+                OutputDestination::TypeIntoPane => active_pane.type_text(text)?,
+            }
+            if paste_buffer {
+                active_pane.paste_buffer()?;
+            }
+
+            let is_sensitive = config.sensitive || pattern.split(',').any(regexes::is_sensitive);
+            if is_sensitive && config.clear_clipboard_after > 0 {
+                schedule_clear(output_destination, config)?;
+            }
+
+            // Pasting or typing already left copy mode.
+            if config.enter_copy_mode && !*uppercased && !typed && !paste_buffer {
+                source_pane.enter_copy_mode(
+                    &config.capture_region,
+                    selection.position,
+                    config.copy_mode_keys.as_deref(),
+                )?;
+            }
+        }
+    }
+
+    log::debug(|| {
+        let record = AuditRecord::new(selection, source_pane.id.as_str());
+        format!(
+            "selected a {} span of pane {}, to {}",
+            record.pattern, record.pane_id, record.destination
+        )
+    });
+
+    if !config.sensitive {
+        history::record(selection, config.basic_config.history_size)?;
+    }
+
+    if let Some(path) = &config.emit_audit_record {
+        AuditRecord::new(selection, source_pane.id.as_str()).append_to(path)?;
+    }
+
+    if let Some(post_hook) = &config.post_hook {
+        let timeout = Duration::from_secs(config.post_hook_timeout);
+        if let Err(err) = actions::run_hook(post_hook, &selection.text, &selection.pattern, timeout)
+        {
+            tmux::display_message(&err.status_message())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy the selected `text` to the tmux buffer, or print it, as configured
+/// by `--handoff`.
+fn handoff(text: &str, config: &ConfigExt) -> Result<()> {
+    match config.handoff {
+        Handoff::Buffer => tmux::set_buffer(text)?,
+        Handoff::File => {
+            let path = config.handoff_file();
+            std::fs::write(&path, text)?;
+            tmux::load_buffer(&path)?;
+        }
+        Handoff::Stdout => println!("{text}"),
+    }
+
+    Ok(())
+}
+
+/// Clear the secret just copied to the `output_destination` after
+/// `--clear-clipboard-after` seconds, in the background.
+///
+/// With `--handoff file`, the file is removed as well.
+fn schedule_clear(output_destination: &OutputDestination, config: &ConfigExt) -> Result<()> {
+    let command = match output_destination {
+        OutputDestination::Tmux if config.handoff == Handoff::Stdout => return Ok(()),
+        OutputDestination::Tmux => {
+            let name = tmux::latest_buffer_name()?;
+            let mut command = actions::fill_template("tmux delete-buffer -b {}", &name);
+            if config.handoff == Handoff::File {
+                let path = config.handoff_file();
+                command.push_str("; ");
+                command.push_str(&actions::fill_template("rm -f {}", &path.to_string_lossy()));
+            }
+            command
+        }
+        OutputDestination::Clipboard => {
+            clipboard::clear_command(&config.clipboard_backend, &config.clipboard_exe)?
+        }
+        OutputDestination::TypeIntoPane => return Ok(()),
+    };
+
+    tmux::run_shell_after(config.clear_clipboard_after, &command)
+}
+
+/// Merge the patterns of the `.copyrat.toml` file closest to the pane's
+/// current path, if it is trusted, into the `config` before it is finalized.
+fn merge_local_config(config: &mut ConfigExt, pane: &tmux::Pane) -> Result<()> {
+    let current_path = pane.current_path()?;
+
+    let Some(path) = local::find(&current_path) else {
+        return Ok(());
+    };
+
+    if local::is_trusted(&path, &config.trusted_dirs) {
+        LocalConfig::load(&path)?.merge_into(&mut config.basic_config);
+        return Ok(());
+    }
+
+    let path = path.canonicalize()?;
+    let content = std::fs::read_to_string(&path)?;
+    let store = match trust::default_path() {
+        Some(store_path) => TrustStore::load(&store_path)?,
+        None => TrustStore::default(),
+    };
+
+    if store.is_approved(&path, &content) {
+        LocalConfig::parse(&content)?.merge_into(&mut config.basic_config);
+    } else {
+        // The file is ignored this time: the prompt does not wait for the
+        // answer.
+        let exe = std::env::current_exe()?;
+        let command = format!(
+            "{} trust {}",
+            actions::fill_template("{}", &exe.to_string_lossy()),
+            actions::fill_template("{}", &path.to_string_lossy()),
+        );
+        let prompt = format!("copyrat: trust {}? (y/n)", path.display());
+        tmux::confirm_before(&prompt, &["run-shell", &command])?;
+    }
+
+    Ok(())
+}
+
+/// Perform the `action` chosen in the action menu, or the action of the
+/// `pattern`, on the selected `text`.
+fn run_action(
+    action: Action,
+    text: &str,
+    pattern: &str,
+    config: &ConfigExt,
+    pane: &tmux::Pane,
+) -> Result<()> {
+    match action {
+        Action::Open => actions::run(&config.open_command, text)?,
+        Action::Edit => {
+            let command = actions::fill_edit_template(&config.edit_command, text, pattern);
+            let current_path = pane.current_path()?;
+            duct::cmd!("tmux", "new-window", "-c", current_path, command).run()?;
+        }
+        Action::Run => match &config.run_command {
+            Some(run_command) => actions::run(run_command, text)?,
+            None => tmux::display_message("copyrat: no run command configured")?,
+        },
+        Action::Pattern => {
+            // The UI only offers this action when the pattern has one.
+            if let Some(template) = template::find(&config.basic_config.actions, pattern) {
+                actions::run(template, text)?;
             }
         }
     }
@@ -0,0 +1,190 @@
+//! Copy of the selected text to the system clipboard.
+//!
+//! The `exec` backend pipes the text to the `--clipboard-exe` executable, such
+//! as `pbcopy`, `wl-copy` or `xclip`. The `osc52` backend writes an OSC 52
+//! escape sequence to the terminal of the tmux client instead, which sets the
+//! clipboard of the terminal emulator, so that copying on a remote machine
+//! fills the local clipboard. The `auto` backend uses `osc52` in SSH sessions,
+//! or if the executable cannot be found, and `exec` otherwise.
+//!
+//! The secrets copied to the clipboard are cleared after a while, with the
+//! command returned by [`clear_command`].
+
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::{actions, tmux, Result};
+
+/// How the selected text is copied to the system clipboard.
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ClipboardBackend {
+    /// Write an OSC 52 escape sequence to the terminal.
+    Osc52,
+    /// Pipe the text to `--clipboard-exe`.
+    Exec,
+    /// `osc52` in SSH sessions or if `--clipboard-exe` is missing, `exec`
+    /// otherwise.
+    Auto,
+}
+
+/// The text could not be copied to the system clipboard.
+#[derive(thiserror::Error, Debug)]
+pub enum ClipboardError {
+    #[error("cannot write the OSC 52 sequence to `{tty}`: {source}")]
+    Osc52 { tty: String, source: io::Error },
+
+    #[error("clipboard command `{exe}` failed: {source}")]
+    Exec { exe: String, source: io::Error },
+}
+
+/// Copy the `text` to the system clipboard with the `backend`.
+pub fn copy(backend: &ClipboardBackend, text: &str, clipboard_exe: &str) -> Result<()> {
+    match resolve_current(backend, clipboard_exe) {
+        ClipboardBackend::Osc52 => {
+            let tty = tmux::client_tty()?;
+            OpenOptions::new()
+                .write(true)
+                .open(&tty)
+                .and_then(|mut file| file.write_all(osc52_sequence(text).as_bytes()))
+                .map_err(|source| ClipboardError::Osc52 { tty, source })?;
+        }
+        _ => {
+            duct::cmd!("echo", "-n", text)
+                .pipe(duct::cmd!("sh", "-c", clipboard_exe))
+                .read()
+                .map_err(|source| ClipboardError::Exec {
+                    exe: clipboard_exe.to_string(),
+                    source,
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the shell command clearing the system clipboard with the
+/// `backend`, as filled by `copy`.
+pub fn clear_command(backend: &ClipboardBackend, clipboard_exe: &str) -> Result<String> {
+    let command = match resolve_current(backend, clipboard_exe) {
+        ClipboardBackend::Osc52 => osc52_clear_command(&tmux::client_tty()?),
+        _ => format!("printf '' | {clipboard_exe}"),
+    };
+    Ok(command)
+}
+
+/// Returns the shell command writing the OSC 52 escape sequence which clears
+/// the clipboard, with an empty text, to the terminal `tty`.
+fn osc52_clear_command(tty: &str) -> String {
+    actions::fill_template(r"printf '\033]52;c;\007' > {}", tty)
+}
+
+/// Returns the concrete backend of `auto` in the current environment.
+pub(crate) fn resolve_current(backend: &ClipboardBackend, clipboard_exe: &str) -> ClipboardBackend {
+    let is_ssh_session =
+        env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some();
+
+    resolve(backend, is_ssh_session, command_exists(clipboard_exe))
+}
+
+/// Returns `true` if the executable of the `clipboard_exe` command is found.
+pub(crate) fn command_exists(clipboard_exe: &str) -> bool {
+    clipboard_exe
+        .split_whitespace()
+        .next()
+        .is_some_and(is_executable_in_path)
+}
+
+/// Returns the concrete backend of `auto`.
+fn resolve(backend: &ClipboardBackend, is_ssh_session: bool, exe_found: bool) -> ClipboardBackend {
+    match backend {
+        ClipboardBackend::Auto if is_ssh_session || !exe_found => ClipboardBackend::Osc52,
+        ClipboardBackend::Auto => ClipboardBackend::Exec,
+        backend => backend.clone(),
+    }
+}
+
+/// Returns `true` if the `command` is a path to a file, or the name of a file
+/// found in `$PATH`.
+fn is_executable_in_path(command: &str) -> bool {
+    if command.contains('/') {
+        return Path::new(command).is_file();
+    }
+
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+}
+
+/// Returns the OSC 52 escape sequence setting the clipboard to the `text`.
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Encode the `bytes` in standard base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | ((byte as u32) << (16 - 8 * index))
+            });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64("é\n".as_bytes()), "w6kK");
+    }
+
+    #[test]
+    fn osc52_escape_sequence() {
+        assert_eq!(osc52_sequence("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+
+    #[test]
+    fn osc52_clear() {
+        assert_eq!(
+            osc52_clear_command("/dev/pts/3"),
+            r"printf '\033]52;c;\007' > '/dev/pts/3'"
+        );
+    }
+
+    #[test]
+    fn resolve_auto_backend() {
+        let auto = ClipboardBackend::Auto;
+        assert_eq!(resolve(&auto, false, true), ClipboardBackend::Exec);
+        assert_eq!(resolve(&auto, true, true), ClipboardBackend::Osc52);
+        assert_eq!(resolve(&auto, false, false), ClipboardBackend::Osc52);
+        assert_eq!(
+            resolve(&ClipboardBackend::Exec, true, false),
+            ClipboardBackend::Exec
+        );
+    }
+}
@@ -1,12 +1,176 @@
 use std::fmt::Display;
+use std::num::NonZeroUsize;
+use std::os::fd::RawFd;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{ArgAction, Parser, ValueEnum};
+use clap::{parser::ValueSource, ArgAction, ArgMatches, Parser, Subcommand, ValueEnum};
 
+use super::builder::ConfigBuilder;
+use super::extended::OutputDestination;
 use crate::{
+    diff::DiffMode,
+    shell,
+    template::{self, PatternTemplate},
     textbuf::{alphabet, regexes},
+    transforms::{self, PatternTransform},
     ui, Error, Result,
 };
 
+/// Main configuration of the `copyrat` executable, parsed from command line.
+///
+/// Without a subcommand, copyrat reads the buffer from a file or stdin and
+/// presents the hints.
+#[derive(Parser, Debug)]
+#[clap(author, about, version, args_conflicts_with_subcommands = true)]
+pub struct MainConfig {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Echo a confirmation line to stderr after selection, such as
+    /// `copied: <text> [pattern: url]`.
+    ///
+    /// The selected text is still the only output on stdout.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub confirm: bool,
+
+    /// Join the lines wrapped by a terminal of this width back, so that spans
+    /// longer than a line, such as long URLs, are matched whole.
+    ///
+    /// A line exactly this many characters wide is deemed to continue on the
+    /// next line. `tmux-copyrat` captures the joined lines already.
+    #[arg(long, value_name = "WIDTH")]
+    pub join_wrapped_lines: Option<usize>,
+
+    /// Print all the spans found, one per line, without the UI.
+    ///
+    /// This is handy in scripts, or to debug custom patterns. The spans are
+    /// printed like selections, see `--format` and `--output-format`.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "confirm")]
+    pub list_only: bool,
+
+    /// Print the selection with this template instead of its text alone.
+    ///
+    /// The placeholders are `{text}`, `{pattern}`, `{line}` and `{col}` (the
+    /// position of the first selected span, counted from 1), and `\t` is a
+    /// tab, such as `'{text}\t{pattern}\t{line}:{col}'`. `%{env:VAR}` and
+    /// the strftime conversions such as `%F` are also expanded.
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Print the line and column of the selected span, as `LINE:COL`, instead
+    /// of its text, such as for an editor to jump to it.
+    ///
+    /// Both are counted from 1, and the column counts characters. This is a
+    /// shorthand for `--format '{line}:{col}'`.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["format", "output_format"])]
+    pub emit_position: bool,
+
+    /// Print the selection as text, or as a JSON object with the `text`,
+    /// `pattern`, `x`, `y` and `hint` fields, for editors and scripts.
+    ///
+    /// The coordinates of the span are counted from 0, in characters.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "format")]
+    pub output_format: OutputFormat,
+
+    /// Write the selection to this already-open file descriptor instead of
+    /// stdout, such as `--output-fd 3 3> >(consumer)`.
+    #[arg(long, value_name = "FD", value_parser = clap::value_parser!(RawFd).range(0..))]
+    pub output_fd: Option<RawFd>,
+
+    /// Print the effective configuration as TOML, and exit.
+    ///
+    /// Each option is merged from the command line, the config file and the
+    /// defaults, with its source in a comment. The unknown options of the
+    /// config file, which are otherwise ignored, are listed.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub dump_config: bool,
+
+    /// File to read the buffer from, or `-` for stdin, the default.
+    ///
+    /// Files taller than the screen are scrolled with PageUp and PageDown.
+    #[arg(value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub config: Config,
+}
+
+#[derive(Subcommand, Debug)]
+#[allow(clippy::large_enum_variant)] // parsed once at startup
+pub enum Command {
+    /// Manage sets of custom patterns.
+    #[command(subcommand)]
+    Patterns(PatternsCommand),
+
+    /// Find the spans for `tmux-copyrat run --server-socket`, so that the
+    /// regexes are compiled only once.
+    Serve {
+        /// Path of the unix socket to listen on.
+        #[arg(long)]
+        socket: PathBuf,
+    },
+
+    /// Print all the options, with their tmux option names, types, defaults
+    /// and possible values, so that other tools can validate a
+    /// configuration.
+    ConfigSchema {
+        /// Print as a JSON array of objects, for use in scripts.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Pick a span of the output of the previous shell command, captured by
+    /// the scripts of the `shell` directory, and print it quoted for the
+    /// command line.
+    PickLast {
+        /// File holding the captured output, instead of `$COPYRAT_LAST_OUTPUT`
+        /// or `copyrat-last-output-UID` in the temporary directory.
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Shell whose quoting is used for the picked span.
+        #[arg(long, value_enum, default_value_t = shell::Shell::Posix)]
+        shell: shell::Shell,
+
+        /// Write the picked span to this already-open file descriptor instead
+        /// of stdout, where the UI is displayed.
+        #[arg(long, value_name = "FD", value_parser = clap::value_parser!(RawFd).range(0..))]
+        output_fd: Option<RawFd>,
+
+        #[command(flatten)]
+        config: Config,
+    },
+
+    /// Print the last selections, from the most recent, with their pattern.
+    ///
+    /// Their tabs and newlines are escaped as `\t` and `\n`.
+    History,
+
+    /// Print the most recent selection again, or the Nth most recent one,
+    /// without the UI.
+    Repeat {
+        /// Print the Nth most recent selection, counted from 1.
+        #[arg(long, value_name = "N", default_value = "1")]
+        nth: NonZeroUsize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PatternsCommand {
+    /// Print custom patterns as a patterns file, for use with
+    /// `--patterns-file`.
+    Export {
+        /// Regex patterns to export ("(foo.*)bar", etc).
+        #[arg(short = 'X', long)]
+        custom_patterns: Vec<String>,
+
+        /// Patterns files whose patterns are exported too.
+        #[arg(long = "patterns-file")]
+        patterns_files: Vec<PathBuf>,
+    },
+}
+
 /// Main configuration, parsed from command line.
 #[derive(Parser, Debug)]
 #[clap(author, about, version)]
@@ -15,7 +179,8 @@ pub struct Config {
     ///
     /// Possible values are "{A}", "{A}-homerow", "{A}-left-hand",
     /// "{A}-right-hand", where "{A}" is one of "qwerty", "azerty", "qwertz"
-    /// "dvorak", "colemak".
+    /// "dvorak", "colemak", and "numeric", whose hints are numbers (type
+    /// Enter after a hint which starts longer hints, such as `1` and `10`).
     ///
     /// # Examples
     ///
@@ -28,6 +193,12 @@ pub struct Config {
     )]
     pub alphabet: alphabet::Alphabet,
 
+    /// Characters bound to the commands of the UI, removed from the alphabet
+    /// by `build`, and which the hints of the `longest` alphabet do not use
+    /// either.
+    #[arg(skip)]
+    pub bound_chars: Vec<char>,
+
     /// Use all available regex patterns.
     #[arg(short = 'A', long = "all-patterns")]
     pub use_all_patterns: bool,
@@ -45,6 +216,69 @@ pub struct Config {
     #[arg(short = 'X', long)]
     pub custom_patterns: Vec<String>,
 
+    /// Additional regex pattern with a name, such as `ticket='(PROJ-\d+)'`.
+    /// Must have a capture group.
+    ///
+    /// Unlike `--custom-patterns`, its spans are labeled with its name
+    /// instead of `custom`, which can be used with `--pattern-priority`,
+    /// `--pattern-color`, `--action` and `--format '{pattern}'`.
+    #[arg(
+        long = "custom-pattern",
+        value_name = "NAME=REGEX",
+        value_parser(parse_named_custom_pattern)
+    )]
+    pub named_custom_patterns: Vec<regexes::NamedPattern>,
+
+    /// Flags of the custom patterns, such as `iw`: `i` case-insensitive, `m`
+    /// multi-line, `s` `.` matching newlines, `U` lazy by default, `w` whole
+    /// words only, and `S` smart case, case-insensitive unless the pattern has
+    /// an uppercase letter.
+    ///
+    /// The inline flags, such as `(?i)`, apply to a single pattern instead.
+    #[arg(
+        long,
+        value_name = "FLAGS",
+        default_value = "",
+        value_parser(regexes::parse_pattern_flags)
+    )]
+    pub custom_pattern_flags: String,
+
+    /// Rank of the patterns whose matches overlap (comma-separated), such as
+    /// `url,path`: the match of the pattern ranked first wins.
+    ///
+    /// Name the anonymous custom patterns `custom`. By default, the match
+    /// starting first wins.
+    #[arg(long, value_delimiter = ',')]
+    pub pattern_priority: Vec<String>,
+
+    /// Copy the surrounding quotes along with the strings matched by these
+    /// `quoted-*` patterns (comma-separated).
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub include_quotes: Vec<QuotedPattern>,
+
+    /// Copy the brackets along with the content matched by these
+    /// balanced-bracket patterns (comma-separated).
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub include_brackets: Vec<BracketPattern>,
+
+    /// Files of additional regex patterns, such as produced by `copyrat
+    /// patterns export`.
+    ///
+    /// These are merged after the custom patterns.
+    #[arg(long = "patterns-file")]
+    pub patterns_files: Vec<PathBuf>,
+
+    /// Regex matching the prompt lines, for instance `'^\S*[$#%❯] '`.
+    ///
+    /// Spans on prompt lines, usually the commands you typed, are not
+    /// hinted unless `--include-prompt-lines` is set.
+    #[arg(long)]
+    pub prompt_regex: Option<String>,
+
+    /// Also hint the spans on prompt lines, see `--prompt-regex`.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub include_prompt_lines: bool,
+
     /// Assign hints starting from the bottom of the screen.
     #[arg(short, long, action = ArgAction::SetTrue)]
     pub reverse: bool,
@@ -53,13 +287,267 @@ pub struct Config {
     #[arg(short, long, action = ArgAction::SetTrue)]
     pub unique_hint: bool,
 
+    /// With `--unique-hint`, render the number of identical spans, such as
+    /// `×3`, next to the first of them.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub count_badges: bool,
+
     /// Move focus back to first/last span.
     #[arg(short = 'w', long, action = ArgAction::SetTrue)]
     pub focus_wrap_around: bool,
 
+    /// Emphasize the spans located on the last N lines with the recent
+    /// colors.
+    ///
+    /// The most recent output is usually the most relevant. Trailing blank
+    /// lines are not counted.
+    #[arg(long, value_name = "N")]
+    pub recent_lines: Option<usize>,
+
+    /// List the spans grouped by pattern, instead of highlighting them on
+    /// top of the buffer.
+    ///
+    /// Press Tab to collapse or expand the group of the focused span.
+    #[arg(short = 'G', long, action = ArgAction::SetTrue)]
+    pub group_by_pattern: bool,
+
+    /// List the spans one per row, with their hint, text and source line,
+    /// instead of highlighting them on top of the buffer.
+    #[arg(short = 'L', long, action = ArgAction::SetTrue, conflicts_with = "group_by_pattern")]
+    pub list_view: bool,
+
+    /// Order of the rows in the list view.
+    #[arg(long, value_enum, default_value_t = ui::ListOrder::Position)]
+    pub list_order: ui::ListOrder,
+
+    /// Open an action menu after selecting a span, instead of copying it
+    /// right away.
+    ///
+    /// The menu offers to copy, paste, open, edit or run a command on the
+    /// selected text.
+    #[arg(short = 'M', long, action = ArgAction::SetTrue)]
+    pub action_menu: bool,
+
+    /// Command run on the focused span by pressing `o`, for the spans of a
+    /// pattern, such as `url='xdg-open {}'`.
+    ///
+    /// The `{}` placeholder is replaced by the shell-quoted text. When an
+    /// action is configured, `o` (see `--key action=KEYS`) is no longer used
+    /// as a hint letter.
+    #[arg(
+        long = "action",
+        value_name = "PATTERN=TEMPLATE",
+        value_parser(template::parse_pattern_template)
+    )]
+    pub actions: Vec<PatternTemplate>,
+
+    /// Template of the text copied from the spans of a pattern, such as
+    /// `version='{version}'`.
+    ///
+    /// `{text}` is replaced by the text of the span, and `{NAME}` by the
+    /// capture group `NAME` of the pattern's regex. `%{env:VAR}` and the
+    /// strftime conversions such as `%F` are also expanded.
+    #[arg(
+        long = "capture-template",
+        value_name = "PATTERN=TEMPLATE",
+        value_parser(template::parse_pattern_template)
+    )]
+    pub capture_templates: Vec<PatternTemplate>,
+
+    /// Transform of the text copied from the spans of a pattern, after its
+    /// capture template, such as `url=trim-punct`.
+    ///
+    /// The transforms are `trim-punct`, `expand-tilde`, `strip-diff-prefix`,
+    /// `lowercase`, `uppercase`, and `sh:COMMAND` to pipe the text through a
+    /// shell command. The transforms of a pattern are applied in order.
+    #[arg(
+        long = "transform",
+        value_name = "PATTERN=TRANSFORM",
+        value_parser(transforms::parse_pattern_transform)
+    )]
+    pub transforms: Vec<PatternTransform>,
+
+    /// Keys of a command of the UI, replacing its default keys, such as
+    /// `yank='y Enter'` or `next=j`.
+    ///
+    /// The keys are separated by spaces and named like in tmux, such as `y`,
+    /// `Enter`, `Space`, `PageUp` or `C-u`. The characters bound to no
+    /// command type the hints.
+    #[arg(
+        long = "key",
+        value_name = "COMMAND=KEYS",
+        value_parser(ui::keymap::parse_key_binding)
+    )]
+    pub key_bindings: Vec<ui::keymap::KeyBinding>,
+
+    /// Start in multi-select mode, where typing hints marks spans, and Enter
+    /// selects all the marked spans.
+    ///
+    /// Press Tab to toggle multi-select mode, unless the spans are grouped by
+    /// pattern.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub multi_select: bool,
+
+    /// Only render the buffer lines once, on a cleared screen.
+    ///
+    /// The following renders, such as marking spans or selecting again with
+    /// `--loop`, only draw the spans and their hints, which reduces the bytes
+    /// written over slow links.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub minimal_render: bool,
+
+    /// Keep the UI open after each selection, to select other spans, until
+    /// Esc is pressed.
+    ///
+    /// `tmux-copyrat` copies each selection right away, `copyrat` prints
+    /// them one per line on exit.
+    #[arg(long = "loop", action = ArgAction::SetTrue)]
+    pub loop_mode: bool,
+
+    /// Keep the UI open after the selection, with the selected spans marked,
+    /// until Esc is pressed.
+    ///
+    /// This shows what was copied. Combined with `--loop`, other spans can be
+    /// selected meanwhile.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub stay_open: bool,
+
+    /// Output destination of the selections, until Space toggles it: the
+    /// tmux buffer, the clipboard, or typing them into the pane.
+    ///
+    /// Only `tmux-copyrat` copies the selections, `copyrat` prints them.
+    #[arg(long, value_enum, default_value = "tmux")]
+    pub default_output: OutputDestination,
+
+    /// Select the span right away, without the UI, if it is the only one
+    /// found.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub auto_select_single: bool,
+
+    /// Select the Nth span found, counted from 1, right away without the UI.
+    ///
+    /// The spans are counted from the top of the buffer, or from its bottom
+    /// with `--reverse`. Nothing is selected if fewer spans are found.
+    #[arg(long, value_name = "N")]
+    pub pick_nth: Option<NonZeroUsize>,
+
+    /// Number of selections kept in the history, for `copyrat history`,
+    /// `copyrat repeat` and the `history` key, or 0 to keep none.
+    ///
+    /// The selections of secrets are not kept.
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    pub history_size: usize,
+
+    /// Append to `FILE` the steps explaining what copyrat did: the options
+    /// read, the captured panes, the spans found and the selection.
+    ///
+    /// The selected texts are not logged.
+    #[arg(long, value_name = "FILE")]
+    pub debug_log: Option<PathBuf>,
+
+    /// Render the pattern and the whole text of the focused span, and the
+    /// output destination, on the last line of the screen.
+    ///
+    /// This shows the text of a span cut by the end of the lines.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub show_status_bar: bool,
+
+    /// Command displaying the whole buffer on its stdin when pressing `C-p`
+    /// (see `--key pager=KEYS`), to read it before going back to the UI.
+    ///
+    /// By default, this is `$PAGER`, or `less` if it is not set.
+    #[arg(long)]
+    pub pager: Option<String>,
+
+    /// Number of lines the buffer must exceed for the pager key to work.
+    ///
+    /// By default, this is the height of the screen, so that the pager only
+    /// displays buffers which do not fit on the screen.
+    #[arg(long, value_name = "LINES")]
+    pub pager_threshold: Option<usize>,
+
+    /// Capture the pane again and find its spans every `SECONDS`, without
+    /// closing the UI, like the refresh key (`C-r`).
+    ///
+    /// The typed keys of a hint and the focused span are kept when possible.
+    /// Only `tmux-copyrat` refreshes the spans, when it captures a single
+    /// pane.
+    #[arg(long, value_name = "SECONDS")]
+    pub refresh_interval: Option<u64>,
+
+    /// Exit when a key typing no hint is entered, such as a key outside the
+    /// alphabet.
+    ///
+    /// With `--strict-alphabet=false`, such keys are ignored instead, and
+    /// Backspace erases the last typed key of a hint in either case.
+    #[arg(long, action = ArgAction::Set, default_value_t = true)]
+    pub strict_alphabet: bool,
+
+    /// Separator of the texts of the spans selected in multi-select mode.
+    #[arg(long, default_value = " ")]
+    pub multi_select_separator: String,
+
+    /// When two spans are selected in multi-select mode, select their
+    /// difference instead of their texts.
+    ///
+    /// `unified` is the unified diff of their lines, and `substring` the
+    /// parts of their texts which differ, joined by the multi-select
+    /// separator.
+    #[arg(long, value_enum)]
+    pub diff: Option<DiffMode>,
+
+    /// Action triggered by typing a hint twice quickly.
+    ///
+    /// This is an alternative to uppercase hints. Note that a hint typed
+    /// once is only selected after the double-tap delay.
+    #[arg(long, value_enum, rename_all = "lowercase")]
+    pub double_tap: Option<ui::DoubleTapAction>,
+
+    /// Maximum delay between the two taps of a hint, in milliseconds.
+    #[arg(long, default_value_t = 300)]
+    pub double_tap_delay: u64,
+
+    /// Ring the terminal bell on these events (comma-separated).
+    ///
+    /// Useful as non-visual feedback when triggering copyrat from muscle
+    /// memory.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub bell: Vec<ui::BellEvent>,
+
+    /// Render the spans of the secret patterns, and copy them without
+    /// confirmation.
+    ///
+    /// By default, the spans of `aws-access-key`, `jwt` and `private-key` are
+    /// masked, and selecting one asks to confirm with `y`, so that they are not
+    /// exposed by accident, for instance while sharing the screen.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub reveal_secrets: bool,
+
+    /// Render the buffer with its original colors and attributes.
+    ///
+    /// The SGR escape sequences of the buffer, such as those captured by
+    /// `tmux capture-pane -e`, are removed before finding the spans, and the
+    /// text around the spans is rendered with them instead of the text
+    /// colors.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub preserve_colors: bool,
+
+    /// How the terminal reports the keys.
+    ///
+    /// With `kitty`, terminals supporting the kitty keyboard protocol report
+    /// unambiguous key combinations, such as `Ctrl-Shift-y`. Terminals which
+    /// do not support it keep sending legacy input.
+    #[arg(long, value_enum, default_value_t = ui::KeyboardProtocol::Legacy)]
+    pub keyboard_protocol: ui::KeyboardProtocol,
+
     #[command(flatten)]
     pub colors: ui::colors::UiColors,
 
+    /// Replace the foreground colors which contrast too little with their
+    /// background by black or white, instead of only warning about them.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub enforce_contrast: bool,
+
     /// Align hint with its span.
     #[arg(long, value_enum, default_value_t = ui::HintAlignment::Leading)]
     pub hint_alignment: ui::HintAlignment,
@@ -79,6 +567,120 @@ pub struct Config {
         value_parser(try_parse_chars)
     )]
     pub hint_surroundings: HintSurroundingsArg,
+
+    /// Surround the leading hints which coincide with the start of their
+    /// span's text, with the `--hint-surroundings` chars.
+    ///
+    /// Otherwise, the hint `ab` on the text `abc` could be read as part of
+    /// the text.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub surround_colliding_hints: bool,
+}
+
+/// The arguments given on the command line, whose values take precedence over
+/// the options of the config file and of tmux.
+#[derive(Debug, Default)]
+pub struct CommandLine(Vec<(String, Vec<String>)>);
+
+impl CommandLine {
+    /// Collect the ids of the arguments given on the command line, and their
+    /// values as given.
+    pub fn from_matches(matches: &ArgMatches) -> CommandLine {
+        let args = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| {
+                let values = match matches.try_get_raw(id.as_str()) {
+                    Ok(Some(values)) => values
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .collect(),
+                    _ => vec![],
+                };
+                (id.to_string(), values)
+            })
+            .collect();
+        CommandLine(args)
+    }
+
+    /// Returns the values of the argument `id`, if it was given on the
+    /// command line.
+    pub fn values(&self, id: &str) -> Option<&[String]> {
+        self.0
+            .iter()
+            .find(|(given, _)| given == id)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Returns `true` if the option `name`, such as `hint-style` for
+    /// `@copyrat-hint-style`, was given on the command line.
+    pub fn contains_option(&self, name: &str) -> bool {
+        let id = match OPTION_IDS.iter().find(|&&(option, _)| option == name) {
+            Some(&(_, id)) => id.to_string(),
+            None => name.replace('-', "_"),
+        };
+        self.values(&id).is_some()
+    }
+}
+
+/// Options whose argument id is not their name with `_` instead of `-`, and
+/// their argument id.
+pub const OPTION_IDS: [(&str, &str); 5] = [
+    ("hint-style", "hint_style_arg"),
+    ("loop", "loop_mode"),
+    ("status-bar", "show_status_bar"),
+    ("select-fg-color", "selected_fg"),
+    ("select-bg-color", "selected_bg"),
+];
+
+/// The patterns matching quoted strings, whose quotes can be included, see
+/// `--include-quotes`.
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
+pub enum QuotedPattern {
+    QuotedSingle,
+    QuotedDouble,
+    QuotedBacktick,
+    QuotedAny,
+}
+
+impl QuotedPattern {
+    /// Returns the name of the pattern.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::QuotedSingle => "quoted-single",
+            Self::QuotedDouble => "quoted-double",
+            Self::QuotedBacktick => "quoted-backtick",
+            Self::QuotedAny => "quoted-any",
+        }
+    }
+}
+
+/// The patterns matching balanced brackets, whose brackets can be included,
+/// see `--include-brackets`.
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
+pub enum BracketPattern {
+    Parens,
+    Brackets,
+    Braces,
+}
+
+impl BracketPattern {
+    /// Returns the name of the pattern.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Parens => "parens",
+            Self::Brackets => "brackets",
+            Self::Braces => "braces",
+        }
+    }
+}
+
+/// Output of the `copyrat` binary, see `--output-format`.
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The selected text, or the `--format` template.
+    Text,
+    /// One JSON object per selection.
+    Json,
 }
 
 /// Type introduced due to parsing limitation,
@@ -103,6 +705,26 @@ impl Display for HintSurroundingsArg {
     }
 }
 
+/// Parse a `NAME=REGEX` string into a named custom pattern, whose name is
+/// made of letters, digits, `-` and `_`, and is not the name of a pattern of
+/// the catalog.
+pub(super) fn parse_named_custom_pattern(src: &str) -> Result<regexes::NamedPattern> {
+    match src.split_once('=') {
+        Some((name, regex))
+            if !name.is_empty()
+                && !regex.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                && name != "custom"
+                && regexes::parse_pattern_name(name).is_err() =>
+        {
+            Ok(regexes::NamedPattern(name.to_string(), regex.to_string()))
+        }
+        _ => Err(Error::InvalidCustomPattern(src.to_string())),
+    }
+}
+
 /// Try to parse a `&str` into a tuple of `char`s.
 fn try_parse_chars(src: &str) -> Result<HintSurroundingsArg> {
     if src.chars().count() != 2 {
@@ -117,6 +739,252 @@ fn try_parse_chars(src: &str) -> Result<HintSurroundingsArg> {
 }
 
 impl Config {
+    /// Finalize the Config parsed from the command line, see
+    /// `ConfigBuilder::build`.
+    pub fn build(self) -> Result<Config> {
+        ConfigBuilder::from(self).build()
+    }
+
+    /// Set the option `name`, named like the tmux options without the
+    /// `@copyrat-` prefix, from its `value`.
+    ///
+    /// Returns `false` if the option is unknown.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<bool> {
+        let case_insensitive = true;
+
+        match name {
+            "alphabet" => self.alphabet = alphabet::parse_alphabet(value)?,
+            "reverse" => self.reverse = value.parse::<bool>()?,
+            "unique-hint" => self.unique_hint = value.parse::<bool>()?,
+            "count-badges" => self.count_badges = value.parse::<bool>()?,
+            "include-quotes" => {
+                self.include_quotes = value
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| {
+                        QuotedPattern::from_str(name.trim(), case_insensitive)
+                            .map_err(Error::ExpectedEnumVariant)
+                    })
+                    .collect::<Result<_>>()?;
+            }
+            "include-brackets" => {
+                self.include_brackets = value
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| {
+                        BracketPattern::from_str(name.trim(), case_insensitive)
+                            .map_err(Error::ExpectedEnumVariant)
+                    })
+                    .collect::<Result<_>>()?;
+            }
+            "pattern-priority" => {
+                self.pattern_priority = value
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.trim().to_string())
+                    .collect();
+            }
+            "custom-pattern-flags" => {
+                self.custom_pattern_flags = regexes::parse_pattern_flags(value)?
+            }
+            name if name.starts_with("custom-pattern-") => {
+                let name = &name["custom-pattern-".len()..];
+                self.named_custom_patterns
+                    .push(parse_named_custom_pattern(&format!("{name}={value}"))?);
+            }
+            "prompt-regex" => self.prompt_regex = Some(value.to_string()),
+            "include-prompt-lines" => self.include_prompt_lines = value.parse::<bool>()?,
+            "group-by-pattern" => self.group_by_pattern = value.parse::<bool>()?,
+            "list-view" => self.list_view = value.parse::<bool>()?,
+            "list-order" => {
+                self.list_order = ui::ListOrder::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "action-menu" => self.action_menu = value.parse::<bool>()?,
+            name if name.starts_with("action-") => {
+                self.actions.push(PatternTemplate {
+                    pattern: name["action-".len()..].to_string(),
+                    template: value.to_string(),
+                });
+            }
+            name if name.starts_with("key-") => {
+                let binding = ui::keymap::KeyBinding::new(&name["key-".len()..], value)?;
+                self.key_bindings.push(binding);
+            }
+            name if name.starts_with("capture-template-") => {
+                self.capture_templates.push(PatternTemplate {
+                    pattern: name["capture-template-".len()..].to_string(),
+                    template: value.to_string(),
+                });
+            }
+            name if name.starts_with("transform-") => {
+                let src = format!("{}={value}", &name["transform-".len()..]);
+                self.transforms
+                    .push(transforms::parse_pattern_transform(&src)?);
+            }
+            "multi-select" => self.multi_select = value.parse::<bool>()?,
+            "minimal-render" => self.minimal_render = value.parse::<bool>()?,
+            "loop" => self.loop_mode = value.parse::<bool>()?,
+            "stay-open" => self.stay_open = value.parse::<bool>()?,
+            "default-output" => {
+                self.default_output = OutputDestination::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "auto-select-single" => self.auto_select_single = value.parse::<bool>()?,
+            "history-size" => self.history_size = value.parse::<usize>()?,
+            "debug-log" => self.debug_log = Some(value.into()),
+            "strict-alphabet" => self.strict_alphabet = value.parse::<bool>()?,
+            "status-bar" => self.show_status_bar = value.parse::<bool>()?,
+            "reveal-secrets" => self.reveal_secrets = value.parse::<bool>()?,
+            "preserve-colors" => self.preserve_colors = value.parse::<bool>()?,
+            "keyboard-protocol" => {
+                self.keyboard_protocol = ui::KeyboardProtocol::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "multi-select-separator" => self.multi_select_separator = value.to_string(),
+            "diff" => {
+                self.diff = Some(
+                    DiffMode::from_str(value, case_insensitive)
+                        .map_err(Error::ExpectedEnumVariant)?,
+                )
+            }
+            "double-tap" => {
+                self.double_tap = Some(
+                    ui::DoubleTapAction::from_str(value, case_insensitive)
+                        .map_err(Error::ExpectedEnumVariant)?,
+                )
+            }
+            "bell" => {
+                self.bell = value
+                    .split(',')
+                    .filter(|event| !event.is_empty())
+                    .map(|event| {
+                        ui::BellEvent::from_str(event.trim(), case_insensitive)
+                            .map_err(Error::ExpectedEnumVariant)
+                    })
+                    .collect::<Result<_>>()?;
+            }
+            "double-tap-delay" => self.double_tap_delay = value.parse::<u64>()?,
+            "recent-lines" => self.recent_lines = Some(value.parse::<usize>()?),
+            "pager" => self.pager = Some(value.to_string()),
+            "pager-threshold" => self.pager_threshold = Some(value.parse::<usize>()?),
+            "refresh-interval" => self.refresh_interval = Some(value.parse::<u64>()?),
+
+            "span-fg" => self.colors.span_fg = ui::colors::parse_color(value)?,
+            "span-bg" => self.colors.span_bg = ui::colors::parse_color(value)?,
+            "recent-fg" => self.colors.recent_fg = ui::colors::parse_color(value)?,
+            "recent-bg" => self.colors.recent_bg = ui::colors::parse_color(value)?,
+            "focused-fg" => self.colors.focused_fg = ui::colors::parse_color(value)?,
+            "focused-bg" => self.colors.focused_bg = ui::colors::parse_color(value)?,
+            // The `select-*-color` names are those of tmux-thumbs.
+            "selected-fg" | "select-fg-color" => {
+                self.colors.selected_fg = ui::colors::parse_color(value)?
+            }
+            "selected-bg" | "select-bg-color" => {
+                self.colors.selected_bg = ui::colors::parse_color(value)?
+            }
+            "hint-fg" => self.colors.hint_fg = ui::colors::parse_color(value)?,
+            "hint-bg" => self.colors.hint_bg = ui::colors::parse_color(value)?,
+            name if name.starts_with("pattern-color-") => {
+                let pattern = &name["pattern-color-".len()..];
+                self.colors
+                    .pattern_colors
+                    .push(ui::colors::parse_pattern_color(&format!(
+                        "{pattern}:{value}"
+                    ))?);
+            }
+
+            "enforce-contrast" => self.enforce_contrast = value.parse::<bool>()?,
+
+            "hint-alignment" => {
+                self.hint_alignment = ui::HintAlignment::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "surround-colliding-hints" => self.surround_colliding_hints = value.parse::<bool>()?,
+            "hint-style" => {
+                self.hint_style_arg = Some(
+                    HintStyleArg::from_str(value, case_insensitive)
+                        .map_err(Error::ExpectedEnumVariant)?,
+                )
+            }
+
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Set the `options` which were not given on the `command_line`, in
+    /// order, ignoring the unknown ones.
+    ///
+    /// The templates, key bindings and pattern colors of the command line are
+    /// kept after the others, so that they take precedence.
+    pub fn merge_options(
+        &mut self,
+        options: &[(String, String)],
+        command_line: &CommandLine,
+    ) -> Result<()> {
+        let actions = std::mem::take(&mut self.actions);
+        let capture_templates = std::mem::take(&mut self.capture_templates);
+        let transforms = std::mem::take(&mut self.transforms);
+        let key_bindings = std::mem::take(&mut self.key_bindings);
+        let pattern_colors = std::mem::take(&mut self.colors.pattern_colors);
+
+        for (name, value) in options {
+            if !command_line.contains_option(name) {
+                self.set_option(name, value)?;
+            }
+        }
+
+        self.actions.extend(actions);
+        self.capture_templates.extend(capture_templates);
+        self.transforms.extend(transforms);
+        self.key_bindings.extend(key_bindings);
+        self.colors.pattern_colors.extend(pattern_colors);
+
+        Ok(())
+    }
+
+    /// Returns `true` if no pattern is selected, in which case the default
+    /// patterns of the config file are used.
+    pub fn has_no_patterns(&self) -> bool {
+        !self.use_all_patterns
+            && self.named_patterns.is_empty()
+            && self.custom_patterns.is_empty()
+            && self.named_custom_patterns.is_empty()
+            && self.patterns_files.is_empty()
+    }
+
+    pub fn double_tap(&self) -> Option<ui::DoubleTap> {
+        self.double_tap.as_ref().map(|action| ui::DoubleTap {
+            action: action.clone(),
+            delay: Duration::from_millis(self.double_tap_delay),
+        })
+    }
+
+    /// Returns a warning about the colors which contrast too little, unless
+    /// `--enforce-contrast` fixed them.
+    pub fn contrast_warning(&self) -> Option<String> {
+        let pairs = self.colors.low_contrast_pairs();
+        if pairs.is_empty() {
+            None
+        } else {
+            Some(format!(
+                "copyrat: low contrast of the {} colors, see --enforce-contrast",
+                pairs.join(", ")
+            ))
+        }
+    }
+
+    /// Returns the regex of the prompt lines to exclude, if any.
+    pub fn prompt_pattern(&self) -> Option<&str> {
+        if self.include_prompt_lines {
+            None
+        } else {
+            self.prompt_regex.as_deref()
+        }
+    }
+
     pub fn hint_style(&self) -> Option<ui::HintStyle> {
         match &self.hint_style_arg {
             None => None,
@@ -131,4 +999,82 @@ impl Config {
             },
         }
     }
+
+    /// Returns the style of the hints colliding with their span's text, see
+    /// `--surround-colliding-hints`.
+    pub fn colliding_hint_style(&self) -> Option<ui::HintStyle> {
+        if self.surround_colliding_hints {
+            let HintSurroundingsArg { open, close } = self.hint_surroundings;
+            Some(ui::HintStyle::Surround(open, close))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    #[test]
+    fn parse_file_argument() {
+        let config = MainConfig::parse_from(["copyrat", "-A", "/var/log/syslog"]);
+        assert_eq!(config.file, Some(PathBuf::from("/var/log/syslog")));
+        assert!(config.config.use_all_patterns);
+
+        let config = MainConfig::parse_from(["copyrat", "-"]);
+        assert_eq!(config.file, Some(PathBuf::from("-")));
+
+        // Subcommands are not taken for files.
+        let config = MainConfig::parse_from(["copyrat", "serve", "--socket", "/tmp/s"]);
+        assert!(matches!(config.command, Some(Command::Serve { .. })));
+        assert_eq!(config.file, None);
+
+        // The options of the UI are given after `pick-last`.
+        let config = MainConfig::parse_from(["copyrat", "pick-last", "--shell", "fish", "-r"]);
+        let Some(Command::PickLast { shell, config, .. }) = config.command else {
+            panic!("expected the pick-last command");
+        };
+        assert_eq!(shell, shell::Shell::Fish);
+        assert!(config.reverse);
+    }
+
+    #[test]
+    fn parse_output_fd() {
+        let config = MainConfig::parse_from(["copyrat", "--output-fd", "3"]);
+        assert_eq!(config.output_fd, Some(3));
+
+        assert!(MainConfig::try_parse_from(["copyrat", "--output-fd", "-1"]).is_err());
+    }
+
+    #[test]
+    fn merge_selected_colors() {
+        let args = ["copyrat", "--selected-bg", "black"];
+        let matches = MainConfig::command().get_matches_from(args);
+        let mut config = MainConfig::from_arg_matches(&matches).unwrap().config;
+
+        // The tmux-thumbs names are accepted, after the command line.
+        let options = [
+            ("select-fg-color".to_string(), "red".to_string()),
+            ("select-bg-color".to_string(), "blue".to_string()),
+        ];
+        config
+            .merge_options(&options, &CommandLine::from_matches(&matches))
+            .unwrap();
+        assert_eq!(
+            format!("{}", termion::color::Fg(config.colors.selected_fg)),
+            format!(
+                "{}",
+                termion::color::Fg(ui::colors::parse_color("red").unwrap())
+            )
+        );
+        assert_eq!(
+            format!("{}", termion::color::Bg(config.colors.selected_bg)),
+            format!(
+                "{}",
+                termion::color::Bg(ui::colors::parse_color("black").unwrap())
+            )
+        );
+    }
 }
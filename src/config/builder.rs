@@ -0,0 +1,467 @@
+//! Construction of the `basic::Config` without parsing a command line, for
+//! library use.
+//!
+//! The builder starts from the defaults of the command line options, and its
+//! [`build`](ConfigBuilder::build) step also finalizes the configurations
+//! parsed from the command line.
+//!
+//! ```
+//! use copyrat::config::builder::ConfigBuilder;
+//! use copyrat::engine;
+//!
+//! let config = ConfigBuilder::new()
+//!     .alphabet("qwerty")
+//!     .pattern_name("url")
+//!     .custom_pattern(r"ticket-(\d+)")
+//!     .reverse(true)
+//!     .build()?;
+//!
+//! let lines = ["see https://example.com", "fixed in ticket-42"];
+//! let spans = engine::scan(&lines, &config)?;
+//! assert_eq!(spans[0].text, "https://example.com");
+//! assert_eq!(spans[1].text, "42");
+//! # Ok::<(), copyrat::Error>(())
+//! ```
+//!
+//! Invalid values, such as an unknown pattern name, are reported by `build`:
+//!
+//! ```
+//! use copyrat::config::builder::ConfigBuilder;
+//!
+//! assert!(ConfigBuilder::new().pattern_name("nope").build().is_err());
+//! ```
+
+use clap::Parser;
+use regex::Regex;
+
+use super::{basic::Config, basic::HintStyleArg, basic::HintSurroundingsArg, patterns_file};
+use crate::{
+    template::PatternTemplate,
+    textbuf::{alphabet, regexes},
+    ui, Error, Result,
+};
+
+/// Builds a `Config` from the defaults of the command line options.
+#[derive(Debug)]
+pub struct ConfigBuilder {
+    config: Config,
+    /// First invalid value given to a setter, returned by `build`.
+    error: Option<Error>,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        ConfigBuilder::new()
+    }
+}
+
+impl From<Config> for ConfigBuilder {
+    /// Start from a configuration parsed from the command line.
+    fn from(config: Config) -> Self {
+        ConfigBuilder {
+            config,
+            error: None,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    /// Start from the default options, as if no argument was given.
+    pub fn new() -> Self {
+        ConfigBuilder::from(Config::parse_from(["copyrat"]))
+    }
+
+    /// Draw the hints from the alphabet `name`, such as `qwerty` or
+    /// `dvorak-homerow`.
+    pub fn alphabet(mut self, name: &str) -> Self {
+        match alphabet::parse_alphabet(name) {
+            Ok(alphabet) => self.config.alphabet = alphabet,
+            Err(err) => self.fail(err.into()),
+        }
+        self
+    }
+
+    /// Use all the named patterns.
+    pub fn all_patterns(mut self, use_all_patterns: bool) -> Self {
+        self.config.use_all_patterns = use_all_patterns;
+        self
+    }
+
+    /// Add the named pattern `name`, such as `url`.
+    pub fn pattern_name(mut self, name: &str) -> Self {
+        match regexes::parse_pattern_name(name) {
+            Ok(pattern) => self.config.named_patterns.push(pattern),
+            Err(err) => self.fail(err.into()),
+        }
+        self
+    }
+
+    /// Add a custom `regex`, which must have a capture group.
+    pub fn custom_pattern(mut self, regex: impl Into<String>) -> Self {
+        self.config.custom_patterns.push(regex.into());
+        self
+    }
+
+    /// Add a custom `regex` whose spans are labeled with the pattern `name`,
+    /// see `--custom-pattern`.
+    pub fn named_custom_pattern(mut self, name: &str, regex: &str) -> Self {
+        match super::basic::parse_named_custom_pattern(&format!("{name}={regex}")) {
+            Ok(pattern) => self.config.named_custom_patterns.push(pattern),
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    /// Apply the `flags`, such as `iw`, to the custom patterns, see
+    /// `--custom-pattern-flags`.
+    pub fn custom_pattern_flags(mut self, flags: &str) -> Self {
+        match regexes::parse_pattern_flags(flags) {
+            Ok(flags) => self.config.custom_pattern_flags = flags,
+            Err(err) => self.fail(err.into()),
+        }
+        self
+    }
+
+    /// Assign the hints starting from the bottom.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.config.reverse = reverse;
+        self
+    }
+
+    /// Keep the same hint for identical spans.
+    pub fn unique_hint(mut self, unique_hint: bool) -> Self {
+        self.config.unique_hint = unique_hint;
+        self
+    }
+
+    /// Move the focus back to the first or last span.
+    pub fn focus_wrap_around(mut self, focus_wrap_around: bool) -> Self {
+        self.config.focus_wrap_around = focus_wrap_around;
+        self
+    }
+
+    /// Start in multi-select mode.
+    pub fn multi_select(mut self, multi_select: bool) -> Self {
+        self.config.multi_select = multi_select;
+        self
+    }
+
+    /// Exclude the spans on the lines matching the prompt `regex`.
+    pub fn prompt_regex(mut self, regex: impl Into<String>) -> Self {
+        self.config.prompt_regex = Some(regex.into());
+        self
+    }
+
+    /// Render with the `colors`.
+    pub fn colors(mut self, colors: ui::colors::UiColors) -> Self {
+        self.config.colors = colors;
+        self
+    }
+
+    /// Align the hints with the start or the end of their span.
+    pub fn hint_alignment(mut self, hint_alignment: ui::HintAlignment) -> Self {
+        self.config.hint_alignment = hint_alignment;
+        self
+    }
+
+    /// Style the hints, in addition to their colors.
+    pub fn hint_style(mut self, hint_style: ui::HintStyle) -> Self {
+        self.config.hint_style_arg = Some(match hint_style {
+            ui::HintStyle::Bold => HintStyleArg::Bold,
+            ui::HintStyle::Italic => HintStyleArg::Italic,
+            ui::HintStyle::Underline => HintStyleArg::Underline,
+            ui::HintStyle::Surround(open, close) => {
+                self.config.hint_surroundings = HintSurroundingsArg { open, close };
+                HintStyleArg::Surround
+            }
+        });
+        self
+    }
+
+    /// Run the command `template` on the focused span of the `pattern` by
+    /// pressing `o`, see `--action`.
+    pub fn action(mut self, pattern: &str, template: &str) -> Self {
+        self.config.actions.push(PatternTemplate {
+            pattern: pattern.to_string(),
+            template: template.to_string(),
+        });
+        self
+    }
+
+    /// Copy the spans of the `pattern` rewritten by the capture `template`,
+    /// see `--capture-template`.
+    pub fn capture_template(mut self, pattern: &str, template: &str) -> Self {
+        self.config.capture_templates.push(PatternTemplate {
+            pattern: pattern.to_string(),
+            template: template.to_string(),
+        });
+        self
+    }
+
+    /// Record the first invalid value.
+    fn fail(&mut self, err: Error) {
+        self.error.get_or_insert(err);
+    }
+
+    /// Returns the finalized Config: merge the patterns files into the custom
+    /// patterns, apply their flags, check they are valid regexes with a
+    /// capture group, check the prompt regex and the names of the pattern
+    /// priority, make the `quoted-*` patterns of `--include-quotes` capture
+    /// their quotes and the bracket patterns of `--include-brackets` their
+    /// brackets, move the named custom patterns to the named patterns, enforce
+    /// the contrast of the colors, and reserve the `o` key for the actions.
+    ///
+    /// Returns the first invalid value given to the setters, if any.
+    pub fn build(self) -> Result<Config> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let mut config = self.config;
+
+        for path in &config.patterns_files {
+            let patterns = patterns_file::load(path)?;
+            config.custom_patterns.extend(patterns);
+        }
+
+        if !config.custom_pattern_flags.is_empty() {
+            let flags = &config.custom_pattern_flags;
+            for pattern in &mut config.custom_patterns {
+                *pattern = regexes::apply_pattern_flags(pattern, flags);
+            }
+            for regexes::NamedPattern(_, pattern) in &mut config.named_custom_patterns {
+                *pattern = regexes::apply_pattern_flags(pattern, flags);
+            }
+        }
+
+        let named_custom_regexes = config
+            .named_custom_patterns
+            .iter()
+            .map(|regexes::NamedPattern(_, regex)| regex);
+        for pattern in config.custom_patterns.iter().chain(named_custom_regexes) {
+            regexes::compile_custom_pattern(pattern)?;
+        }
+        if let Some(pattern) = &config.prompt_regex {
+            Regex::new(pattern).map_err(|err| regexes::invalid_pattern(pattern, &err))?;
+        }
+
+        for name in &config.pattern_priority {
+            let is_custom = name == "custom"
+                || config
+                    .named_custom_patterns
+                    .iter()
+                    .any(|regexes::NamedPattern(custom, _)| custom == name);
+            if !is_custom {
+                regexes::parse_pattern_name(name)?;
+            }
+        }
+
+        if !config.include_quotes.is_empty() {
+            if config.use_all_patterns {
+                config.named_patterns = regexes::all_patterns();
+                config.use_all_patterns = false;
+            }
+            config.named_patterns = std::mem::take(&mut config.named_patterns)
+                .into_iter()
+                .map(|pattern| {
+                    if config.include_quotes.iter().any(|q| q.name() == pattern.0) {
+                        pattern.including_quotes()
+                    } else {
+                        pattern
+                    }
+                })
+                .collect();
+        }
+
+        // Not part of `--all-patterns`.
+        config.named_patterns = std::mem::take(&mut config.named_patterns)
+            .into_iter()
+            .map(|pattern| {
+                if config
+                    .include_brackets
+                    .iter()
+                    .any(|b| b.name() == pattern.0)
+                {
+                    pattern.including_brackets()
+                } else {
+                    pattern
+                }
+            })
+            .collect();
+
+        // Before the other patterns, so that they win over the matches
+        // starting at the same position, like the custom patterns.
+        if !config.named_custom_patterns.is_empty() {
+            if config.use_all_patterns {
+                config.named_patterns = regexes::all_patterns();
+                config.use_all_patterns = false;
+            }
+            let named_custom_patterns = std::mem::take(&mut config.named_custom_patterns);
+            config.named_patterns.splice(0..0, named_custom_patterns);
+        }
+
+        if config.enforce_contrast {
+            config.colors.enforce_contrast();
+        }
+
+        // The keys bound to the commands cannot type the hints, except the key
+        // of the actions when there is none, which then types the hints.
+        let keymap = ui::KeyMap::new(&config.key_bindings);
+        config.bound_chars = keymap
+            .chars()
+            .filter(|&(_, command)| {
+                command != ui::keymap::Command::Action || !config.actions.is_empty()
+            })
+            .map(|(ch, _)| ch)
+            .collect();
+        let letters = config.alphabet.0.replace(&config.bound_chars[..], "");
+        if letters.is_empty() {
+            return Err(Error::AlphabetOfBoundKeys(config.alphabet.0));
+        }
+        config.alphabet = alphabet::Alphabet(letters);
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::basic;
+
+    #[test]
+    fn build_config() {
+        let config = ConfigBuilder::new()
+            .alphabet("qwerty-homerow")
+            .pattern_name("sha")
+            .hint_style(ui::HintStyle::Surround('[', ']'))
+            .action("sha", "git show {}")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.named_patterns.len(), 1);
+        assert!(matches!(
+            config.hint_style(),
+            Some(ui::HintStyle::Surround('[', ']'))
+        ));
+        // The `o` key is reserved for the actions.
+        assert!(!config.alphabet.0.contains('o'));
+        assert_eq!(config.multi_select_separator, " ");
+
+        // The first invalid value is reported.
+        let err = ConfigBuilder::new()
+            .alphabet("nope")
+            .pattern_name("nope")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::UnknownAlphabet));
+        assert!(ConfigBuilder::new().custom_pattern(r"\d+").build().is_err());
+        let err = ConfigBuilder::new()
+            .prompt_regex("^[$")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidPattern { .. }));
+    }
+
+    #[test]
+    fn bound_keys_are_not_hints() {
+        let config = basic::Config::parse_from([
+            "copyrat",
+            "--alphabet",
+            "qwerty",
+            "--pattern-name",
+            "pointer-address",
+            "--key",
+            "next=j",
+        ]);
+        let config = config.build().unwrap();
+        assert!(!config.alphabet.0.contains('j'));
+        assert!(config.alphabet.0.contains('o'));
+
+        // Nor the hints made from the `longest` alphabet.
+        let lines = ["0x1"; 1000];
+        let spans = crate::engine::scan(&lines, &config).unwrap();
+        assert_eq!(spans.len(), 1000);
+        assert!(spans.iter().all(|span| !span.hint.contains('j')));
+
+        let config = basic::Config::parse_from([
+            "copyrat",
+            "--alphabet",
+            "qwerty-homerow",
+            "--key",
+            "next=a s d f",
+            "--key",
+            "previous=j k l g h",
+        ]);
+        assert!(matches!(config.build(), Err(Error::AlphabetOfBoundKeys(_))));
+    }
+
+    #[test]
+    fn named_custom_patterns() {
+        let config = ConfigBuilder::new()
+            .all_patterns(true)
+            .named_custom_pattern("ticket", r"(PROJ-\d+)")
+            .build()
+            .unwrap();
+        assert!(!config.use_all_patterns);
+        assert_eq!(config.named_patterns[0].0, "ticket");
+
+        let lines = ["fixed PROJ-42 in 1a2b3c4d"];
+        let spans = crate::engine::scan(&lines, &config).unwrap();
+        let patterns: Vec<&str> = spans.iter().map(|span| span.pattern).collect();
+        assert_eq!(patterns, ["ticket", "sha"]);
+
+        // The names of the pattern priority can be the custom names.
+        let config = basic::Config::parse_from([
+            "copyrat",
+            "--custom-pattern",
+            r"ticket=(PROJ-\d+)",
+            "--pattern-priority",
+            "ticket,url",
+        ]);
+        assert!(config.build().is_ok());
+        let config = basic::Config::parse_from(["copyrat", "--pattern-priority", "ticket"]);
+        assert!(matches!(config.build(), Err(Error::UnknownPatternName)));
+
+        for invalid in ["url=(a)", "custom=(a)", "=(a)", "ticket=", r"(\w+)=(\d+)"] {
+            assert!(
+                basic::Config::try_parse_from(["copyrat", "--custom-pattern", invalid]).is_err()
+            );
+        }
+        assert!(ConfigBuilder::new()
+            .named_custom_pattern("ticket", r"PROJ-\d+")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn custom_pattern_flags() {
+        let config = ConfigBuilder::new()
+            .custom_pattern(r"(proj-\d+)")
+            .named_custom_pattern("word", "(id)")
+            .custom_pattern_flags("Sw")
+            .build()
+            .unwrap();
+        let lines = ["PROJ-42 proj-7 ids id"];
+        let spans = crate::engine::scan(&lines, &config).unwrap();
+        let texts: Vec<&str> = spans.iter().map(|span| span.text).collect();
+        assert_eq!(texts, ["PROJ-42", "proj-7", "id"]);
+
+        // The inline flags apply to a single pattern.
+        let config = ConfigBuilder::new()
+            .custom_pattern(r"(?i)(proj-\d+)")
+            .build()
+            .unwrap();
+        assert_eq!(crate::engine::scan(&lines, &config).unwrap().len(), 2);
+
+        // An invalid regex is an error rather than a panic when searching.
+        let err = ConfigBuilder::new()
+            .custom_pattern("(proj")
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Invalid pattern `(proj`: unclosed group");
+        assert!(ConfigBuilder::new()
+            .custom_pattern_flags("iq")
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Effective configuration, printed as TOML by `--dump-config`.
+//!
+//! Each option takes its value from the command line, else from tmux, else
+//! from the config file, else from its default, and the source is noted next
+//! to it. The options which are not known, and otherwise silently ignored,
+//! are listed in a comment, such as a misspelled `@copyrat-revers`.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+
+use super::{
+    basic::CommandLine,
+    schema::{self, OptionSchema, PLUGIN_OPTIONS},
+    toml::{self, Value},
+};
+
+/// Where the effective value of an option comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    CommandLine,
+    Tmux,
+    ConfigFile,
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CommandLine => write!(f, "command line"),
+            Self::Tmux => write!(f, "tmux"),
+            Self::ConfigFile => write!(f, "config file"),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Option and its effective value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Name of the option, like the tmux option without the `@copyrat-`
+    /// prefix.
+    pub name: String,
+    pub value: Value,
+    pub source: Source,
+}
+
+/// Returns the effective configuration of the `binary`, `copyrat` or
+/// `tmux-copyrat`, as TOML which can be used as a config file.
+///
+/// The `file_options` are those of the config file, and the `tmux_options`
+/// are named with their `@copyrat-` prefix.
+pub fn to_toml(
+    binary: &str,
+    command_line: &CommandLine,
+    file_options: &[(String, String)],
+    tmux_options: &BTreeMap<String, String>,
+) -> String {
+    let given = given_options(file_options, tmux_options);
+
+    let mut out = format!(
+        "# Effective configuration of {binary}: the command line overrides the tmux\n\
+         # options, which override the config file, which overrides the defaults.\n"
+    );
+    let ignored = ignored_options(&given);
+    if !ignored.is_empty() {
+        out.push_str(&format!(
+            "#\n# Unknown options, ignored: {}\n",
+            ignored.join(", ")
+        ));
+    }
+    out.push('\n');
+
+    for entry in entries(binary, command_line, &given) {
+        let line = toml::format_entry(&entry.name, &entry.value);
+        out.push_str(&format!("{line} # {}\n", entry.source));
+    }
+    out
+}
+
+/// Returns the options of the config file, then those of tmux, without their
+/// `@copyrat-` prefix, so that the last option of a name wins.
+fn given_options<'o>(
+    file_options: &'o [(String, String)],
+    tmux_options: &'o BTreeMap<String, String>,
+) -> Vec<(&'o str, &'o str, Source)> {
+    let file = file_options
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str(), Source::ConfigFile));
+    let tmux = tmux_options.iter().filter_map(|(name, value)| {
+        let name = name.strip_prefix("@copyrat-")?;
+        Some((name, value.as_str(), Source::Tmux))
+    });
+    file.chain(tmux).collect()
+}
+
+/// Returns the `given` options which are not known, such as
+/// `@copyrat-revers (tmux)`.
+fn ignored_options(given: &[(&str, &str, Source)]) -> Vec<String> {
+    given
+        .iter()
+        .filter(|&&(name, _, _)| !PLUGIN_OPTIONS.contains(&name) && !schema::is_option(name))
+        .map(|&(name, _, source)| match source {
+            Source::Tmux => format!("@copyrat-{name} (tmux)"),
+            _ => format!("{name} ({source})"),
+        })
+        .collect()
+}
+
+/// Returns the effective value of each option of the `binary` which has one,
+/// in the order of the schema.
+fn entries(binary: &str, command_line: &CommandLine, given: &[(&str, &str, Source)]) -> Vec<Entry> {
+    let options: Vec<(OptionSchema, String)> = schema::options()
+        .into_iter()
+        .filter(|option| option.binaries.contains(&binary))
+        .filter_map(|option| {
+            let name = option.tmux.as_deref()?.strip_prefix("@copyrat-")?;
+            let name = name.to_string();
+            Some((option, name))
+        })
+        .collect();
+    // Such as `custom-pattern-flags`, which is not a `custom-pattern-NAME`.
+    let names: Vec<&str> = options.iter().map(|(_, name)| name.as_str()).collect();
+
+    let mut entries = vec![];
+    for (option, name) in &options {
+        match family_prefix(name) {
+            Some(prefix) => {
+                // All the options of the family apply, the command line ones
+                // replacing the others of the same name.
+                let mut family = BTreeMap::new();
+                for &(given_name, value, source) in given {
+                    if given_name.starts_with(prefix) && !names.contains(&given_name) {
+                        family.insert(given_name.to_string(), (typed(option, value), source));
+                    }
+                }
+                for value in command_line.values(&option.id).unwrap_or_default() {
+                    if let Some((suffix, value)) = value.split_once(['=', ':']) {
+                        let value = typed(option, value);
+                        family.insert(format!("{prefix}{suffix}"), (value, Source::CommandLine));
+                    }
+                }
+                entries.extend(family.into_iter().map(|(name, (value, source))| Entry {
+                    name,
+                    value,
+                    source,
+                }));
+            }
+            None => {
+                let effective = match command_line.values(&option.id) {
+                    Some(values) => Some((command_line_value(option, values), Source::CommandLine)),
+                    None => given
+                        .iter()
+                        .rev()
+                        .find(|&&(given_name, _, _)| given_name == name)
+                        .map(|&(_, value, source)| (typed(option, value), source))
+                        .or_else(|| {
+                            let default = option.default.as_deref()?;
+                            Some((typed(option, default), Source::Default))
+                        }),
+                };
+                if let Some((value, source)) = effective {
+                    entries.push(Entry {
+                        name: name.clone(),
+                        value,
+                        source,
+                    });
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Returns the prefix of a family of options, such as `action-` for
+/// `action-PATTERN`, whose last part is a name.
+fn family_prefix(name: &str) -> Option<&str> {
+    let (prefix, placeholder) = name.rsplit_once('-')?;
+    let is_placeholder = placeholder.chars().all(|c| c.is_ascii_uppercase());
+    is_placeholder.then(|| &name[..prefix.len() + 1])
+}
+
+/// Returns the `value` of the `option` as a TOML boolean or integer if it is
+/// one, else as a string.
+fn typed(option: &OptionSchema, value: &str) -> Value {
+    let typed = match option.kind {
+        "bool" => value.parse().ok().map(Value::Boolean),
+        "integer" => value.parse().ok().map(Value::Integer),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+/// Returns the `values` of the `option` given on the command line: an array
+/// if it can be given several times, and the paths joined like in `$PATH`.
+fn command_line_value(option: &OptionSchema, values: &[String]) -> Value {
+    if !option.multiple {
+        let value = values.last().map_or("true", String::as_str);
+        return typed(option, value);
+    }
+    if option.kind == "path" {
+        let paths = env::join_paths(values).unwrap_or_default();
+        return Value::String(paths.to_string_lossy().into_owned());
+    }
+    Value::Array(values.iter().map(|value| typed(option, value)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::extended::MainConfig;
+    use clap::CommandFactory;
+
+    #[test]
+    fn effective_options_and_sources() {
+        let matches = MainConfig::command().get_matches_from([
+            "tmux-copyrat",
+            "run",
+            "--alphabet",
+            "qwerty",
+            "--action",
+            "url=open {}",
+            "--trusted-dir",
+            "/a",
+            "--trusted-dir",
+            "/b",
+        ]);
+        let (_, matches) = matches.subcommand().unwrap();
+        let command_line = CommandLine::from_matches(matches);
+
+        let file_options = [
+            ("alphabet".to_string(), "azerty".to_string()),
+            ("reverse".to_string(), "true".to_string()),
+            ("action-path".to_string(), "vi {}".to_string()),
+        ];
+        let tmux_options: BTreeMap<String, String> = [
+            ("@copyrat-reverse", "false"),
+            ("@copyrat-revers", "true"),
+            ("@copyrat-keytable", "cpyrt"),
+            ("@copyrat-action-url", "xdg-open {}"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+        let given = given_options(&file_options, &tmux_options);
+        assert_eq!(ignored_options(&given), ["@copyrat-revers (tmux)"]);
+
+        let entries = entries("tmux-copyrat", &command_line, &given);
+        let find = |name: &str| {
+            let entry = entries.iter().find(|entry| entry.name == name)?;
+            Some((entry.value.clone(), entry.source))
+        };
+        assert_eq!(
+            find("alphabet"),
+            Some((Value::String("qwerty".into()), Source::CommandLine))
+        );
+        assert_eq!(find("reverse"), Some((Value::Boolean(false), Source::Tmux)));
+        assert_eq!(
+            find("action-path"),
+            Some((Value::String("vi {}".into()), Source::ConfigFile))
+        );
+        assert_eq!(
+            find("action-url"),
+            Some((Value::String("open {}".into()), Source::CommandLine))
+        );
+        assert_eq!(
+            find("trusted-dirs"),
+            Some((Value::String("/a:/b".into()), Source::CommandLine))
+        );
+        assert_eq!(
+            find("post-hook-timeout"),
+            Some((Value::Integer(5), Source::Default))
+        );
+        assert_eq!(find("custom-pattern-flags").unwrap().1, Source::Default);
+        assert_eq!(find("run-command"), None);
+
+        let copyrat_entries = super::entries("copyrat", &CommandLine::default(), &[]);
+        assert!(copyrat_entries.iter().all(|entry| entry.name != "ui-mode"));
+        assert_eq!(
+            family_prefix("custom-pattern-NAME"),
+            Some("custom-pattern-")
+        );
+        assert_eq!(family_prefix("custom-pattern-flags"), None);
+    }
+}
@@ -1,21 +1,67 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::path::PathBuf;
 
 use clap::{Args, Parser, ValueEnum};
 
-use super::basic;
-use crate::{textbuf::alphabet, tmux, ui, Error, Result};
+use super::{
+    basic::{self, CommandLine},
+    file::FileConfig,
+};
+use crate::{clipboard::ClipboardBackend, log, tmux, Error, Result};
 
 #[derive(Parser, Debug)]
 #[clap(author, about, version)]
+#[allow(clippy::large_enum_variant)] // parsed once at startup
 pub enum MainConfig {
     /// Run tmux-copyrat.
     Run {
         #[command(flatten)]
         config_ext: ConfigExt,
+
+        /// Print the effective configuration as TOML, and exit.
+        ///
+        /// Each option is merged from the command line, the tmux options, the
+        /// config file and the defaults, with its source in a comment. The
+        /// unknown tmux options and options of the config file, which are
+        /// otherwise ignored, are listed.
+        #[arg(long)]
+        dump_config: bool,
     },
     /// Print the tmux plugin config file for initial configuration.
     Init,
+    /// Check the tmux version, the options, the clipboard executable and the
+    /// capture of the active pane, and tell how to fix what is wrong.
+    ///
+    /// The options are those of `run`, so that the options of a key binding
+    /// can be checked as well. Exits with 1 if a check failed.
+    Doctor {
+        #[command(flatten)]
+        config_ext: ConfigExt,
+    },
+    /// Print the keys bound in the config file, one per line, for the tmux
+    /// plugin config file.
+    Bindings,
+    /// Approve the directory-local config file at `PATH`, as confirmed in the
+    /// tmux prompt, until it changes.
+    Trust {
+        /// Path of the `.copyrat.toml` file.
+        path: PathBuf,
+    },
+    /// Print the version, target triple and release artifact names of this
+    /// binary.
+    ReleaseInfo {
+        /// Print as a JSON object, for use in scripts.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Update tmux-copyrat to the latest GitHub release.
+    #[cfg(feature = "updater")]
+    SelfUpdate {
+        /// Only check if a newer version is available, without installing it.
+        #[arg(long)]
+        check: bool,
+    },
 }
 
 /// Extended configuration for handling Tmux-specific configuration (options
@@ -40,6 +86,23 @@ pub struct ConfigExt {
     #[arg(short = 'W', long, default_value = "[copyrat]")]
     pub window_name: String,
 
+    /// How copyrat, running in the temporary window, is displayed in place of
+    /// the active pane.
+    ///
+    /// If the temporary window cannot be swapped or selected, copyrat is
+    /// displayed in a tmux popup instead.
+    #[arg(value_enum, long, rename_all = "kebab-case", default_value = "swap")]
+    pub swap_strategy: SwapStrategy,
+
+    /// Where copyrat is displayed.
+    ///
+    /// `window` displays copyrat, running in the temporary window, as
+    /// configured by `--swap-strategy`. `popup` runs copyrat again in a
+    /// borderless tmux popup over the active pane (tmux 3.3 or later), which
+    /// avoids swapping panes, even if the active pane is zoomed.
+    #[arg(long, value_enum, default_value_t = UiMode::Window)]
+    pub ui_mode: UiMode,
+
     /// Capture visible area or entire pane history.
     #[arg(
         value_enum,
@@ -49,77 +112,276 @@ pub struct ConfigExt {
     )]
     pub capture_region: CaptureRegion,
 
+    /// Capture all the panes of the window rather than the active one, with
+    /// a line naming each pane before its content.
+    ///
+    /// The selection is then copied from the pane it was found in, such as
+    /// for `--enter-copy-mode` or the audit record, but still pasted to the
+    /// active pane.
+    #[arg(long)]
+    pub capture_all_panes: bool,
+
     /// Name of the copy-to-clipboard executable.
     ///
     /// If during execution, the output destination is set to be clipboard,
-    /// then copyrat will pipe the selected text to this executable.
+    /// then copyrat will pipe the selected text to this executable, unless
+    /// the clipboard backend is `osc52`.
     /// On macOS, this is `pbcopy`, on Linux, this is `xclip`.
     #[arg(long, default_value = "pbcopy")]
     pub clipboard_exe: String,
 
+    /// How the selected text is copied to the system clipboard.
+    ///
+    /// `osc52` writes an escape sequence to the terminal, which works over
+    /// SSH if the terminal emulator supports it. `auto` uses `osc52` in SSH
+    /// sessions or if the clipboard executable is missing.
+    #[arg(long, value_enum, default_value_t = ClipboardBackend::Auto)]
+    pub clipboard_backend: ClipboardBackend,
+
+    /// Command opening the selected text, chosen in the action menu.
+    ///
+    /// See also `--action`, to run a command depending on the pattern.
+    ///
+    /// The `{}` placeholder is replaced by the shell-quoted text. On macOS,
+    /// this is `open {}`.
+    #[arg(long, default_value = "xdg-open {}")]
+    pub open_command: String,
+
+    /// Command editing the selected text, chosen in the action menu.
+    ///
+    /// It is run in a new tmux window, in the current path of the active
+    /// pane. The `{}` placeholder is replaced by the shell-quoted text, or by
+    /// `+LINE PATH` for the spans of the `path-line` pattern.
+    #[arg(long, default_value = "${EDITOR:-vi} {}")]
+    pub edit_command: String,
+
+    /// Custom command run on the selected text, chosen in the action menu.
+    ///
+    /// The `{}` placeholder is replaced by the shell-quoted text.
+    #[arg(long)]
+    pub run_command: Option<String>,
+
+    /// Enter copy mode in the pane after copying the selection, with the
+    /// cursor on the selected span, to carry on selecting by hand.
+    #[arg(long)]
+    pub enter_copy_mode: bool,
+
+    /// Keys sent to the pane in copy mode after `--enter-copy-mode`, such as
+    /// `v` to begin a selection, separated by spaces.
+    #[arg(long, value_name = "KEYS")]
+    pub copy_mode_keys: Option<String>,
+
+    /// Paste the selection into the active pane right after copying it to
+    /// the tmux buffer, like the `@thumbs-command` of tmux-thumbs.
+    ///
+    /// Nothing is pasted with `--handoff stdout`, nor when the selection is
+    /// copied to the clipboard.
+    #[arg(long)]
+    pub paste_on_select: bool,
+
+    /// How the selection copied to the tmux buffer is handed off.
+    ///
+    /// `buffer` sets the tmux buffer directly. `file` writes the selection to
+    /// `--handoff-file`, then loads the tmux buffer from it. `stdout` prints
+    /// the selection instead of setting the tmux buffer, for scripts running
+    /// `tmux-copyrat run --swap-strategy none`.
+    #[arg(long, value_enum, default_value_t = Handoff::Buffer)]
+    pub handoff: Handoff,
+
+    /// File the selection is written to with `--handoff file`.
+    ///
+    /// By default, this is `copyrat-selection` in the temporary directory.
+    #[arg(long, value_name = "FILE")]
+    pub handoff_file: Option<PathBuf>,
+
+    /// Command run after every selection, to integrate with other tools.
+    ///
+    /// The selected text is passed on its stdin and in `$COPYRAT_TEXT`, and
+    /// the name of its pattern in `$COPYRAT_PATTERN`. A failure is reported
+    /// in the tmux status line.
+    #[arg(long)]
+    pub post_hook: Option<String>,
+
+    /// Seconds after which the post-hook is killed and reported as failed.
+    #[arg(long, default_value_t = 5)]
+    pub post_hook_timeout: u64,
+
+    /// Treat every selection as a secret, cleared from the tmux buffer or the
+    /// clipboard after `--clear-clipboard-after` seconds.
+    ///
+    /// The selections of the secret patterns, such as `jwt`, are always
+    /// cleared.
+    #[arg(long)]
+    pub sensitive: bool,
+
+    /// Seconds after which the secrets are cleared from the tmux buffer or the
+    /// clipboard, or `0` to keep them.
+    ///
+    /// The clipboard is cleared even if something else was copied since.
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub clear_clipboard_after: u64,
+
+    /// Append a JSON line to `FILE` for every selection, as an audit trail.
+    ///
+    /// The line records the time, the pattern, the SHA-256 hash of the text,
+    /// the destination and the id of the tmux pane.
+    #[arg(long, value_name = "FILE")]
+    pub emit_audit_record: Option<PathBuf>,
+
+    /// Directories in which `.copyrat.toml` files are trusted.
+    ///
+    /// If the active pane's current path (or one of its parents) contains a
+    /// `.copyrat.toml` file located inside one of these directories, its
+    /// patterns are added. Local config files found elsewhere must be
+    /// approved first, in a tmux prompt.
+    #[arg(long = "trusted-dir")]
+    pub trusted_dirs: Vec<PathBuf>,
+
+    /// Unix socket of a `copyrat serve` process finding the spans.
+    ///
+    /// This saves compiling the regexes on every invocation. If the server
+    /// cannot be reached, the spans are found as usual.
+    #[arg(long)]
+    pub server_socket: Option<PathBuf>,
+
+    /// Use the patterns bound to this key in the config file.
+    ///
+    /// The key bindings of the config file are set up by the tmux plugin
+    /// config file.
+    #[arg(long, value_name = "KEY")]
+    pub binding: Option<String>,
+
     // Include fields from the basic config
     #[command(flatten)]
     pub basic_config: basic::Config,
 }
 
 impl ConfigExt {
-    /// Finalize the ConfigExt by merging the tmux options.
-    pub fn build(mut self) -> Result<ConfigExt> {
+    /// Finalize the ConfigExt by merging the options of the config file and
+    /// of tmux, and the patterns of the config file.
+    ///
+    /// The options given on the `command_line` take precedence over the tmux
+    /// options, which take precedence over the config file.
+    pub fn build(self, file_config: FileConfig, command_line: &CommandLine) -> Result<ConfigExt> {
+        self.merge(file_config, command_line)?.finalize()
+    }
+
+    /// Merge the options of the config file and of tmux, and the patterns of
+    /// the config file, like `build`, but without finalizing the basic
+    /// config, so that more patterns can be added before `finalize`.
+    pub fn merge(
+        mut self,
+        file_config: FileConfig,
+        command_line: &CommandLine,
+    ) -> Result<ConfigExt> {
+        let mut options = file_config.options.clone();
+        let file_options = options.len();
+        let mut tmux_options = BTreeMap::new();
+
         if !self.ignore_tmux_options {
-            let tmux_options: HashMap<String, String> = tmux::get_options("@copyrat-")?;
-
-            // Override default values with those coming from tmux.
-            let inner = &mut self.basic_config;
-
-            for (name, value) in &tmux_options {
-                match name.as_ref() {
-                    "@copyrat-capture-region" => {
-                        let case_insensitive = true;
-                        self.capture_region = CaptureRegion::from_str(value, case_insensitive)
-                            .map_err(Error::ExpectedEnumVariant)?
-                    }
-                    "@copyrat-alphabet" => {
-                        inner.alphabet = alphabet::parse_alphabet(value)?;
-                    }
-                    "@copyrat-reverse" => {
-                        inner.reverse = value.parse::<bool>()?;
-                    }
-                    "@copyrat-unique-hint" => {
-                        inner.unique_hint = value.parse::<bool>()?;
-                    }
-
-                    "@copyrat-span-fg" => inner.colors.span_fg = ui::colors::parse_color(value)?,
-                    "@copyrat-span-bg" => inner.colors.span_bg = ui::colors::parse_color(value)?,
-                    "@copyrat-focused-fg" => {
-                        inner.colors.focused_fg = ui::colors::parse_color(value)?
-                    }
-                    "@copyrat-focused-bg" => {
-                        inner.colors.focused_bg = ui::colors::parse_color(value)?
-                    }
-                    "@copyrat-hint-fg" => inner.colors.hint_fg = ui::colors::parse_color(value)?,
-                    "@copyrat-hint-bg" => inner.colors.hint_bg = ui::colors::parse_color(value)?,
-
-                    "@copyrat-hint-alignment" => {
-                        let case_insensitive = true;
-                        inner.hint_alignment = ui::HintAlignment::from_str(value, case_insensitive)
-                            .map_err(Error::ExpectedEnumVariant)?
-                    }
-                    "@copyrat-hint-style" => {
-                        let case_insensitive = true;
-                        inner.hint_style_arg = Some(
-                            basic::HintStyleArg::from_str(value, case_insensitive)
-                                .map_err(Error::ExpectedEnumVariant)?,
-                        )
-                    }
-
-                    // Ignore unknown options.
-                    _ => (),
-                }
+            tmux_options = tmux::get_options("@copyrat-")?;
+
+            options.extend(tmux_options.iter().filter_map(|(name, value)| {
+                let name = name.strip_prefix("@copyrat-")?.to_string();
+                Some((name, value.clone()))
+            }));
+        }
+
+        let mut basic_options = vec![];
+        for (name, value) in options {
+            if !command_line.contains_option(&name) && !self.set_option(&name, &value)? {
+                basic_options.push((name, value));
             }
         }
+        self.basic_config
+            .merge_options(&basic_options, command_line)?;
+
+        file_config.merge_patterns_into(&mut self.basic_config, self.binding.as_deref())?;
+
+        // The debug log may be set by the options.
+        if let Some(path) = &self.basic_config.debug_log {
+            log::start_debug_log(path);
+        }
+        log::debug(|| {
+            let tmux_options: Vec<String> = tmux_options
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            format!(
+                "{} options from the config file, tmux options: {}",
+                file_options,
+                tmux_options.join(" ")
+            )
+        });
+
+        Ok(self)
+    }
 
+    /// Finalize the basic config, after the options and the patterns are
+    /// merged, see `basic::Config::build`.
+    pub fn finalize(mut self) -> Result<ConfigExt> {
+        self.basic_config = self.basic_config.build()?;
         Ok(self)
     }
+
+    /// Set the tmux-specific option `name`, named like the tmux options
+    /// without the `@copyrat-` prefix, from its `value`.
+    ///
+    /// Returns `false` if the option is not tmux-specific.
+    pub(crate) fn set_option(&mut self, name: &str, value: &str) -> Result<bool> {
+        match name {
+            "capture-region" => {
+                let case_insensitive = true;
+                self.capture_region = CaptureRegion::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "capture-all-panes" => self.capture_all_panes = value.parse::<bool>()?,
+            "ui-mode" => {
+                let case_insensitive = true;
+                self.ui_mode =
+                    UiMode::from_str(value, case_insensitive).map_err(Error::ExpectedEnumVariant)?
+            }
+            "swap-strategy" => {
+                let case_insensitive = true;
+                self.swap_strategy = SwapStrategy::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "clipboard-backend" => {
+                let case_insensitive = true;
+                self.clipboard_backend = ClipboardBackend::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "open-command" => self.open_command = value.to_string(),
+            "edit-command" => self.edit_command = value.to_string(),
+            "run-command" => self.run_command = Some(value.to_string()),
+            "enter-copy-mode" => self.enter_copy_mode = value.parse::<bool>()?,
+            "copy-mode-keys" => self.copy_mode_keys = Some(value.to_string()),
+            "paste-on-select" => self.paste_on_select = value.parse::<bool>()?,
+            "handoff" => {
+                let case_insensitive = true;
+                self.handoff = Handoff::from_str(value, case_insensitive)
+                    .map_err(Error::ExpectedEnumVariant)?
+            }
+            "handoff-file" => self.handoff_file = Some(value.into()),
+            "post-hook" => self.post_hook = Some(value.to_string()),
+            "post-hook-timeout" => self.post_hook_timeout = value.parse()?,
+            "sensitive" => self.sensitive = value.parse::<bool>()?,
+            "clear-clipboard-after" => self.clear_clipboard_after = value.parse()?,
+            "emit-audit-record" => self.emit_audit_record = Some(value.into()),
+            "trusted-dirs" => self.trusted_dirs.extend(std::env::split_paths(value)),
+            "server-socket" => self.server_socket = Some(value.into()),
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the file the selection is written to with `--handoff file`.
+    pub fn handoff_file(&self) -> PathBuf {
+        self.handoff_file
+            .clone()
+            .unwrap_or_else(|| std::env::temp_dir().join("copyrat-selection"))
+    }
 }
 
 /// Specifies which region of the terminal buffer to capture.
@@ -136,22 +398,81 @@ pub enum CaptureRegion {
     //Region(i32, i32),
 }
 
+/// Describes how copyrat, running in the temporary window, is displayed in
+/// place of the active pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum SwapStrategy {
+    /// Swap the only pane of the temporary window with the active pane, and
+    /// swap them back on exit.
+    Swap,
+    /// Select the temporary window, and go back to the previous window on
+    /// exit. This avoids rearranging the panes, but the spans are displayed
+    /// in the whole window.
+    SelectWindow,
+    /// Display nothing more: copyrat already runs over the active pane, for
+    /// instance in a `tmux display-popup`.
+    None,
+}
+
+/// Returns the arguments `args` of `tmux-copyrat`, to run it again in a tmux
+/// popup: its `--swap-strategy`, if any, is replaced with `none`.
+pub fn popup_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut popup_args = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--swap-strategy" {
+            args.next();
+        } else if !arg.starts_with("--swap-strategy=") {
+            popup_args.push(arg);
+        }
+    }
+
+    popup_args.extend(["--swap-strategy".to_string(), "none".to_string()]);
+    popup_args
+}
+
+/// Describes where copyrat is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum UiMode {
+    /// In the temporary window, see `SwapStrategy`.
+    Window,
+    /// In a tmux popup over the active pane.
+    Popup,
+}
+
+/// Describes how the selection copied to the tmux buffer is handed off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum Handoff {
+    /// Set the tmux buffer directly.
+    Buffer,
+    /// Write the selection to a file, then load the tmux buffer from it.
+    File,
+    /// Print the selection on stdout, without setting the tmux buffer.
+    Stdout,
+}
+
 /// Describes the type of buffer the selected should be copied to: either a
-/// tmux buffer or the system clipboard.
-#[derive(Clone)]
+/// tmux buffer or the system clipboard, or the pane the selection is typed
+/// into.
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
 pub enum OutputDestination {
     /// The selection will be copied to the tmux buffer.
     Tmux,
     /// The selection will be copied to the system clipboard.
     Clipboard,
+    /// The selection will be typed into the pane, as if it were typed on the
+    /// keyboard.
+    #[value(name = "type")]
+    TypeIntoPane,
 }
 
 impl OutputDestination {
-    /// Toggle between the variants of `OutputDestination`.
+    /// Toggle between the tmux buffer and the clipboard. Typing into the pane
+    /// toggles to the tmux buffer.
     pub fn toggle(&mut self) {
         match *self {
             Self::Tmux => *self = Self::Clipboard,
-            Self::Clipboard => *self = Self::Tmux,
+            Self::Clipboard | Self::TypeIntoPane => *self = Self::Tmux,
         }
     }
 }
@@ -161,6 +482,33 @@ impl fmt::Display for OutputDestination {
         match self {
             Self::Tmux => write!(f, "tmux buffer"),
             Self::Clipboard => write!(f, "clipboard"),
+            Self::TypeIntoPane => write!(f, "pane"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_swap_strategy_of_popup() {
+        let args = [
+            "run",
+            "--swap-strategy",
+            "select-window",
+            "-x",
+            "url",
+            "--swap-strategy=swap",
+        ];
+        let args = popup_args(args.map(String::from));
+        assert_eq!(args, ["run", "-x", "url", "--swap-strategy", "none"]);
+
+        let config = MainConfig::try_parse_from(
+            ["tmux-copyrat"]
+                .into_iter()
+                .chain(args.iter().map(String::as_str)),
+        );
+        assert!(config.is_ok());
+    }
+}
@@ -0,0 +1,443 @@
+//! User configuration file, so that the options and patterns don't have to be
+//! encoded in `.tmux.conf` strings.
+//!
+//! Both `copyrat` and `tmux-copyrat run` read `$XDG_CONFIG_HOME/copyrat/config.toml`
+//! (`~/.config/copyrat/config.toml` by default), if it exists:
+//!
+//! ```toml
+//! # Options, named like the tmux options without the `@copyrat-` prefix.
+//! alphabet = "qwerty-homerow"
+//! reverse = true
+//! bell = ["yank", "no-match"]
+//!
+//! # Default patterns, used when the command line selects none.
+//! pattern-names = ["url", "path"]
+//!
+//! [colors]
+//! span-fg = "green"
+//! hint-bg = "cyan"
+//!
+//! # Custom patterns, also used by default. The named ones can be bound to
+//! # keys.
+//! [[patterns]]
+//! name = "ticket"
+//! regex = "(JIRA-\\d+)"
+//!
+//! # Keys of the tmux key table, and the patterns they highlight.
+//! [bindings]
+//! j = ["ticket"]
+//! o = ["url", "path"]
+//! ```
+//!
+//! The options of the config file have the lowest precedence: the tmux
+//! options override them, and the command line options override both.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use super::{
+    basic,
+    toml::{self, Table, Value},
+};
+use crate::{textbuf::regexes, Error, Result};
+
+/// A custom pattern of the config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomPattern {
+    /// Name used by the key bindings, if any.
+    pub name: Option<String>,
+    pub regex: String,
+}
+
+/// Patterns highlighted by a key of the tmux key table.
+#[derive(Debug)]
+pub struct Binding {
+    pub key: String,
+    pub named_patterns: Vec<regexes::NamedPattern>,
+    pub custom_patterns: Vec<String>,
+}
+
+/// Configuration read from the config file.
+#[derive(Debug, Default)]
+pub struct FileConfig {
+    /// Options, named like the tmux options without the `@copyrat-` prefix.
+    pub options: Vec<(String, String)>,
+    pub named_patterns: Vec<regexes::NamedPattern>,
+    pub custom_patterns: Vec<CustomPattern>,
+    pub bindings: Vec<Binding>,
+}
+
+impl FileConfig {
+    /// Read the config file at the default path, if it exists.
+    pub fn load_default() -> Result<FileConfig> {
+        match default_path() {
+            Some(path) if path.is_file() => FileConfig::load(&path),
+            _ => Ok(FileConfig::default()),
+        }
+    }
+
+    /// Read the config file at `path`.
+    pub fn load(path: &Path) -> Result<FileConfig> {
+        let content = fs::read_to_string(path)?;
+        FileConfig::parse(&content)
+    }
+
+    /// Parse the content of a config file.
+    pub fn parse(content: &str) -> Result<FileConfig> {
+        let table = toml::parse(content)?;
+
+        let mut options = vec![];
+        for (key, value) in table.iter() {
+            match key {
+                "pattern-names" | "patterns" | "bindings" => (),
+                "colors" => {
+                    let colors = value
+                        .as_table()
+                        .ok_or_else(|| Error::InvalidConfigValue(key.into()))?;
+                    for (key, value) in colors.iter() {
+                        options.push((key.to_string(), option_value(key, value)?));
+                    }
+                }
+                key => options.push((key.to_string(), option_value(key, value)?)),
+            }
+        }
+
+        let named_patterns = match table.get("pattern-names") {
+            None => vec![],
+            Some(value) => pattern_names(value, "pattern-names")?,
+        };
+
+        let custom_patterns = custom_patterns(&table)?;
+
+        let bindings = match table.get("bindings") {
+            None => vec![],
+            Some(value) => value
+                .as_table()
+                .ok_or_else(|| Error::InvalidConfigValue("bindings".into()))?
+                .iter()
+                .map(|(key, value)| binding(key, value, &custom_patterns))
+                .collect::<Result<_>>()?,
+        };
+
+        Ok(FileConfig {
+            options,
+            named_patterns,
+            custom_patterns,
+            bindings,
+        })
+    }
+
+    /// Add the patterns of the `binding` key to the `config`, then the default
+    /// patterns if the `config` has no patterns.
+    pub fn merge_patterns_into(
+        self,
+        config: &mut basic::Config,
+        binding: Option<&str>,
+    ) -> Result<()> {
+        if let Some(key) = binding {
+            let binding = self
+                .bindings
+                .into_iter()
+                .find(|binding| binding.key == key)
+                .ok_or_else(|| Error::UnknownBinding(key.to_string()))?;
+            config.named_patterns.extend(binding.named_patterns);
+            config.custom_patterns.extend(binding.custom_patterns);
+        }
+
+        if config.has_no_patterns() {
+            config.named_patterns = self.named_patterns;
+            config.custom_patterns = self
+                .custom_patterns
+                .into_iter()
+                .map(|pattern| pattern.regex)
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// Merge the options not given on the `command_line`, and the default
+    /// patterns, into the `config` of `copyrat`.
+    pub fn merge_into(
+        self,
+        config: &mut basic::Config,
+        command_line: &basic::CommandLine,
+    ) -> Result<()> {
+        config.merge_options(&self.options, command_line)?;
+        self.merge_patterns_into(config, None)
+    }
+}
+
+/// Returns the path of the config file, in `$XDG_CONFIG_HOME` or else in
+/// `~/.config`.
+pub fn default_path() -> Option<PathBuf> {
+    config_path(
+        env::var_os("XDG_CONFIG_HOME").as_deref(),
+        env::var_os("HOME").as_deref(),
+    )
+}
+
+fn config_path(xdg_config_home: Option<&OsStr>, home: Option<&OsStr>) -> Option<PathBuf> {
+    // Relative paths are ignored, as per the XDG specification.
+    let config_dir = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| Path::new(home).join(".config")))?;
+
+    Some(config_dir.join("copyrat").join("config.toml"))
+}
+
+/// Convert the `value` of the option `key` to the string of a tmux option,
+/// arrays being comma-separated.
+fn option_value(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .ok_or_else(|| Error::InvalidConfigValue(key.into()))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|values| values.join(",")),
+        Value::Table(_) => Err(Error::InvalidConfigValue(key.into())),
+    }
+}
+
+/// Parse an array of pattern names.
+fn pattern_names(value: &Value, key: &str) -> Result<Vec<regexes::NamedPattern>> {
+    value
+        .as_array()
+        .ok_or_else(|| Error::InvalidConfigValue(key.into()))?
+        .iter()
+        .map(|name| {
+            name.as_str()
+                .ok_or_else(|| Error::InvalidConfigValue(key.into()))
+                .and_then(|name| Ok(regexes::parse_pattern_name(name)?))
+        })
+        .collect()
+}
+
+/// Parse the `[[patterns]]` entries, which have a `regex` and an optional
+/// `name`.
+fn custom_patterns(table: &Table) -> Result<Vec<CustomPattern>> {
+    let entries = match table.get("patterns") {
+        None => return Ok(vec![]),
+        Some(value) => value
+            .as_array()
+            .ok_or_else(|| Error::InvalidConfigValue("patterns".into()))?,
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            let entry = entry
+                .as_table()
+                .ok_or_else(|| Error::InvalidConfigValue("patterns".into()))?;
+            let regex = entry
+                .get("regex")
+                .and_then(Value::as_str)
+                .ok_or_else(|| Error::InvalidConfigValue("patterns.regex".into()))?;
+            let name = match entry.get("name") {
+                None => None,
+                Some(name) => Some(
+                    name.as_str()
+                        .ok_or_else(|| Error::InvalidConfigValue("patterns.name".into()))?
+                        .to_string(),
+                ),
+            };
+
+            // Invalid regexes are reported when searching.
+            if let Ok(regex) = Regex::new(regex) {
+                regexes::check_capture_group(&regex)?;
+            }
+
+            Ok(CustomPattern {
+                name,
+                regex: regex.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the binding of `key`, whose patterns are the names of custom
+/// patterns or else of named patterns.
+fn binding(key: &str, value: &Value, custom_patterns: &[CustomPattern]) -> Result<Binding> {
+    let names = value
+        .as_array()
+        .ok_or_else(|| Error::InvalidConfigValue(format!("bindings.{key}")))?;
+
+    let mut binding = Binding {
+        key: key.to_string(),
+        named_patterns: vec![],
+        custom_patterns: vec![],
+    };
+
+    for name in names {
+        let name = name
+            .as_str()
+            .ok_or_else(|| Error::InvalidConfigValue(format!("bindings.{key}")))?;
+
+        match custom_patterns
+            .iter()
+            .find(|pattern| pattern.name.as_deref() == Some(name))
+        {
+            Some(pattern) => binding.custom_patterns.push(pattern.regex.clone()),
+            None => binding
+                .named_patterns
+                .push(regexes::parse_pattern_name(name)?),
+        }
+    }
+
+    Ok(binding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    const CONTENT: &str = r#"
+alphabet = "qwerty"
+reverse = true
+recent-lines = 3
+bell = ["yank", "no-match"]
+pattern-names = ["url", "path"]
+
+[colors]
+span-fg = "green"
+
+[[patterns]]
+name = "ticket"
+regex = "(JIRA-\\d+)"
+
+[[patterns]]
+regex = "(svc-[a-z]+)"
+
+[bindings]
+j = ["ticket", "uuid"]
+"#;
+
+    fn option<'a>(config: &'a FileConfig, name: &str) -> Option<&'a str> {
+        config
+            .options
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn parse_args(args: &[&str]) -> (basic::Config, basic::CommandLine) {
+        let matches = basic::MainConfig::command().get_matches_from(args);
+        let main_config = basic::MainConfig::from_arg_matches(&matches).unwrap();
+        (
+            main_config.config,
+            basic::CommandLine::from_matches(&matches),
+        )
+    }
+
+    #[test]
+    fn parse_config_file() {
+        let config = FileConfig::parse(CONTENT).unwrap();
+
+        assert_eq!(option(&config, "alphabet"), Some("qwerty"));
+        assert_eq!(option(&config, "reverse"), Some("true"));
+        assert_eq!(option(&config, "recent-lines"), Some("3"));
+        assert_eq!(option(&config, "bell"), Some("yank,no-match"));
+        assert_eq!(option(&config, "span-fg"), Some("green"));
+        assert_eq!(option(&config, "pattern-names"), None);
+
+        assert_eq!(config.named_patterns.len(), 2);
+        assert_eq!(config.custom_patterns[0].name.as_deref(), Some("ticket"));
+        assert_eq!(config.custom_patterns[1].name, None);
+
+        let binding = &config.bindings[0];
+        assert_eq!(binding.key, "j");
+        assert_eq!(binding.custom_patterns, [r"(JIRA-\d+)"]);
+        assert_eq!(binding.named_patterns[0].0, "uuid");
+    }
+
+    #[test]
+    fn parse_config_file_errors() {
+        assert!(matches!(
+            FileConfig::parse("[bindings]\nj = [\"nope\"]"),
+            Err(Error::UnknownPatternName)
+        ));
+        assert!(matches!(
+            FileConfig::parse("[[patterns]]\nregex = \"svc-[a-z]+\""),
+            Err(Error::PatternWithoutGroup(_))
+        ));
+        assert!(matches!(
+            FileConfig::parse("reverse = [1]"),
+            Err(Error::InvalidConfigValue(_))
+        ));
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let (mut config, command_line) = parse_args(&["copyrat", "-k", "azerty", "-X", "(a+)"]);
+        FileConfig::parse(CONTENT)
+            .unwrap()
+            .merge_into(&mut config, &command_line)
+            .unwrap();
+
+        assert_eq!(config.alphabet.0, alphabet_letters("azerty"));
+        assert!(config.reverse);
+        assert_eq!(config.recent_lines, Some(3));
+        // The command line selects patterns.
+        assert!(config.named_patterns.is_empty());
+        assert_eq!(config.custom_patterns, ["(a+)"]);
+
+        let (mut config, command_line) = parse_args(&["copyrat"]);
+        FileConfig::parse(CONTENT)
+            .unwrap()
+            .merge_into(&mut config, &command_line)
+            .unwrap();
+
+        assert_eq!(config.alphabet.0, alphabet_letters("qwerty"));
+        assert_eq!(config.named_patterns.len(), 2);
+        assert_eq!(config.custom_patterns, [r"(JIRA-\d+)", "(svc-[a-z]+)"]);
+    }
+
+    #[test]
+    fn merge_binding_patterns() {
+        let (mut config, _) = parse_args(&["copyrat"]);
+        let file_config = FileConfig::parse(CONTENT).unwrap();
+        file_config
+            .merge_patterns_into(&mut config, Some("j"))
+            .unwrap();
+
+        assert_eq!(config.custom_patterns, [r"(JIRA-\d+)"]);
+        assert_eq!(config.named_patterns.len(), 1);
+
+        let file_config = FileConfig::parse(CONTENT).unwrap();
+        assert!(matches!(
+            file_config.merge_patterns_into(&mut config, Some("k")),
+            Err(Error::UnknownBinding(_))
+        ));
+    }
+
+    #[test]
+    fn config_file_path() {
+        let path = config_path(Some(OsStr::new("/xdg")), Some(OsStr::new("/home/me")));
+        assert_eq!(path, Some(PathBuf::from("/xdg/copyrat/config.toml")));
+
+        let path = config_path(Some(OsStr::new("xdg")), Some(OsStr::new("/home/me")));
+        assert_eq!(
+            path,
+            Some(PathBuf::from("/home/me/.config/copyrat/config.toml"))
+        );
+
+        assert_eq!(config_path(None, None), None);
+    }
+
+    fn alphabet_letters(name: &str) -> String {
+        crate::textbuf::alphabet::parse_alphabet(name).unwrap().0
+    }
+}
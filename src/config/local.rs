@@ -0,0 +1,222 @@
+//! Directory-local configuration, for project-specific patterns.
+//!
+//! When `tmux-copyrat` is launched from a pane whose current path (or one of
+//! its parents) contains a `.copyrat.toml` file, its patterns are merged into
+//! the configuration, so that identifiers specific to a project (e.g. a
+//! monorepo's service names) are hintable only where relevant.
+//!
+//! A local config file can only add patterns:
+//!
+//! ```toml
+//! # Named patterns to add, see the patterns catalog.
+//! pattern-names = ["uuid", "sha"]
+//!
+//! # Custom patterns to add, with one capture group each (or a `match` group).
+//! [[patterns]]
+//! regex = "(svc-[a-z]+)"
+//! ```
+//!
+//! Because any repository could ship such a file, it is only used if it is
+//! located inside one of the trusted directories, or once approved, see
+//! `trust`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{basic, patterns_file, toml};
+use crate::{textbuf::regexes, Error, Result};
+
+/// Name of the directory-local configuration file.
+pub const FILE_NAME: &str = ".copyrat.toml";
+
+/// Patterns read from a directory-local configuration file.
+#[derive(Debug)]
+pub struct LocalConfig {
+    pub named_patterns: Vec<regexes::NamedPattern>,
+    pub custom_patterns: Vec<String>,
+}
+
+impl LocalConfig {
+    /// Read the local configuration file at `path`.
+    pub fn load(path: &Path) -> Result<LocalConfig> {
+        let content = fs::read_to_string(path)?;
+        LocalConfig::parse(&content)
+    }
+
+    /// Parse the content of a local configuration file.
+    pub fn parse(content: &str) -> Result<LocalConfig> {
+        let table = toml::parse(content)?;
+
+        let named_patterns = match table.get("pattern-names") {
+            None => vec![],
+            Some(value) => value
+                .as_array()
+                .ok_or_else(|| Error::InvalidConfigValue("pattern-names".into()))?
+                .iter()
+                .map(|name| {
+                    name.as_str()
+                        .ok_or_else(|| Error::InvalidConfigValue("pattern-names".into()))
+                        .and_then(|name| Ok(regexes::parse_pattern_name(name)?))
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        let custom_patterns = patterns_file::parse(content)?;
+        for pattern in &custom_patterns {
+            regexes::compile_custom_pattern(pattern)?;
+        }
+
+        Ok(LocalConfig {
+            named_patterns,
+            custom_patterns,
+        })
+    }
+
+    /// Add the local patterns to the `config`, before it is built so that
+    /// they are checked and expanded like the others.
+    ///
+    /// The local patterns only add to the patterns of the `config`: to all of
+    /// them if it selects none or `--all-patterns`.
+    pub fn merge_into(self, config: &mut basic::Config) {
+        if self.named_patterns.is_empty() && self.custom_patterns.is_empty() {
+            return;
+        }
+        if config.has_no_patterns() || config.use_all_patterns {
+            config.named_patterns = regexes::all_patterns();
+            config.use_all_patterns = false;
+        }
+
+        for pattern in self.named_patterns {
+            let is_selected = config
+                .named_patterns
+                .iter()
+                .any(|regexes::NamedPattern(name, _)| *name == pattern.0);
+            if !is_selected {
+                config.named_patterns.push(pattern);
+            }
+        }
+        config.custom_patterns.extend(self.custom_patterns);
+    }
+}
+
+/// Returns the path to the closest local configuration file, searching from
+/// `dir` up to the root directory.
+pub fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Returns `true` if the `file` is located inside one of the `trusted_dirs`.
+pub fn is_trusted(file: &Path, trusted_dirs: &[PathBuf]) -> bool {
+    let file = match fs::canonicalize(file) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+
+    trusted_dirs
+        .iter()
+        .filter_map(|dir| fs::canonicalize(dir).ok())
+        .any(|dir| file.starts_with(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn parse_local_config() {
+        let content = r#"
+pattern-names = ["uuid", "sha"]
+
+[[patterns]]
+regex = "(svc-[a-z]+)"
+"#;
+        let local = LocalConfig::parse(content).unwrap();
+
+        let names: Vec<&str> = local
+            .named_patterns
+            .iter()
+            .map(|regexes::NamedPattern(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["uuid", "sha"]);
+        assert_eq!(local.custom_patterns, ["(svc-[a-z]+)"]);
+    }
+
+    #[test]
+    fn parse_local_config_unknown_pattern_name() {
+        let content = r#"pattern-names = ["nope"]"#;
+        assert!(matches!(
+            LocalConfig::parse(content),
+            Err(Error::UnknownPatternName)
+        ));
+    }
+
+    #[test]
+    fn parse_local_config_pattern_without_group() {
+        let content = "[[patterns]]\nregex = \"svc-[a-z]+\"";
+        assert!(matches!(
+            LocalConfig::parse(content),
+            Err(Error::PatternWithoutGroup(_))
+        ));
+
+        // Rather than being dropped, and panicking when searching.
+        let content = "[[patterns]]\nregex = \"(svc-[a-z]+\"";
+        assert!(matches!(
+            LocalConfig::parse(content),
+            Err(Error::InvalidPattern { .. })
+        ));
+    }
+
+    #[test]
+    fn merge_local_config_into_default_patterns() {
+        let content = r#"
+pattern-names = ["parens", "url"]
+
+[[patterns]]
+regex = "(svc-[a-z]+)"
+"#;
+        let mut config =
+            basic::Config::parse_from(["copyrat", "--include-quotes", "quoted-single"]);
+        LocalConfig::parse(content).unwrap().merge_into(&mut config);
+        let config = config.build().unwrap();
+
+        let lines = ["see https://a.io on svc-api: 'x' (y)"];
+        let spans = crate::engine::scan(&lines, &config).unwrap();
+        let spans: Vec<(&str, &str)> = spans.iter().map(|span| (span.pattern, span.text)).collect();
+        assert_eq!(
+            spans,
+            [
+                ("url", "https://a.io"),
+                ("custom", "svc-api"),
+                ("quoted-single", "'x'"),
+                ("parens", "y"),
+            ]
+        );
+
+        // Without local patterns, the config is left alone.
+        let mut config = basic::Config::parse_from(["copyrat", "-x", "url"]);
+        LocalConfig::parse("").unwrap().merge_into(&mut config);
+        assert_eq!(config.named_patterns.len(), 1);
+    }
+
+    #[test]
+    fn find_and_trust() {
+        let root = std::env::temp_dir().join(format!("copyrat-local-{}", std::process::id()));
+        let project = root.join("project");
+        let subdir = project.join("src");
+        fs::create_dir_all(&subdir).unwrap();
+        fs::write(project.join(FILE_NAME), "").unwrap();
+
+        let found = find(&subdir).expect("the file in the parent dir should be found");
+        assert_eq!(found, project.join(FILE_NAME));
+
+        assert!(is_trusted(&found, std::slice::from_ref(&root)));
+        assert!(is_trusted(&found, std::slice::from_ref(&project)));
+        assert!(!is_trusted(&found, std::slice::from_ref(&subdir)));
+        assert!(!is_trusted(&found, &[]));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,2 +1,10 @@
 pub mod basic;
+pub mod builder;
+pub mod dump;
 pub mod extended;
+pub mod file;
+pub mod local;
+pub mod patterns_file;
+pub mod schema;
+pub mod toml;
+pub mod trust;
@@ -0,0 +1,104 @@
+//! Patterns files let a team share a curated set of custom patterns (ticket
+//! IDs, internal URLs, host naming schemes, ...) as a TOML file.
+//!
+//! A patterns file is a list of `[[patterns]]` entries, each holding a
+//! `regex` with one capture group:
+//!
+//! ```toml
+//! [[patterns]]
+//! regex = "(JIRA-\\d+)"
+//!
+//! [[patterns]]
+//! regex = '(https://intranet\.example\.com/\S+)'
+//! ```
+//!
+//! Patterns loaded from files are merged with the custom patterns provided on
+//! the command line, after them.
+
+use std::fs;
+use std::path::Path;
+
+use super::toml::{self, Table, Value};
+use crate::{Error, Result};
+
+/// Read the custom patterns from the patterns file at `path`.
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    parse(&content).map_err(|err| match err {
+        Error::InvalidPatternsFile(_) => Error::InvalidPatternsFile(path.display().to_string()),
+        err => err,
+    })
+}
+
+/// Parse the content of a patterns file.
+pub fn parse(content: &str) -> Result<Vec<String>> {
+    let table = toml::parse(content)?;
+
+    let entries = match table.get("patterns") {
+        None => return Ok(vec![]),
+        Some(value) => value
+            .as_array()
+            .ok_or_else(|| Error::InvalidPatternsFile(String::new()))?,
+    };
+
+    entries
+        .iter()
+        .map(|entry| {
+            entry
+                .as_table()
+                .and_then(|entry| entry.get("regex"))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| Error::InvalidPatternsFile(String::new()))
+        })
+        .collect()
+}
+
+/// Serialize the custom patterns into the content of a patterns file.
+pub fn export(patterns: &[String]) -> String {
+    let entries = patterns
+        .iter()
+        .map(|regex| {
+            let mut entry = Table::new();
+            entry.insert("regex", Value::String(regex.clone()));
+            Value::Table(entry)
+        })
+        .collect();
+
+    let mut table = Table::new();
+    table.insert("patterns", Value::Array(entries));
+
+    format!(
+        "# Custom patterns for copyrat.\n\n{}",
+        toml::to_string(&table)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_then_parse() {
+        let patterns = vec![
+            r"(JIRA-\d+)".to_string(),
+            r#"host="([a-z]+-\d{2})""#.to_string(),
+        ];
+
+        let content = export(&patterns);
+        assert!(content.contains("[[patterns]]\nregex = \"(JIRA-\\\\d+)\"\n"));
+
+        assert_eq!(parse(&content).unwrap(), patterns);
+    }
+
+    #[test]
+    fn parse_rejects_entries_without_regex() {
+        let content = "[[patterns]]\nname = \"ticket\"\n";
+        assert!(matches!(parse(content), Err(Error::InvalidPatternsFile(_))));
+    }
+
+    #[test]
+    fn parse_empty_file() {
+        assert!(parse("# nothing yet\n").unwrap().is_empty());
+    }
+}
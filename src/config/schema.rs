@@ -0,0 +1,329 @@
+//! Description of all the options, generated from their clap definitions,
+//! see `copyrat config-schema`.
+//!
+//! External tools, such as the tmux plugin script or dotfile managers, can
+//! validate a configuration against it rather than keeping their own list of
+//! the options.
+
+use std::any::TypeId;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, Args, CommandFactory, Parser};
+
+use super::{basic, extended};
+use crate::json;
+
+/// Options read by the tmux plugin config file only.
+pub const PLUGIN_OPTIONS: [&str; 5] = [
+    "window-name",
+    "window-background",
+    "window-index",
+    "keytable",
+    "keyswitch",
+];
+
+/// Description of an option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionSchema {
+    /// Name on the command line, such as `--alphabet`.
+    pub cli: String,
+    /// Id of the clap argument, such as `hint_style_arg` for `--hint-style`.
+    pub id: String,
+    /// Name of the tmux option, such as `@copyrat-alphabet`, if the option
+    /// can be set from tmux and in the config file (without the prefix). An
+    /// uppercase last part, such as in `@copyrat-action-PATTERN`, is replaced
+    /// by a name.
+    pub tmux: Option<String>,
+    /// Type of the values: `bool`, `integer`, `path`, `enum` or `string`.
+    pub kind: &'static str,
+    /// Whether the option can be given several times.
+    pub multiple: bool,
+    pub default: Option<String>,
+    /// Values of an `enum` option.
+    pub values: Vec<String>,
+    /// Binaries accepting the option: `copyrat` and `tmux-copyrat`, or only
+    /// `tmux-copyrat`.
+    pub binaries: &'static [&'static str],
+    /// First line of the help of the option.
+    pub help: String,
+}
+
+/// Returns the description of all the options of `copyrat` and of
+/// `tmux-copyrat run`.
+pub fn options() -> Vec<OptionSchema> {
+    let mut basic_command = basic::Config::command();
+    basic_command.build();
+    let mut run_command = extended::ConfigExt::augment_args(clap::Command::new("run"));
+    run_command.build();
+
+    let basic_ids: Vec<&str> = basic_command
+        .get_arguments()
+        .map(|arg| arg.get_id().as_str())
+        .collect();
+
+    run_command
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set())
+        .filter_map(|arg| {
+            let binaries: &[&str] = if basic_ids.contains(&arg.get_id().as_str()) {
+                &["copyrat", "tmux-copyrat"]
+            } else {
+                &["tmux-copyrat"]
+            };
+            describe(arg, binaries)
+        })
+        .collect()
+}
+
+/// Returns the description of the option `arg`, or `None` if it has no long
+/// name or prints the help or the version.
+fn describe(arg: &Arg, binaries: &'static [&'static str]) -> Option<OptionSchema> {
+    if matches!(
+        arg.get_action(),
+        ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+    ) {
+        return None;
+    }
+    let long = arg.get_long()?;
+
+    let values: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    let type_id = arg.get_value_parser().type_id();
+    let kind = if matches!(arg.get_action(), ArgAction::SetTrue) || type_id == TypeId::of::<bool>()
+    {
+        "bool"
+    } else if !values.is_empty() {
+        "enum"
+    } else if [
+        TypeId::of::<u8>(),
+        TypeId::of::<u16>(),
+        TypeId::of::<u64>(),
+        TypeId::of::<usize>(),
+        TypeId::of::<NonZeroUsize>(),
+        TypeId::of::<i32>(),
+    ]
+    .iter()
+    .any(|&id| type_id == id)
+    {
+        "integer"
+    } else if type_id == TypeId::of::<PathBuf>() {
+        "path"
+    } else {
+        "string"
+    };
+
+    let default = arg
+        .get_default_values()
+        .iter()
+        .map(|value| value.to_string_lossy())
+        .collect::<Vec<_>>();
+
+    Some(OptionSchema {
+        cli: format!("--{long}"),
+        id: arg.get_id().to_string(),
+        tmux: tmux_name(arg, long).map(|name| format!("@copyrat-{name}")),
+        kind,
+        multiple: matches!(arg.get_action(), ArgAction::Append),
+        default: (!default.is_empty()).then(|| default.join(",")),
+        values,
+        binaries,
+        help: arg
+            .get_help()
+            .map(|help| {
+                help.to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// Returns the name of the tmux option of `arg`, without the `@copyrat-`
+/// prefix, if the option can be set from tmux.
+///
+/// The options are named after the long name or the id of their argument,
+/// except those of `basic::OPTION_IDS`. The options adding to a list, such as
+/// `@copyrat-action-PATTERN`, are named after the first part of the value
+/// name, such as `PATTERN=TEMPLATE`.
+fn tmux_name(arg: &Arg, long: &str) -> Option<String> {
+    let id = arg.get_id().as_str();
+    if let Some(&(name, _)) = basic::OPTION_IDS
+        .iter()
+        .find(|&&(_, option_id)| option_id == id)
+    {
+        return Some(name.to_string());
+    }
+    if let Some(name) = [long.to_string(), id.replace('_', "-")]
+        .into_iter()
+        .find(|name| is_option(name))
+    {
+        return Some(name);
+    }
+
+    let value_name = arg.get_value_names()?.first()?.as_str();
+    let placeholder = value_name.split(['=', ':']).next()?;
+    is_option(&format!("{long}-{placeholder}")).then(|| format!("{long}-{placeholder}"))
+}
+
+/// Returns `true` if `name` is the name of an option, without the
+/// `@copyrat-` prefix.
+///
+/// The option is set on default configurations: unknown options are
+/// reported, while known options may fail to parse the empty value.
+pub(super) fn is_option(name: &str) -> bool {
+    let mut config = basic::Config::parse_from(["copyrat"]);
+    let extended::MainConfig::Run { mut config_ext, .. } =
+        extended::MainConfig::parse_from(["tmux-copyrat", "run"])
+    else {
+        unreachable!("The run command was parsed.");
+    };
+
+    !matches!(config.set_option(name, ""), Ok(false))
+        || !matches!(config_ext.set_option(name, ""), Ok(false))
+}
+
+impl OptionSchema {
+    /// Returns the option as a JSON object.
+    pub fn to_json(&self) -> String {
+        let strings = |values: &[String]| {
+            let values: Vec<String> = values
+                .iter()
+                .map(|value| json::string(Some(value)))
+                .collect();
+            format!("[{}]", values.join(","))
+        };
+        let binaries: Vec<String> = self.binaries.iter().map(|name| name.to_string()).collect();
+
+        format!(
+            r#"{{"cli":{},"tmux":{},"type":{},"multiple":{},"default":{},"values":{},"binaries":{},"help":{}}}"#,
+            json::string(Some(&self.cli)),
+            json::string(self.tmux.as_deref()),
+            json::string(Some(self.kind)),
+            self.multiple,
+            json::string(self.default.as_deref()),
+            strings(&self.values),
+            strings(&binaries),
+            json::string(Some(&self.help)),
+        )
+    }
+}
+
+impl fmt::Display for OptionSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}", self.cli, self.kind)?;
+        if self.multiple {
+            write!(f, ", multiple")?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, ", default: {default}")?;
+        }
+        if !self.values.is_empty() {
+            write!(f, ", values: {}", self.values.join("|"))?;
+        }
+        write!(f, ")")?;
+        if let Some(tmux) = &self.tmux {
+            write!(f, " {tmux}")?;
+        }
+        if self.binaries.len() == 1 {
+            write!(f, " [{}]", self.binaries[0])?;
+        }
+        write!(f, "\n    {}", self.help)
+    }
+}
+
+/// Returns the `options` as a JSON array.
+pub fn to_json(options: &[OptionSchema]) -> String {
+    let options: Vec<String> = options.iter().map(OptionSchema::to_json).collect();
+    format!("[{}]", options.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'o>(options: &'o [OptionSchema], cli: &str) -> &'o OptionSchema {
+        options.iter().find(|option| option.cli == cli).unwrap()
+    }
+
+    #[test]
+    fn schema_names_and_types() {
+        let options = options();
+
+        let alphabet = find(&options, "--alphabet");
+        assert_eq!(alphabet.tmux.as_deref(), Some("@copyrat-alphabet"));
+        assert_eq!(alphabet.kind, "string");
+        assert_eq!(alphabet.binaries, ["copyrat", "tmux-copyrat"]);
+
+        let reverse = find(&options, "--reverse");
+        assert_eq!(
+            (reverse.kind, reverse.default.as_deref()),
+            ("bool", Some("false"))
+        );
+
+        assert_eq!(
+            find(&options, "--loop").tmux.as_deref(),
+            Some("@copyrat-loop")
+        );
+        assert_eq!(
+            find(&options, "--show-status-bar").tmux.as_deref(),
+            Some("@copyrat-status-bar")
+        );
+        let action = find(&options, "--action");
+        assert_eq!(action.tmux.as_deref(), Some("@copyrat-action-PATTERN"));
+        assert!(action.multiple);
+        assert_eq!(
+            find(&options, "--pattern-color").tmux.as_deref(),
+            Some("@copyrat-pattern-color-PATTERN")
+        );
+        assert_eq!(find(&options, "--pattern-name").tmux, None);
+        assert_eq!(find(&options, "--recent-lines").kind, "integer");
+        assert_eq!(find(&options, "--pick-nth").kind, "integer");
+
+        let swap = find(&options, "--swap-strategy");
+        assert_eq!(swap.kind, "enum");
+        assert_eq!(swap.default.as_deref(), Some("swap"));
+        assert!(swap.values.contains(&"select-window".to_string()));
+        assert_eq!(swap.binaries, ["tmux-copyrat"]);
+        assert_eq!(swap.tmux.as_deref(), Some("@copyrat-swap-strategy"));
+        assert_eq!(
+            find(&options, "--paste-on-select").tmux.as_deref(),
+            Some("@copyrat-paste-on-select")
+        );
+
+        assert_eq!(
+            find(&options, "--trusted-dir").tmux.as_deref(),
+            Some("@copyrat-trusted-dirs")
+        );
+        assert!(options
+            .iter()
+            .all(|option| option.cli != "--help" && option.cli != "--version"));
+    }
+
+    #[test]
+    fn schema_json() {
+        let options = options();
+        let parsed = json::parse(&to_json(&options)).unwrap();
+
+        let json::Value::Array(parsed) = parsed else {
+            panic!("The schema should be an array.");
+        };
+        assert_eq!(parsed.len(), options.len());
+        let alphabet = parsed
+            .iter()
+            .find(|option| option.get("cli") == Some(&json::Value::String("--alphabet".into())))
+            .unwrap();
+        assert_eq!(
+            alphabet.get("tmux"),
+            Some(&json::Value::String("@copyrat-alphabet".into()))
+        );
+        assert_eq!(alphabet.get("multiple"), Some(&json::Value::Bool(false)));
+    }
+}
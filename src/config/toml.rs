@@ -0,0 +1,634 @@
+//! Minimal reader and writer for the subset of TOML used by copyrat files.
+//!
+//! Only what copyrat files need is supported: tables (`[a.b]`), arrays of
+//! tables (`[[a]]`), bare/quoted/dotted keys, basic and literal strings,
+//! integers, booleans and arrays. Multi-line strings, floats, dates and inline
+//! tables are rejected with an error.
+
+use std::fmt::Write;
+
+use crate::{Error, Result};
+
+/// A TOML value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&Table> {
+        match self {
+            Self::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+}
+
+/// A TOML table, which keeps its keys in insertion order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table(Vec<(String, Value)>);
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.0.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Insert the value under `key`, replacing any previous value.
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(existing) => *existing = value,
+            None => self.0.push((key, value)),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Parse a TOML document into its root `Table`.
+pub fn parse(src: &str) -> Result<Table> {
+    Parser::new(src).parse_document()
+}
+
+/// Serialize a `Table` into a TOML document.
+///
+/// Plain keys are written first, followed by sub-tables and arrays of tables.
+pub fn to_string(table: &Table) -> String {
+    let mut out = String::new();
+    write_table(&mut out, table, &[]);
+    out
+}
+
+fn write_table(out: &mut String, table: &Table, path: &[String]) {
+    for (key, value) in table.iter() {
+        if !is_table_like(value) {
+            writeln!(out, "{}", format_entry(key, value)).unwrap();
+        }
+    }
+
+    for (key, value) in table.iter() {
+        let mut sub_path = path.to_vec();
+        sub_path.push(format_key(key));
+
+        match value {
+            Value::Table(sub_table) => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                writeln!(out, "[{}]", sub_path.join(".")).unwrap();
+                write_table(out, sub_table, &sub_path);
+            }
+            Value::Array(items) if is_table_like(value) => {
+                for item in items {
+                    if !out.is_empty() {
+                        out.push('\n');
+                    }
+                    writeln!(out, "[[{}]]", sub_path.join(".")).unwrap();
+                    if let Value::Table(sub_table) = item {
+                        write_table(out, sub_table, &sub_path);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Tables and non-empty arrays of tables are written as sections.
+fn is_table_like(value: &Value) -> bool {
+    match value {
+        Value::Table(_) => true,
+        Value::Array(items) => {
+            !items.is_empty() && items.iter().all(|item| matches!(item, Value::Table(_)))
+        }
+        _ => false,
+    }
+}
+
+/// Format the `key = value` line of a plain key.
+pub fn format_entry(key: &str, value: &Value) -> String {
+    format!("{} = {}", format_key(key), format_value(value))
+}
+
+fn format_key(key: &str) -> String {
+    let is_bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if is_bare {
+        key.to_string()
+    } else {
+        format_string(key)
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format_string(s),
+        Value::Integer(i) => i.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(format_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Table(table) => {
+            let items: Vec<String> = table
+                .iter()
+                .map(|(k, v)| format!("{} = {}", format_key(k), format_value(v)))
+                .collect();
+            format!("{{ {} }}", items.join(", "))
+        }
+    }
+}
+
+/// Format `s` as a TOML basic string, escaping as necessary.
+fn format_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04X}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn new(src: &str) -> Self {
+        Parser {
+            chars: src.chars().collect(),
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn error(&self, reason: impl Into<String>) -> Error {
+        Error::InvalidToml {
+            line: self.line,
+            reason: reason.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected `{expected}`, found `{c}`"))),
+            None => Err(self.error(format!("expected `{expected}`, found end of file"))),
+        }
+    }
+
+    /// Skip spaces and tabs.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.next();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.next();
+            }
+        }
+    }
+
+    /// Skip whitespace, comments and newlines.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') | Some('\r') => {
+                    self.next();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    /// Expect nothing but whitespace or a comment until the end of the line.
+    fn expect_end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some('\r') => {
+                self.next();
+                self.expect('\n')
+            }
+            Some(c) => Err(self.error(format!("unexpected `{c}` after value"))),
+        }
+    }
+
+    fn parse_document(mut self) -> Result<Table> {
+        let mut root = Table::new();
+        let mut current: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_blank();
+            let Some(c) = self.peek() else {
+                break;
+            };
+
+            if c == '[' {
+                self.next();
+                let is_array = self.peek() == Some('[');
+                if is_array {
+                    self.next();
+                }
+                self.skip_whitespace();
+                let path = self.parse_key()?;
+                self.skip_whitespace();
+                self.expect(']')?;
+                if is_array {
+                    self.expect(']')?;
+                }
+                self.expect_end_of_line()?;
+
+                if is_array {
+                    self.push_array_table(&mut root, &path)?;
+                } else {
+                    self.ensure_table(&mut root, &path)?;
+                }
+                current = path;
+            } else {
+                let key = self.parse_key()?;
+                self.skip_whitespace();
+                self.expect('=')?;
+                self.skip_whitespace();
+                let value = self.parse_value()?;
+                self.expect_end_of_line()?;
+
+                let (last, parents) = key.split_last().expect("keys are never empty");
+                let mut path = current.clone();
+                path.extend_from_slice(parents);
+                let table = self.ensure_table(&mut root, &path)?;
+                if table.get(last).is_some() {
+                    return Err(self.error(format!("duplicate key `{last}`")));
+                }
+                table.insert(last.clone(), value);
+            }
+        }
+
+        Ok(root)
+    }
+
+    /// Parse a possibly dotted key, such as `a`, `"a b"` or `a.b`.
+    fn parse_key(&mut self) -> Result<Vec<String>> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let part = match self.peek() {
+                Some('"') => self.parse_basic_string()?,
+                Some('\'') => self.parse_literal_string()?,
+                _ => {
+                    let mut part = String::new();
+                    while let Some(c) = self.peek() {
+                        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                            part.push(c);
+                            self.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    if part.is_empty() {
+                        return Err(self.error("expected a key"));
+                    }
+                    part
+                }
+            };
+            parts.push(part);
+
+            self.skip_whitespace();
+            if self.peek() == Some('.') {
+                self.next();
+            } else {
+                break;
+            }
+        }
+        Ok(parts)
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.parse_basic_string()?)),
+            Some('\'') => Ok(Value::String(self.parse_literal_string()?)),
+            Some('[') => self.parse_array(),
+            Some('t') | Some('f') => {
+                let word = self.take_word();
+                match word.as_str() {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    _ => Err(self.error(format!("invalid value `{word}`"))),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+                let word = self.take_word();
+                word.replace('_', "")
+                    .parse::<i64>()
+                    .map(Value::Integer)
+                    .map_err(|_| self.error(format!("invalid integer `{word}`")))
+            }
+            Some(c) => Err(self.error(format!("unsupported value starting with `{c}`"))),
+            None => Err(self.error("expected a value, found end of file")),
+        }
+    }
+
+    fn take_word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+' {
+                word.push(c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        word
+    }
+
+    fn parse_array(&mut self) -> Result<Value> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(']') {
+                self.next();
+                break;
+            }
+            items.push(self.parse_value()?);
+            self.skip_blank();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => break,
+                _ => return Err(self.error("expected `,` or `]` in array")),
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn parse_literal_string(&mut self) -> Result<String> {
+        self.expect('\'')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => {
+                    self.next();
+                    return Ok(s);
+                }
+                Some(c) => {
+                    self.next();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    fn parse_basic_string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                _ => (),
+            }
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some(u @ ('u' | 'U')) => {
+                            let len = if u == 'u' { 4 } else { 8 };
+                            let hex: String = (0..len).filter_map(|_| self.next()).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error(format!("invalid escape `\\{u}{hex}`")))?
+                        }
+                        Some(c) => return Err(self.error(format!("invalid escape `\\{c}`"))),
+                        None => return Err(self.error("unterminated string")),
+                    };
+                    s.push(escaped);
+                }
+                Some(c) => s.push(c),
+                None => unreachable!("end of file is checked above"),
+            }
+        }
+    }
+
+    /// Return the table at `path`, creating missing tables along the way. For
+    /// arrays of tables, the last table of the array is used.
+    fn ensure_table<'t>(&self, root: &'t mut Table, path: &[String]) -> Result<&'t mut Table> {
+        let mut table = root;
+        for key in path {
+            if table.get(key).is_none() {
+                table.insert(key.clone(), Value::Table(Table::new()));
+            }
+            table = match table.get_mut(key) {
+                Some(Value::Table(sub_table)) => sub_table,
+                Some(Value::Array(items)) => match items.last_mut() {
+                    Some(Value::Table(sub_table)) => sub_table,
+                    _ => return Err(self.error(format!("`{key}` is not a table"))),
+                },
+                _ => return Err(self.error(format!("`{key}` is not a table"))),
+            };
+        }
+        Ok(table)
+    }
+
+    /// Append a new table to the array of tables at `path`.
+    fn push_array_table(&self, root: &mut Table, path: &[String]) -> Result<()> {
+        let (last, parents) = path.split_last().expect("keys are never empty");
+        let parent = self.ensure_table(root, parents)?;
+        match parent.get_mut(last) {
+            None => parent.insert(last.clone(), Value::Array(vec![Value::Table(Table::new())])),
+            Some(Value::Array(items)) => items.push(Value::Table(Table::new())),
+            Some(_) => return Err(self.error(format!("`{last}` is not an array of tables"))),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scalars_and_tables() {
+        let src = r#"
+# comment
+alphabet = "qwerty" # trailing comment
+reverse = true
+count = 42
+
+[colors]
+span-fg = 'blue'
+"hint fg" = "yellow"
+"#;
+        let table = parse(src).unwrap();
+
+        assert_eq!(table.get("alphabet").unwrap().as_str(), Some("qwerty"));
+        assert_eq!(table.get("reverse").unwrap().as_bool(), Some(true));
+        assert_eq!(table.get("count").unwrap().as_integer(), Some(42));
+
+        let colors = table.get("colors").unwrap().as_table().unwrap();
+        assert_eq!(colors.get("span-fg").unwrap().as_str(), Some("blue"));
+        assert_eq!(colors.get("hint fg").unwrap().as_str(), Some("yellow"));
+    }
+
+    #[test]
+    fn parse_arrays_of_tables() {
+        let src = r#"
+[[patterns]]
+regex = "(JIRA-\\d+)"
+
+[[patterns]]
+regex = '(https://intranet\.example\.com\S+)'
+"#;
+        let table = parse(src).unwrap();
+        let patterns = table.get("patterns").unwrap().as_array().unwrap();
+
+        assert_eq!(patterns.len(), 2);
+        let regexes: Vec<&str> = patterns
+            .iter()
+            .map(|p| {
+                p.as_table()
+                    .unwrap()
+                    .get("regex")
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            regexes,
+            [r"(JIRA-\d+)", r"(https://intranet\.example\.com\S+)"]
+        );
+    }
+
+    #[test]
+    fn parse_multiline_array() {
+        let src = "names = [\n  \"url\", # urls\n  \"path\",\n]\n";
+        let table = parse(src).unwrap();
+        let names = table.get("names").unwrap().as_array().unwrap();
+        assert_eq!(
+            names,
+            [Value::String("url".into()), Value::String("path".into())]
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_line() {
+        let src = "a = 1\nb = \"unterminated\n";
+        match parse(src) {
+            Err(Error::InvalidToml { line, .. }) => assert_eq!(line, 2),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        assert!(
+            parse("a = 1\na = 2").is_err(),
+            "duplicate keys are rejected"
+        );
+        assert!(parse("a = 1.5").is_err(), "floats are not supported");
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut pattern = Table::new();
+        pattern.insert("regex", Value::String(r#"("quoted" \d+)"#.into()));
+
+        let mut colors = Table::new();
+        colors.insert("span-fg", Value::String("blue".into()));
+
+        let mut table = Table::new();
+        table.insert("reverse", Value::Boolean(false));
+        table.insert("colors", Value::Table(colors));
+        table.insert("patterns", Value::Array(vec![Value::Table(pattern)]));
+
+        let text = to_string(&table);
+        assert_eq!(
+            text,
+            "reverse = false\n\n[colors]\nspan-fg = \"blue\"\n\n[[patterns]]\nregex = \"(\\\"quoted\\\" \\\\d+)\"\n"
+        );
+        assert_eq!(parse(&text).unwrap(), table);
+    }
+}
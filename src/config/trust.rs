@@ -0,0 +1,246 @@
+//! Trust-on-first-use of the directory-local configuration files.
+//!
+//! A `.copyrat.toml` file located outside of the trusted directories is only
+//! used once approved: `tmux-copyrat` then asks for confirmation in the tmux
+//! status line, and `tmux-copyrat trust PATH` records the SHA-256 hash of the
+//! approved file in the trust store, `~/.local/state/copyrat/trust`. If the
+//! file changes, it must be approved again.
+//!
+//! The trust store has one line per approved file: its hash, a space, and its
+//! canonical path.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+/// Hashes of the approved local configuration files.
+#[derive(Debug, Default)]
+pub struct TrustStore {
+    /// Hash and canonical path of each approved file.
+    entries: Vec<(String, PathBuf)>,
+}
+
+impl TrustStore {
+    /// Read the trust store at `path`, which is empty if the file does not
+    /// exist yet.
+    pub fn load(path: &Path) -> Result<TrustStore> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(TrustStore::parse(&content)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(TrustStore::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Parse the content of a trust store, skipping the malformed lines.
+    fn parse(content: &str) -> TrustStore {
+        let entries = content
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(hash, path)| (hash.to_string(), PathBuf::from(path)))
+            .collect();
+
+        TrustStore { entries }
+    }
+
+    /// Returns `true` if the local configuration `file` was approved with
+    /// this `content`.
+    pub fn is_approved(&self, file: &Path, content: &str) -> bool {
+        let hash = sha256_hex(content.as_bytes());
+        self.entries
+            .iter()
+            .any(|(approved, path)| *approved == hash && path == file)
+    }
+
+    /// Approve the local configuration `file` with this `content`, replacing
+    /// a previous approval of the file.
+    pub fn approve(&mut self, file: &Path, content: &str) {
+        self.entries.retain(|(_, path)| path != file);
+        self.entries
+            .push((sha256_hex(content.as_bytes()), file.to_path_buf()));
+    }
+
+    /// Write the trust store to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content: String = self
+            .entries
+            .iter()
+            .map(|(hash, path)| format!("{hash} {}\n", path.display()))
+            .collect();
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+}
+
+/// Returns the path of the trust store, in `$XDG_STATE_HOME` or
+/// `~/.local/state`, or `None` if neither is set.
+pub fn default_path() -> Option<PathBuf> {
+    state_path(
+        env::var_os("XDG_STATE_HOME").as_deref(),
+        env::var_os("HOME").as_deref(),
+        "trust",
+    )
+}
+
+/// Returns the path of the state file `name` of copyrat, in
+/// `$XDG_STATE_HOME/copyrat` or `~/.local/state/copyrat`.
+pub(crate) fn state_path(
+    xdg_state_home: Option<&OsStr>,
+    home: Option<&OsStr>,
+    name: &str,
+) -> Option<PathBuf> {
+    // Relative paths are ignored, as per the XDG specification.
+    let state_dir = xdg_state_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| Path::new(home).join(".local").join("state")))?;
+
+    Some(state_dir.join("copyrat").join(name))
+}
+
+/// Returns the SHA-256 hash of the `bytes`, in lowercase hexadecimal.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // Padding: a 1 bit, zeros, then the length in bits on 64 bits.
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((bytes.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    state.iter().map(|word| format!("{word:08x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_sha256() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks after padding.
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn approve_local_config() {
+        let file = Path::new("/home/me/repo/.copyrat.toml");
+        let content = "pattern-names = [\"uuid\"]\n";
+        let mut store = TrustStore::default();
+        assert!(!store.is_approved(file, content));
+
+        store.approve(file, content);
+        assert!(store.is_approved(file, content));
+        assert!(!store.is_approved(file, "pattern-names = [\"sha\"]\n"));
+        assert!(!store.is_approved(Path::new("/tmp/.copyrat.toml"), content));
+
+        // Approving again replaces the previous approval.
+        store.approve(file, "changed");
+        assert!(!store.is_approved(file, content));
+
+        let saved: String = store
+            .entries
+            .iter()
+            .map(|(hash, path)| format!("{hash} {}\n", path.display()))
+            .collect();
+        let store = TrustStore::parse(&saved);
+        assert!(store.is_approved(file, "changed"));
+    }
+
+    #[test]
+    fn trust_store_path() {
+        assert_eq!(
+            state_path(
+                Some(OsStr::new("/xdg")),
+                Some(OsStr::new("/home/me")),
+                "trust"
+            ),
+            Some(PathBuf::from("/xdg/copyrat/trust"))
+        );
+        assert_eq!(
+            state_path(
+                Some(OsStr::new("relative")),
+                Some(OsStr::new("/home/me")),
+                "trust"
+            ),
+            Some(PathBuf::from("/home/me/.local/state/copyrat/trust"))
+        );
+        assert_eq!(state_path(None, None, "trust"), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! Differences between the texts of two selected spans, see `--diff`.
+//!
+//! This compares two similar spans printed close together, such as two
+//! container ids or two versions: instead of their texts, the selection is
+//! their unified diff, or only the parts of their texts which differ.
+
+use clap::{Parser, ValueEnum};
+
+/// Describes what is selected instead of the texts of two spans.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum DiffMode {
+    /// The unified diff of the lines of the texts.
+    Unified,
+    /// The parts of the texts between their common prefix and suffix.
+    Substring,
+}
+
+impl DiffMode {
+    /// Returns the difference between the texts `old` and `new`. Their
+    /// differing substrings are joined by the `separator`.
+    pub fn apply(&self, old: &str, new: &str, separator: &str) -> String {
+        match self {
+            Self::Unified => unified(old, new),
+            Self::Substring => {
+                let (old, new) = differing_substrings(old, new);
+                [old, new].join(separator)
+            }
+        }
+    }
+}
+
+/// Returns the parts of `old` and `new` left once their common prefix and
+/// suffix are removed, which are empty if the texts are equal.
+///
+/// The prefix and the suffix do not overlap, and do not split a char.
+pub fn differing_substrings<'t>(old: &'t str, new: &'t str) -> (&'t str, &'t str) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+
+    let suffix: usize = old
+        .chars()
+        .rev()
+        .zip(new.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+
+    (&old[..old.len() - suffix], &new[..new.len() - suffix])
+}
+
+/// Returns the unified diff of the lines of `old` and `new`, as a single
+/// hunk showing all their lines.
+pub fn unified(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Length of the longest common subsequence of the lines after `i` and
+    // `j`.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = format!(
+        "--- a\n+++ b\n@@ -{} +{} @@\n",
+        hunk_range(old.len()),
+        hunk_range(new.len())
+    );
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push_str(&format!(" {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+
+    diff
+}
+
+/// Returns the range of a hunk starting at the first line with `len` lines,
+/// such as `1,3`.
+fn hunk_range(len: usize) -> String {
+    match len {
+        0 => "0,0".to_string(),
+        1 => "1".to_string(),
+        len => format!("1,{len}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_substrings() {
+        assert_eq!(
+            differing_substrings("sha256:4f1a2b", "sha256:4f9c2b"),
+            ("1a", "9c")
+        );
+        assert_eq!(differing_substrings("v1.2.3", "v1.2.3"), ("", ""));
+        // The common prefix and suffix do not overlap.
+        assert_eq!(differing_substrings("aa", "aaa"), ("", "a"));
+        assert_eq!(differing_substrings("été", "ete"), ("été", "ete"));
+        assert_eq!(differing_substrings("kééa", "kéüa"), ("é", "ü"));
+
+        assert_eq!(DiffMode::Substring.apply("v1.2.3", "v1.4.3", " "), "2 4");
+    }
+
+    #[test]
+    fn diff_unified() {
+        assert_eq!(
+            unified("v1.2.3", "v1.2.4"),
+            "--- a\n+++ b\n@@ -1 +1 @@\n-v1.2.3\n+v1.2.4\n"
+        );
+        assert_eq!(
+            unified("a\nb\nc", "a\nc\nd"),
+            "--- a\n+++ b\n@@ -1,3 +1,3 @@\n a\n-b\n c\n+d\n"
+        );
+        assert_eq!(unified("", "a"), "--- a\n+++ b\n@@ -0,0 +1 @@\n+a\n");
+    }
+}
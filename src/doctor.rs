@@ -0,0 +1,358 @@
+//! Self-diagnosis of the tmux integration, for `tmux-copyrat doctor`.
+//!
+//! Each check reports what it found and, if something is wrong, how to fix
+//! it: the tmux version, the config file, the `@copyrat-*` options, the
+//! clipboard executable and the capture of the active pane.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use clap::Parser;
+
+use crate::clipboard::{self, ClipboardBackend};
+use crate::config::{
+    basic::CommandLine,
+    extended::{CaptureRegion, ConfigExt, MainConfig},
+    file::{self, FileConfig},
+    schema::PLUGIN_OPTIONS,
+};
+use crate::tmux;
+
+/// Oldest tmux version known to work, see the README.
+const MIN_TMUX_VERSION: (u32, u32) = (3, 0);
+
+/// Oldest tmux version supporting `@copyrat-ui-mode popup`.
+const MIN_POPUP_TMUX_VERSION: (u32, u32) = (3, 3);
+
+/// Outcome of a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Copyrat works, but maybe not as expected.
+    Warning,
+    /// Copyrat does not work: the message tells how to fix it.
+    Failed,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok => write!(f, "ok"),
+            Self::Warning => write!(f, "warning"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Result of a check, such as the tmux version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: Status,
+    /// What is checked, such as `tmux` or `clipboard`.
+    pub name: String,
+    pub message: String,
+}
+
+impl Check {
+    fn new(status: Status, name: &str, message: impl Into<String>) -> Check {
+        Check {
+            status,
+            name: name.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = self.status.to_string();
+        write!(f, "{status:<7} {}: {}", self.name, self.message)
+    }
+}
+
+/// Run all the checks, with the `config_ext` and the `command_line` of
+/// `tmux-copyrat doctor`, and return their results.
+///
+/// The checks needing a tmux session are skipped outside of one.
+pub fn run(config_ext: ConfigExt, command_line: &CommandLine) -> Vec<Check> {
+    let mut checks = vec![check_tmux_version()];
+
+    let in_session = tmux::is_inside_session();
+    if !in_session {
+        checks.push(Check::new(
+            Status::Warning,
+            "tmux session",
+            "not running inside tmux: run `tmux-copyrat doctor` from a tmux pane to check the options and the capture",
+        ));
+    }
+
+    let file_config = match FileConfig::load_default() {
+        Ok(file_config) => {
+            checks.push(check_config_file(&file_config));
+            file_config
+        }
+        Err(err) => {
+            let path = file::default_path().unwrap_or_default();
+            checks.push(Check::new(
+                Status::Failed,
+                "config file",
+                format!("{err}: fix or remove {}", path.display()),
+            ));
+            FileConfig::default()
+        }
+    };
+
+    if in_session && !config_ext.ignore_tmux_options {
+        match tmux::get_options("@copyrat-") {
+            Ok(options) => checks.extend(check_options(&options)),
+            Err(err) => checks.push(Check::new(Status::Failed, "tmux options", err.to_string())),
+        }
+    }
+
+    match config_ext.build(file_config, command_line) {
+        Ok(config) => {
+            checks.push(check_clipboard(&config));
+            if in_session {
+                checks.push(check_capture(&config));
+            }
+        }
+        Err(err) => checks.push(Check::new(
+            Status::Failed,
+            "configuration",
+            format!("{err}: fix the options of tmux, of the config file or of the key binding"),
+        )),
+    }
+
+    checks
+}
+
+/// Check that tmux can be run, and that its version is supported.
+fn check_tmux_version() -> Check {
+    match duct::cmd!("tmux", "-V").stderr_null().read() {
+        Ok(output) => version_check(&output),
+        Err(err) => Check::new(
+            Status::Failed,
+            "tmux",
+            format!(
+                "cannot run `tmux -V` ({err}): install tmux {}.{} or later",
+                MIN_TMUX_VERSION.0, MIN_TMUX_VERSION.1
+            ),
+        ),
+    }
+}
+
+/// Returns the check of the tmux version printed by `tmux -V`.
+fn version_check(output: &str) -> Check {
+    let version = output.trim();
+    match parse_version(version) {
+        Some(found) if found < MIN_TMUX_VERSION => Check::new(
+            Status::Failed,
+            "tmux",
+            format!(
+                "{version} is not supported: upgrade to tmux {}.{} or later",
+                MIN_TMUX_VERSION.0, MIN_TMUX_VERSION.1
+            ),
+        ),
+        Some(found) if found < MIN_POPUP_TMUX_VERSION => Check::new(
+            Status::Warning,
+            "tmux",
+            format!(
+                "{version} does not support `@copyrat-ui-mode popup`, which needs tmux {}.{}",
+                MIN_POPUP_TMUX_VERSION.0, MIN_POPUP_TMUX_VERSION.1
+            ),
+        ),
+        Some(_) => Check::new(Status::Ok, "tmux", version),
+        // Development versions, such as `tmux next-3.5` or `tmux master`.
+        None => Check::new(
+            Status::Warning,
+            "tmux",
+            format!("cannot tell if {version} is supported"),
+        ),
+    }
+}
+
+/// Returns the major and minor version of `tmux -V`, such as `(3, 3)` for
+/// `tmux 3.3a`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, rest) = version.strip_prefix("tmux ")?.split_once('.')?;
+    let minor_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    Some((major.parse().ok()?, rest[..minor_len].parse().ok()?))
+}
+
+/// Check the options and patterns of the config file.
+fn check_config_file(file_config: &FileConfig) -> Check {
+    match file::default_path() {
+        Some(path) if path.is_file() => Check::new(
+            Status::Ok,
+            "config file",
+            format!(
+                "{} options and {} bindings read from {}",
+                file_config.options.len(),
+                file_config.bindings.len(),
+                path.display()
+            ),
+        ),
+        _ => Check::new(Status::Ok, "config file", "none"),
+    }
+}
+
+/// Returns the checks of the `@copyrat-*` tmux `options`: one for each
+/// invalid or unknown option, or a single one if they are all valid.
+fn check_options(options: &BTreeMap<String, String>) -> Vec<Check> {
+    let Ok(MainConfig::Run {
+        config_ext: mut config,
+        ..
+    }) = MainConfig::try_parse_from(["tmux-copyrat", "run"])
+    else {
+        return vec![];
+    };
+
+    let mut checks = vec![];
+    for (option, value) in options {
+        let Some(name) = option.strip_prefix("@copyrat-") else {
+            continue;
+        };
+        let known = config
+            .set_option(name, value)
+            .and_then(|known| Ok(known || config.basic_config.set_option(name, value)?));
+        match known {
+            Ok(true) => (),
+            Ok(false) if PLUGIN_OPTIONS.contains(&name) => (),
+            Ok(false) => checks.push(Check::new(
+                Status::Warning,
+                option,
+                "unknown option, ignored: check its name in CONFIGURATION.md",
+            )),
+            Err(err) => checks.push(Check::new(
+                Status::Failed,
+                option,
+                format!("invalid value `{value}`: {err}"),
+            )),
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(Check::new(
+            Status::Ok,
+            "tmux options",
+            format!("{} @copyrat-* options parsed", options.len()),
+        ));
+    }
+    checks
+}
+
+/// Check that the selections can be copied to the system clipboard.
+fn check_clipboard(config: &ConfigExt) -> Check {
+    let exe = &config.clipboard_exe;
+    match clipboard::resolve_current(&config.clipboard_backend, exe) {
+        ClipboardBackend::Osc52 => Check::new(
+            Status::Ok,
+            "clipboard",
+            "OSC 52 escape sequence: the terminal must support it, and tmux `set-clipboard` must not be `off`",
+        ),
+        _ if clipboard::command_exists(exe) => {
+            Check::new(Status::Ok, "clipboard", format!("`{exe}`"))
+        }
+        _ => Check::new(
+            Status::Failed,
+            "clipboard",
+            format!(
+                "`{exe}` is not found in $PATH: install it, set @copyrat-clipboard-exe to the clipboard command, or set @copyrat-clipboard-backend to osc52"
+            ),
+        ),
+    }
+}
+
+/// Check that the active pane can be captured.
+fn check_capture(config: &ConfigExt) -> Check {
+    let capture = tmux::available_panes()
+        .map_err(|err| err.to_string())
+        .and_then(|panes| {
+            let pane = panes
+                .into_iter()
+                .find(|pane| pane.is_active)
+                .ok_or_else(|| "no active pane".to_string())?;
+            let buffer = pane
+                .capture(
+                    &CaptureRegion::VisibleArea,
+                    config.basic_config.preserve_colors,
+                )
+                .map_err(|err| err.to_string())?;
+            Ok((pane.id, buffer))
+        });
+
+    match capture {
+        Ok((id, buffer)) => Check::new(
+            Status::Ok,
+            "capture",
+            format!(
+                "{} lines captured from pane {id}",
+                buffer.split('\n').count()
+            ),
+        ),
+        Err(err) => Check::new(
+            Status::Failed,
+            "capture",
+            format!("{err}: check that tmux-copyrat runs in the same tmux server as the pane"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_tmux_versions() {
+        assert_eq!(parse_version("tmux 3.3a"), Some((3, 3)));
+        assert_eq!(parse_version("tmux 3.0"), Some((3, 0)));
+        assert_eq!(parse_version("tmux next-3.5"), None);
+
+        assert_eq!(version_check("tmux 3.4\n").status, Status::Ok);
+        assert_eq!(version_check("tmux 3.2a").status, Status::Warning);
+        assert_eq!(version_check("tmux master").status, Status::Warning);
+        let check = version_check("tmux 2.9a");
+        assert_eq!(check.status, Status::Failed);
+        assert_eq!(
+            check.to_string(),
+            "failed  tmux: tmux 2.9a is not supported: upgrade to tmux 3.0 or later"
+        );
+    }
+
+    #[test]
+    fn check_tmux_options() {
+        let options = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let checks = check_options(&options(&[
+            ("@copyrat-keytable", "cpyrt"),
+            ("@copyrat-reverse", "true"),
+            ("@copyrat-capture-region", "entire-history"),
+        ]));
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Ok);
+
+        let checks = check_options(&options(&[
+            ("@copyrat-reverse", "yes"),
+            ("@copyrat-revers", "true"),
+            ("@copyrat-unique-hint", "true"),
+        ]));
+        let statuses: Vec<(&str, Status)> = checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("@copyrat-revers", Status::Warning),
+                ("@copyrat-reverse", Status::Failed),
+            ]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Stable API to embed copyrat in other terminal applications.
+//!
+//! Unlike [`run`](crate::run), these functions neither read the keys from
+//! stdin nor switch the terminal to raw mode: [`scan`] finds the spans in some
+//! lines, and [`render_overlay`] writes the highlighted spans and their hints
+//! to any writer, leaving the terminal to the embedding application.
+//!
+//! ```
+//! use clap::Parser;
+//! use copyrat::{config::basic, engine};
+//!
+//! let opt = basic::Config::parse_from(["copyrat", "--pattern-name", "url"]);
+//! let lines = ["see https://example.com"];
+//!
+//! let spans = engine::scan(&lines, &opt)?;
+//! assert_eq!(spans[0].text, "https://example.com");
+//!
+//! let mut screen = vec![];
+//! engine::render_overlay(&mut screen, &engine::model(&lines, &opt)?, &opt, (80, 24), None);
+//! assert!(!screen.is_empty());
+//! # Ok::<(), copyrat::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::io;
+
+use regex::{Regex, RegexSet};
+
+use crate::config::basic;
+use crate::log;
+use crate::template;
+use crate::textbuf::{self, Model, Span};
+use crate::transforms;
+use crate::ui::render::{self, Frame, Overlay, Renderer};
+use crate::ui::Selection;
+use crate::Result;
+
+/// Returns the spans found in the `lines`, with their hints, as configured by
+/// `opt`.
+///
+/// The spans are in the order of the lines, and the same on every run, see
+/// [`find_raw_spans`](crate::textbuf::find_raw_spans).
+pub fn scan<'a>(lines: &'a [&'a str], opt: &'a basic::Config) -> Result<Vec<Span<'a>>> {
+    Ok(model(lines, opt)?.spans)
+}
+
+/// Returns the model of the `lines`, holding the spans found as configured by
+/// `opt`, as needed by [`render_overlay`].
+///
+/// Fails if a pattern is not a valid regex.
+pub fn model<'a>(lines: &'a [&'a str], opt: &'a basic::Config) -> Result<Model<'a>> {
+    let raw_spans = textbuf::find_raw_spans(
+        lines,
+        &opt.named_patterns,
+        &opt.custom_patterns,
+        opt.use_all_patterns,
+        opt.prompt_pattern(),
+        &opt.pattern_priority,
+        &mut Regex::new,
+        &mut |patterns| RegexSet::new(patterns),
+    )?;
+    let model = Model::from_raw_spans(
+        lines,
+        raw_spans,
+        &opt.alphabet,
+        &opt.bound_chars,
+        opt.reverse,
+        opt.unique_hint,
+    );
+    log::debug(|| format!("{} lines: {}", lines.len(), spans_summary(&model.spans)));
+
+    Ok(model)
+}
+
+/// Returns the number of `spans`, and of spans of each pattern, such as
+/// `3 spans (sha 1, url 2)`, for the debug log.
+pub(crate) fn spans_summary(spans: &[Span]) -> String {
+    let mut counts = BTreeMap::new();
+    for span in spans {
+        *counts.entry(span.pattern).or_insert(0) += 1;
+    }
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(pattern, count)| format!("{pattern} {count}"))
+        .collect();
+
+    format!("{} spans ({})", spans.len(), counts.join(", "))
+}
+
+/// Returns `true` if a span of the `model` is selected without the UI, see
+/// [`pick`].
+pub fn is_picked(model: &Model, opt: &basic::Config) -> bool {
+    opt.pick_nth.is_some() || (opt.auto_select_single && model.spans.len() == 1)
+}
+
+/// Returns the selection of the span of the `model` picked without the UI:
+/// the Nth span with `--pick-nth`, counted from the bottom with `--reverse`,
+/// or the only span with `--auto-select-single`.
+///
+/// Returns `None` if no span is picked, such as when there are fewer than N.
+pub fn pick(model: &Model, opt: &basic::Config) -> Option<Selection> {
+    let index = match opt.pick_nth {
+        Some(nth) if opt.reverse => model.spans.len().checked_sub(nth.get())?,
+        Some(nth) => nth.get() - 1,
+        None if is_picked(model, opt) => 0,
+        None => return None,
+    };
+    let span = model.spans.get(index)?;
+
+    let text = template::span_text(&opt.capture_templates, span);
+    let text = transforms::apply(&opt.transforms, span.pattern, text);
+    Some(Selection::from_span(model.lines, span, text))
+}
+
+/// Render the lines of the `model` with its spans and their hints on the
+/// `writer`, the way copyrat does, for a screen of `screen_size` (width,
+/// height).
+///
+/// The span at `focus_index`, if any, is rendered with the focused colors.
+pub fn render_overlay(
+    writer: &mut dyn io::Write,
+    model: &Model,
+    opt: &basic::Config,
+    screen_size: (u16, u16),
+    focus_index: Option<usize>,
+) {
+    let hint_style = opt.hint_style();
+    let mut renderer = Overlay::new(
+        model.lines,
+        screen_size.0,
+        &opt.hint_alignment,
+        opt.colliding_hint_style(),
+    );
+    if !opt.reveal_secrets {
+        renderer = renderer.with_masked_secrets(model.lines, &model.spans);
+    }
+    if opt.unique_hint && opt.count_badges {
+        renderer = renderer.with_count_badges();
+    }
+    let frame = Frame {
+        model,
+        // Out of range, so that no span is focused.
+        focus_index: focus_index.unwrap_or(model.spans.len()),
+        screen_size,
+        colors: &opt.colors,
+        hint_style: &hint_style,
+        recent_from: opt
+            .recent_lines
+            .map(|count| render::first_recent_line(model.lines, count)),
+        marked: &[],
+        selected: &[],
+        scroll: 0,
+        base_text: true,
+        reveal_secrets: opt.reveal_secrets,
+        typed_hint: "",
+        styles: None,
+        expanded: None,
+    };
+
+    renderer.full_render(writer, &frame);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn scan_and_render_without_terminal() {
+        let opt = basic::Config::parse_from(["copyrat", "--pattern-name", "url"]);
+        let lines = ["see https://example.com", "and https://example.org"];
+
+        let spans = scan(&lines, &opt).unwrap();
+        let texts: Vec<&str> = spans.iter().map(|span| span.text).collect();
+        assert_eq!(texts, ["https://example.com", "https://example.org"]);
+        assert_eq!(spans_summary(&spans), "2 spans (url 2)");
+
+        let model = model(&lines, &opt).unwrap();
+        let mut focused = vec![];
+        render_overlay(&mut focused, &model, &opt, (80, 24), Some(0));
+        let mut unfocused = vec![];
+        render_overlay(&mut unfocused, &model, &opt, (80, 24), None);
+
+        let focused = String::from_utf8(focused).unwrap();
+        assert!(focused.contains("https://example.com"));
+        assert!(focused.contains(spans[1].hint.as_str()));
+        assert_ne!(focused, String::from_utf8(unfocused).unwrap());
+    }
+
+    #[test]
+    fn pick_without_ui() {
+        let lines = ["1.1.1.1 and 2.2.2.2", "then 3.3.3.3"];
+        let text = |args: &[&str]| {
+            let opt = basic::Config::parse_from([&["copyrat", "-x", "ipv4"], args].concat());
+            let model = model(&lines, &opt).unwrap();
+            assert_eq!(is_picked(&model, &opt), !args.is_empty());
+            pick(&model, &opt).map(|selection| selection.text)
+        };
+
+        assert_eq!(text(&[]), None);
+        assert_eq!(text(&["--pick-nth", "2"]).as_deref(), Some("2.2.2.2"));
+        assert_eq!(text(&["--pick-nth", "1", "-r"]).as_deref(), Some("3.3.3.3"));
+        assert_eq!(text(&["--pick-nth", "4"]), None);
+
+        // Several spans are found, so the UI is needed.
+        let opt = basic::Config::parse_from(["copyrat", "--auto-select-single"]);
+        assert!(!is_picked(&model(&lines, &opt).unwrap(), &opt));
+        let lines = ["only 1.1.1.1 here"];
+        let opt = basic::Config::parse_from(["copyrat", "-x", "ipv4", "--auto-select-single"]);
+        let selection = pick(&model(&lines, &opt).unwrap(), &opt).unwrap();
+        assert_eq!(
+            (selection.text.as_str(), selection.position),
+            ("1.1.1.1", (1, 6))
+        );
+    }
+}
@@ -1,4 +1,4 @@
-// use std::fmt;
+//! Errors of copyrat, and the exit code of the process for each of them.
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -8,15 +8,38 @@ pub enum Error {
     #[error("Unknown alphabet")]
     UnknownAlphabet,
 
+    #[error("Invalid alphabet `{0}`: expected distinct lowercase letters or symbols")]
+    InvalidAlphabet(String),
+
+    #[error("No letter of the alphabet `{0}` is left for the hints: all are bound to commands")]
+    AlphabetOfBoundKeys(String),
+
     #[error("Unknown ANSI color name: allowed values are magenta, cyan, black, ...")]
     UnknownColor,
 
     #[error("Unknown pattern name")]
     UnknownPatternName,
 
+    #[error(
+        "Pattern `{0}` has no capture group: wrap the text to copy in `(...)` or `(?P<match>...)`"
+    )]
+    PatternWithoutGroup(String),
+
+    #[error("Invalid pattern `{pattern}`: {reason}")]
+    InvalidPattern { pattern: String, reason: String },
+
+    #[error("Unknown pattern flag `{0}`: expected i, m, s, U, w or S")]
+    UnknownPatternFlag(char),
+
+    #[error("No active tmux pane in the current window")]
+    NoActivePane,
+
     #[error("Expected a pane id marker")]
     ExpectedPaneIdMarker,
 
+    #[error("Unexpected tmux pane `{line}`: {reason}")]
+    InvalidPane { line: String, reason: String },
+
     #[error("Failed parsing integer")]
     ExpectedInt {
         #[from]
@@ -35,9 +58,225 @@ pub enum Error {
     #[error("Expected the value to be within `{0}`")]
     ExpectedEnumVariant(String),
 
+    #[error("Invalid TOML at line {line}: {reason}")]
+    InvalidToml { line: usize, reason: String },
+
+    #[error("Invalid value for the configuration key `{0}`")]
+    InvalidConfigValue(String),
+
+    #[error("No binding `{0}` in the config file")]
+    UnknownBinding(String),
+
+    #[error("Invalid patterns file `{0}`: expected `[[patterns]]` entries with a `regex` string")]
+    InvalidPatternsFile(String),
+
+    #[error("Invalid `{0}`: expected `PATTERN=TEMPLATE`")]
+    InvalidPatternTemplate(String),
+
+    #[error(
+        "Invalid transform `{0}`: expected `PATTERN=TRANSFORM`, where TRANSFORM is trim-punct, expand-tilde, strip-diff-prefix, lowercase, uppercase or sh:COMMAND"
+    )]
+    UnknownTransform(String),
+
+    #[error("Invalid `{0}`: expected `NAME=REGEX`, where NAME is not a pattern of the catalog")]
+    InvalidCustomPattern(String),
+
+    #[error("Invalid `{0}`: expected `PATTERN:FG` or `PATTERN:FG:BG`")]
+    InvalidPatternColor(String),
+
+    #[error("Invalid `{0}`: expected `COMMAND=KEYS`, such as `yank=y Enter`")]
+    InvalidKeyBinding(String),
+
+    #[error("Unknown key `{0}`: expected a character, or a name like `Enter` or `C-u`")]
+    UnknownKey(String),
+
+    #[error("Post-hook failed: {0}")]
+    PostHook(String),
+
+    #[error(
+        "tmux-copyrat must run inside a tmux session: outside tmux, pipe the text to `copyrat` instead"
+    )]
+    NotInTmux,
+
+    #[error("Copyrat server error: {0}")]
+    Server(String),
+
+    #[error("Self-update failed: {0}")]
+    SelfUpdate(String),
+
+    #[error("{source}")]
+    Tmux {
+        #[from]
+        source: crate::tmux::CommandError,
+    },
+
+    #[error("{source}")]
+    Terminal {
+        #[from]
+        source: crate::ui::TerminalError,
+    },
+
+    #[error("{source}")]
+    Clipboard {
+        #[from]
+        source: crate::clipboard::ClipboardError,
+    },
+
+    #[error("Cannot write to the file descriptor {fd}: {source}")]
+    OutputFd { fd: i32, source: std::io::Error },
+
     #[error("IOError: `{source}`")]
     Io {
         #[from]
         source: std::io::Error,
     },
 }
+
+impl From<copyrat_core::Error> for Error {
+    fn from(err: copyrat_core::Error) -> Self {
+        match err {
+            copyrat_core::Error::UnknownAlphabet => Error::UnknownAlphabet,
+            copyrat_core::Error::InvalidAlphabet(letters) => Error::InvalidAlphabet(letters),
+            copyrat_core::Error::UnknownPatternName => Error::UnknownPatternName,
+            copyrat_core::Error::PatternWithoutGroup(pattern) => {
+                Error::PatternWithoutGroup(pattern)
+            }
+            copyrat_core::Error::InvalidPattern { pattern, reason } => {
+                Error::InvalidPattern { pattern, reason }
+            }
+            copyrat_core::Error::UnknownPatternFlag(flag) => Error::UnknownPatternFlag(flag),
+        }
+    }
+}
+
+/// Exit codes of `copyrat` and `tmux-copyrat`, so that scripts and the tmux
+/// plugin can tell the failures apart.
+///
+/// Nothing was selected, or no span was found, exits with `1`.
+pub mod exit_code {
+    /// Nothing was selected, or no span was found.
+    pub const NO_SELECTION: i32 = 1;
+    /// Invalid options, configuration or patterns.
+    pub const CONFIG: i32 = 2;
+    /// Not running in tmux, or a tmux command failed.
+    pub const TMUX: i32 = 3;
+    /// The selection could not be copied to the clipboard.
+    pub const CLIPBOARD: i32 = 4;
+    /// The post-hook failed.
+    pub const POST_HOOK: i32 = 5;
+    /// The copyrat server failed.
+    pub const SERVER: i32 = 6;
+    /// The self-update failed.
+    pub const SELF_UPDATE: i32 = 7;
+    /// Reading or writing a file or the terminal failed.
+    pub const IO: i32 = 8;
+}
+
+impl Error {
+    /// Returns the exit code of the process failing with this error, see
+    /// [`exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::ExpectedSurroundingPair
+            | Error::UnknownAlphabet
+            | Error::InvalidAlphabet(_)
+            | Error::AlphabetOfBoundKeys(_)
+            | Error::UnknownColor
+            | Error::UnknownPatternName
+            | Error::PatternWithoutGroup(_)
+            | Error::InvalidPattern { .. }
+            | Error::UnknownPatternFlag(_)
+            | Error::ExpectedInt { .. }
+            | Error::ExpectedBool { .. }
+            | Error::ExpectedEnumVariant(_)
+            | Error::InvalidToml { .. }
+            | Error::InvalidConfigValue(_)
+            | Error::UnknownBinding(_)
+            | Error::InvalidPatternsFile(_)
+            | Error::InvalidPatternTemplate(_)
+            | Error::UnknownTransform(_)
+            | Error::InvalidCustomPattern(_)
+            | Error::InvalidPatternColor(_)
+            | Error::InvalidKeyBinding(_)
+            | Error::UnknownKey(_) => exit_code::CONFIG,
+            Error::ExpectedPaneIdMarker
+            | Error::InvalidPane { .. }
+            | Error::ExpectedString(_)
+            | Error::NotInTmux
+            | Error::NoActivePane
+            | Error::Tmux { .. } => exit_code::TMUX,
+            Error::Clipboard { .. } => exit_code::CLIPBOARD,
+            Error::PostHook(_) => exit_code::POST_HOOK,
+            Error::Server(_) => exit_code::SERVER,
+            Error::SelfUpdate(_) => exit_code::SELF_UPDATE,
+            Error::Terminal { .. } | Error::OutputFd { .. } | Error::Io { .. } => exit_code::IO,
+        }
+    }
+
+    /// Returns the text reporting the error in the tmux status line.
+    pub fn status_message(&self) -> String {
+        format!("copyrat: {self}")
+    }
+
+    /// Print the error on stderr, then exit the process with its exit code.
+    pub fn exit(&self) -> ! {
+        eprintln!("{self}");
+        std::process::exit(self.exit_code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_exit_codes() {
+        let io_error = || std::io::Error::from(std::io::ErrorKind::NotFound);
+
+        assert_eq!(Error::UnknownColor.exit_code(), exit_code::CONFIG);
+        assert_eq!(
+            Error::from("x".parse::<u64>().unwrap_err()).exit_code(),
+            exit_code::CONFIG
+        );
+        assert_eq!(Error::NotInTmux.exit_code(), exit_code::TMUX);
+        assert_eq!(Error::NoActivePane.exit_code(), exit_code::TMUX);
+        let tmux_error = crate::tmux::CommandError::new(&["list-panes"], io_error());
+        assert_eq!(Error::from(tmux_error).exit_code(), exit_code::TMUX);
+        let clipboard_error = crate::clipboard::ClipboardError::Exec {
+            exe: "pbcopy".to_string(),
+            source: io_error(),
+        };
+        let err = Error::from(clipboard_error);
+        assert_eq!(err.exit_code(), exit_code::CLIPBOARD);
+        assert_eq!(
+            err.status_message(),
+            "copyrat: clipboard command `pbcopy` failed: entity not found"
+        );
+        assert_eq!(
+            Error::PostHook(String::new()).exit_code(),
+            exit_code::POST_HOOK
+        );
+        assert_eq!(Error::Server(String::new()).exit_code(), exit_code::SERVER);
+        assert_eq!(Error::from(io_error()).exit_code(), exit_code::IO);
+        let terminal_error = crate::ui::TerminalError::Open(io_error());
+        let err = Error::from(terminal_error);
+        assert_eq!(err.exit_code(), exit_code::IO);
+        assert_eq!(
+            err.status_message(),
+            "copyrat: Cannot open the terminal `/dev/tty`: entity not found"
+        );
+
+        // The codes are stable, and distinct from the successful exit.
+        let codes = [
+            exit_code::NO_SELECTION,
+            exit_code::CONFIG,
+            exit_code::TMUX,
+            exit_code::CLIPBOARD,
+            exit_code::POST_HOOK,
+            exit_code::SERVER,
+            exit_code::SELF_UPDATE,
+            exit_code::IO,
+        ];
+        assert_eq!(codes, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,213 @@
+//! History of the selections, for `copyrat history`, `copyrat repeat` and
+//! the `history` key of the UI.
+//!
+//! The last `--history-size` selections are kept in
+//! `~/.local/state/copyrat/history` (or in `$XDG_STATE_HOME` if set), one per
+//! line: the pattern of the selection, a tab, and its text, where the
+//! backslashes, tabs and newlines are escaped as `\\`, `\t` and `\n`. The most
+//! recent selection is on the last line.
+//!
+//! The selections of secrets are not kept, see `regexes::is_sensitive`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{extended::OutputDestination, trust};
+use crate::textbuf::regexes;
+use crate::ui::Selection;
+use crate::Result;
+
+/// Selection kept in the history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Name of the pattern, or the distinct names separated by commas if
+    /// several spans were selected.
+    pub pattern: String,
+    pub text: String,
+}
+
+impl HistoryEntry {
+    /// Returns the entry as a selection of its text, copied to the tmux
+    /// buffer.
+    pub fn to_selection(&self) -> Selection {
+        Selection {
+            text: self.text.clone(),
+            texts: vec![self.text.clone()],
+            pattern: self.pattern.clone(),
+            position: (1, 1),
+            hint: String::new(),
+            uppercased: false,
+            output_destination: OutputDestination::Tmux,
+            action: None,
+        }
+    }
+}
+
+/// Last selections, from the oldest to the most recent.
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Read the history at `path`, which is empty if the file does not exist
+    /// yet.
+    pub fn load(path: &Path) -> Result<History> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(History::parse(&content)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(History::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Parse the content of a history file, skipping the malformed lines.
+    fn parse(content: &str) -> History {
+        let entries = content
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(pattern, text)| HistoryEntry {
+                pattern: pattern.to_string(),
+                text: unescape(text),
+            })
+            .collect();
+
+        History { entries }
+    }
+
+    /// Returns the entries, from the most recent to the oldest.
+    pub fn recent(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter().rev()
+    }
+
+    /// Add the `entry` as the most recent one, removing a previous entry of
+    /// the same text, and keep only the last `size` entries.
+    pub fn push(&mut self, entry: HistoryEntry, size: usize) {
+        self.entries.retain(|previous| previous.text != entry.text);
+        self.entries.push(entry);
+        let excess = self.entries.len().saturating_sub(size);
+        self.entries.drain(..excess);
+    }
+
+    /// Write the history to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let content: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{}\t{}\n", entry.pattern, escape(&entry.text)))
+            .collect();
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+}
+
+/// Returns the path of the history, in `$XDG_STATE_HOME` or
+/// `~/.local/state`, or `None` if neither is set.
+pub fn default_path() -> Option<PathBuf> {
+    trust::state_path(
+        env::var_os("XDG_STATE_HOME").as_deref(),
+        env::var_os("HOME").as_deref(),
+        "history",
+    )
+}
+
+/// Returns the last `size` selections of the default history, from the most
+/// recent to the oldest.
+pub fn load_default(size: usize) -> Result<Vec<HistoryEntry>> {
+    if size == 0 {
+        return Ok(vec![]);
+    }
+    let Some(path) = default_path() else {
+        return Ok(vec![]);
+    };
+
+    Ok(History::load(&path)?.recent().take(size).cloned().collect())
+}
+
+/// Add the `selection` to the default history, keeping its last `size`
+/// selections, unless it is a secret.
+pub fn record(selection: &Selection, size: usize) -> Result<()> {
+    let is_sensitive = selection.pattern.split(',').any(regexes::is_sensitive);
+    if size == 0 || is_sensitive {
+        return Ok(());
+    }
+    let Some(path) = default_path() else {
+        return Ok(());
+    };
+
+    let mut history = History::load(&path)?;
+    let entry = HistoryEntry {
+        pattern: selection.pattern.clone(),
+        text: selection.text.clone(),
+    };
+    history.push(entry, size);
+    history.save(&path)
+}
+
+/// Returns the `text` with its backslashes, tabs and newlines escaped, so
+/// that it fits on a line of the history.
+pub fn escape(text: &str) -> String {
+    text.replace('\\', r"\\")
+        .replace('\t', r"\t")
+        .replace('\n', r"\n")
+}
+
+/// Returns the `text` escaped by `escape`, unescaped.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pattern: &str, text: &str) -> HistoryEntry {
+        HistoryEntry {
+            pattern: pattern.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn keep_last_selections() {
+        let mut history = History::default();
+        history.push(entry("sha", "e006b06"), 3);
+        history.push(entry("url", "https://a.io"), 3);
+        history.push(entry("path", "C:\\tmp\tx\ny"), 3);
+        history.push(entry("sha", "e006b06"), 3);
+        history.push(entry("ipv4", "10.0.0.1"), 3);
+
+        let texts: Vec<&str> = history.recent().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, ["10.0.0.1", "e006b06", "C:\\tmp\tx\ny"]);
+
+        let saved: String = history
+            .entries
+            .iter()
+            .map(|entry| format!("{}\t{}\n", entry.pattern, escape(&entry.text)))
+            .collect();
+        assert_eq!(
+            saved,
+            "path\tC:\\\\tmp\\tx\\ny\nsha\te006b06\nipv4\t10.0.0.1\n"
+        );
+        assert_eq!(History::parse(&saved).entries, history.entries);
+    }
+}
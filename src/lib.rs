@@ -33,16 +33,90 @@
 //! <kbd>e</kbd>), or first toggle the destination buffer with the <kbd>space</kbd>
 //! key and press the hint with no caps.
 //!
+//! In SSH sessions, or if the `--clipboard-exe` executable (`pbcopy`, `xclip`,
+//! ...) cannot be found, the system clipboard is set with an OSC 52 escape
+//! sequence, which most terminal emulators support: copying on a remote machine
+//! fills your local clipboard. See `--clipboard-backend` to choose explicitly.
+//!
 //! You can also use the <kbd>n</kbd> and <kbd>N</kbd> (or <kbd>Up</kbd> and
 //! <kbd>Down</kbd>) keys to move focus across the highlighted spans. Press
 //! <kbd>y</kbd> to yank the focused span into the tmux buffer, or press
 //! <kbd>Y</kbd> to yank it into the system clipboard.
 //!
+//! Press <kbd>Ctrl-y</kbd> to yank all the spans at once, or type a hint with
+//! <kbd>Alt</kbd> to trigger the alternate action of `--double-tap` (pasting by
+//! default). With `--keyboard-protocol kitty`, terminals supporting the kitty
+//! keyboard protocol (kitty, WezTerm, foot, Ghostty...) report these keys
+//! unambiguously, and <kbd>Ctrl-Shift-y</kbd> yanks and pastes all the spans.
+//!
+//! The keys of the UI can be changed, for instance with `--key next=j` or the
+//! `@copyrat-key-next` tmux option, see [CONFIGURATION.md].
+//!
+//! When the captured history is taller than the screen, for instance with
+//! `--capture-region entire-history`, press <kbd>PageUp</kbd> and
+//! <kbd>PageDown</kbd> (or <kbd>Ctrl-u</kbd> and <kbd>Ctrl-d</kbd> for half a
+//! page) to scroll. Moving the focus to an off-screen span scrolls to it.
+//!
+//! When hundreds of spans are highlighted, press <kbd>/</kbd> and type some text:
+//! only the spans containing it are kept, with new and shorter hints. Press
+//! <kbd>Enter</kbd> to go back to the hints, or <kbd>Esc</kbd> to remove the
+//! filter.
+//!
+//! To number the hints `1`, `2`, ... `10`, `11` instead of using letters, pass
+//! `--alphabet numeric`. A number which starts longer hints, such as `1` when
+//! there are 12 hints, is selected by typing it then <kbd>Enter</kbd>.
+//!
+//! Secrets matched by the `aws-access-key`, `jwt` and `private-key` patterns are
+//! masked on screen, and selecting one asks to confirm with <kbd>y</kbd>, so that
+//! they are not copied by accident. Pass `--reveal-secrets` to show them.
+//!
+//! In `tmux-copyrat`, these secrets are also cleared from the tmux buffer or the
+//! clipboard 30 seconds after being copied (see `--clear-clipboard-after`), and
+//! `--sensitive` treats every selection as a secret.
+//!
 //! By default, span highlighting starts from the bottom of the terminal, but you
 //! can reverse that behavior with the `--reverse` option. The
 //! `--focus-wrap-around` option makes navigation go back to the first span. Many
 //! more options are described in [CONFIGURATION.md].
 //!
+//! If you prefer to decide what to do with a span after selecting it, the
+//! `--action-menu` option (`-M`) displays a menu at the bottom of the screen once
+//! a hint is pressed: press <kbd>c</kbd> to copy, <kbd>p</kbd> to paste,
+//! <kbd>o</kbd> to open (URLs, files), <kbd>e</kbd> to edit with `$EDITOR`,
+//! <kbd>r</kbd> to run your `--run-command`, or <kbd>Esc</kbd> to go back to the
+//! hints.
+//!
+//! To act on a span without the menu, configure a command per pattern with
+//! `--action PATTERN=TEMPLATE`, for instance `--action url='xdg-open {}'`, where
+//! `{}` is replaced by the span's text. Pressing <kbd>o</kbd> then runs the action
+//! of the focused span, and `o` is no longer used in hints.
+//!
+//! If pressing <kbd>shift</kbd> is awkward on your keyboard, the `--double-tap`
+//! option makes typing a hint twice quickly trigger the alternate action instead:
+//! `paste` (like uppercase hints) or `clipboard`. The maximum delay between both
+//! taps is set with `--double-tap-delay` (300ms by default).
+//!
+//! To grab several spans at once, press <kbd>Tab</kbd> to enter multi-select mode
+//! (or start in it with `--multi-select`): typing a hint then marks or unmarks its
+//! span, and <kbd>Enter</kbd> selects all the marked spans, joined by the
+//! `--multi-select-separator` (a space by default).
+//!
+//! If the pane layout is noisy, the `--group-by-pattern` option (`-G`) lists the
+//! spans grouped under their pattern name instead of highlighting them in place.
+//! Press <kbd>Tab</kbd> to collapse or expand the group of the focused span.
+//!
+//! Alternatively, the `--list-view` option (`-L`) shows one span per row, with its
+//! hint, its text and the line it was found on, like a minimal [FZF] for matches.
+//! Rows are sorted by position, or by pattern with `--list-order pattern`.
+//!
+//! If the pane history is long, the `--recent-lines N` option emphasizes the
+//! spans located on the last N lines with brighter colors (see `--recent-fg` and
+//! `--recent-bg`), as the most recent output is usually the most relevant.
+//!
+//! Spans on your prompt lines, such as the command you just typed, are usually
+//! noise. Describe your prompt with `--prompt-regex` to leave them out, or pass
+//! `--include-prompt-lines` to keep them.
+//!
 //! ### Matched patterns and default key-bindings
 //!
 //! tmux-copyrat can match one or more pre-defined (named) patterns, but you can
@@ -56,26 +130,68 @@
 //!
 //! | key binding      | searches for                           | pattern name      |
 //! | ---              | ---                                    | ---               |
+//! | <kbd>a</kbd>     | Command-line arguments `--opt=value`   | `command-line-args` |
 //! | <kbd>c</kbd>     | Hex color codes                        | `hexcolor`        |
 //! | <kbd>d</kbd>     | Dates or datetimes                     | `datetime`        |
 //! | <kbd>D</kbd>     | Docker/Podman IDs                      | `docker`          |
 //! | <kbd>e</kbd>     | Emails                                 | `email`           |
 //! | <kbd>G</kbd>     | String of 4+ digits                    | `digits`          |
+//! |                  | Git branch names, in the output of git | `git-branch`      |
+//! |                  | Git remote refs `origin/main`          | `git-remote-ref`  |
+//! |                  | Git SSH URLs `git@host:org/repo.git`   | `git-ssh-url`     |
+//! | <kbd>g</kbd>     | Git branches, remote refs and SSH URLs |                   |
 //! | <kbd>h</kbd>     | SHA-1/-2 short & long                  | `sha`             |
+//! |                  | Files of `git diff` headers `--- a/..` | `diff-a`          |
+//! |                  | Files of `git diff` headers `+++ b/..` | `diff-b`          |
+//! |                  | IPFS hashes                            | `ipfs`            |
+//! |                  | Kubernetes pods `web-7d4f8c9b6f-x2kqp` | `k8s-pod`         |
+//! |                  | Kubernetes resources `pod/nginx`       | `k8s-resource`    |
+//! |                  | Container images `registry/repo:tag`   | `container-image` |
+//! | <kbd>k</kbd>     | Kubernetes pods, resources and images  |                   |
+//! | <kbd>l</kbd>     | Filepaths with a line `src/a.rs:42:7`  | `path-line`       |
 //! | <kbd>m</kbd>     | Markdown URLs `[..](matched-url)`      | `markdown-url`    |
 //! | <kbd>p</kbd>     | Abs. and rel. filepaths                | `path`            |
 //! | <kbd>P</kbd>     | Hex numbers and pointer addresses      | `pointer-address` |
 //! |                  | strings inside single quotes           | `quoted-single`   |
 //! |                  | strings inside double quotes           | `quoted-double`   |
 //! |                  | strings inside backticks               | `quoted-backtick` |
-//! | <kbd>q</kbd>     | strings inside single/double/backticks |                   |
+//! |                  | content of balanced parentheses        | `parens`          |
+//! |                  | content of balanced square brackets    | `brackets`        |
+//! |                  | content of balanced curly braces       | `braces`          |
+//! | <kbd>q</kbd>     | strings inside single/double/backticks | `quoted-any`      |
+//! | <kbd>s</kbd>     | Socket addresses `host:port`           | `socket-addr`     |
 //! | <kbd>u</kbd>     | URLs                                   | `url`             |
 //! | <kbd>U</kbd>     | UUIDs                                  | `uuid`            |
 //! | <kbd>v</kbd>     | version numbers                        | `version`         |
-//! | <kbd>4</kbd>     | IPv4 addresses                         | `4`               |
-//! | <kbd>6</kbd>     | IPv6 addresses                         | `6`               |
+//! | <kbd>4</kbd>     | IPv4 addresses                         | `ipv4`            |
+//! | <kbd>6</kbd>     | IPv6 addresses                         | `ipv6`            |
+//! |                  | AWS access key ids, masked             | `aws-access-key`  |
+//! |                  | JSON Web Tokens, masked                | `jwt`             |
+//! |                  | Private key headers, masked            | `private-key`     |
 //! | <kbd>space</kbd> | All patterns                           |                   |
 //!
+//! The quoted strings are matched without their quotes, and may contain escaped
+//! quotes such as `"a \"b\" c"`. Pass `--include-quotes quoted-double` (or
+//! `@copyrat-include-quotes`) to copy the quotes as well. The `quoted-any` pattern
+//! matches the strings inside any of the three quotes, like the three `quoted-*`
+//! patterns together.
+//!
+//! The `path-line` pattern matches the paths followed by a line and an optional
+//! column, such as `src/main.rs:42:7` in the output of compilers or grep. Its
+//! `path`, `line` and `column` capture groups can be used in capture templates, and
+//! the edit action of the action menu opens the file at the line, such as with
+//! `$EDITOR +42 'src/main.rs'`.
+//!
+//! When the matches of several patterns overlap, the match starting first wins.
+//! Rank the patterns with `--pattern-priority url,path` (or
+//! `@copyrat-pattern-priority`) to prefer the matches of some patterns instead.
+//!
+//! The balanced-bracket patterns are not part of `--all-patterns`, name them
+//! explicitly with `-x parens` for instance. They match the content of the
+//! outermost pair, such as the arguments of a function call in a stack trace;
+//! pass `--include-brackets parens` (or `@copyrat-include-brackets`) to copy the
+//! brackets as well.
+//!
 //! ## Tmux compatibility
 //!
 //! `tmux-copyrat` is known to be compatible with tmux 3.0 onwards.
@@ -83,6 +199,12 @@
 //! Testing this kind of integration with tmux is time consuming, so I'll be
 //! grateful if you report incompatibilities as you find them.
 //!
+//! If copyrat does not behave as expected, run `tmux-copyrat doctor` from a tmux
+//! pane: it checks the tmux version, the config file, the `@copyrat-*` options,
+//! the clipboard executable and the capture of the active pane, and tells how to
+//! fix what is wrong. It accepts the options of the key bindings, such as
+//! `--clipboard-exe`, to check them as well.
+//!
 //! ## The `copyrat` standalone executable
 //!
 //! Although the central binary of this crate is `tmux-copyrat`, the crate also
@@ -129,6 +251,181 @@
 //! and content inside backticks were highlighted due to the `--named-pattern`
 //! option (`-x`).
 //!
+//! Custom patterns copy the text of their first capture group, and a pattern
+//! without any group is reported as an error. To match some context around the
+//! text to copy, name the copied group `match`: with `-X
+//! '(\w+)=(?P<match>\d+)'`, only the digits are copied.
+//!
+//! The regexes follow the syntax of the Rust `regex` crate, where inline flags
+//! such as `(?i)` make a single pattern case-insensitive. `--custom-pattern-flags
+//! iw` applies flags to all the custom patterns: `i` case-insensitive, `m`
+//! multi-line, `s` `.` matching newlines, `U` lazy by default, `w` whole words
+//! only, and `S` smart case, case-insensitive unless the pattern has an uppercase
+//! letter. An invalid regex is reported as an error, before the UI opens.
+//!
+//! The spans of the `-X` patterns are labeled `custom`. To give a pattern its own
+//! name, for `--pattern-priority`, `--pattern-color`, `--action` or `--format
+//! '{pattern}'`, use `--custom-pattern 'ticket=(PROJ-\d+)'` instead.
+//!
+//! To copy a normalized form of the spans of a pattern, give it a capture
+//! template, where `{text}` is the span's text and `{NAME}` its capture group
+//! `NAME`. For instance, the `version` pattern has a `version` group without the
+//! `v` prefix and the build metadata: `--capture-template 'version={version}'`
+//! copies `v1.2.3-rc1+build5` as `1.2.3-rc1`.
+//!
+//! The copied text can then be transformed: `--transform url=trim-punct` drops the
+//! punctuation ending a sentence after a URL, and `--transform path=expand-tilde`
+//! replaces a leading `~` by the home directory. The other transforms, including
+//! `sh:COMMAND`, are described in [CONFIGURATION.md].
+//!
+//! When used interactively, the `--confirm` option echoes a colorized line such as
+//! `copied: e006b06 [pattern: sha]` to stderr once a span is selected, while
+//! stdout stays clean for piping.
+//!
+//! With `--format`, the selection is printed with a template instead, for scripts
+//! needing the pattern and the position of the span: for instance
+//! `--format '{text}\t{pattern}\t{line}:{col}'` prints
+//! `e006b06<TAB>sha<TAB>12:5`. `--emit-position` prints `12:5` alone, for
+//! editors to jump to the span.
+//!
+//! Both templates also expand `%{env:VAR}` to the environment variable `VAR`, and
+//! the strftime conversions such as `%F` or `%H` to the current local time (`%%`
+//! is a literal percent sign): for note-taking, `--format '[%F] {text}'` prints
+//! `[2024-05-01] https://example.com`.
+//!
+//! With `--output-format json`, each selection is printed as a JSON object such
+//! as `{"text":"e006b06","pattern":"sha","x":4,"y":11,"hint":"a"}`, whose `x` and
+//! `y` coordinates are counted from 0, for editors and scripts.
+//!
+//! With `--list-only`, copyrat prints all the spans it finds, without the UI, in
+//! the same formats: this is handy in scripts, or to debug custom patterns. The
+//! spans are listed in the order of the text, and the same input and options
+//! always list the same spans with the same hints, so that runs can be diffed.
+//!
+//! With `--output-fd 3`, the selections are written to the already-open file
+//! descriptor 3 instead of stdout, such as `copyrat --output-fd 3 3> >(consumer)`
+//! in bash, which avoids temporary files in shell integrations.
+//!
+//! Text piped from a terminal has lost which lines were wrapped: with
+//! `--join-wrapped-lines 80`, the lines exactly 80 characters wide are joined
+//! with the next one, so that long URLs are matched whole.
+//!
+//! Instead of piping the text to `copyrat`, pass the path of a file, such as
+//! `copyrat --all-patterns /var/log/syslog` (`-` reads stdin). Files taller than
+//! the screen are scrolled with <kbd>PageUp</kbd> and <kbd>PageDown</kbd>.
+//!
+//! ### Sharing custom patterns
+//!
+//! Custom patterns can be saved to a patterns file and shared with your team, for
+//! instance by checking it into your dotfiles:
+//!
+//! ```console
+//! copyrat patterns export -X '(JIRA-\d+)' -X '(https://intranet\.example\.com/\S+)' > team.toml
+//! ```
+//!
+//! Both `copyrat` and `tmux-copyrat run` load such files with
+//! `--patterns-file team.toml`, merging their patterns with the other patterns.
+//!
+//! ### Config file
+//!
+//! Rather than encoding everything in `.tmux.conf` strings, both `copyrat` and
+//! `tmux-copyrat run` read the options, the default patterns, the custom patterns
+//! and the key bindings from `~/.config/copyrat/config.toml` (in
+//! `$XDG_CONFIG_HOME` if set). The tmux options override the config file, and the
+//! command line options override both, see [CONFIGURATION.md].
+//!
+//! `copyrat config-schema --json` prints all the options, with their tmux option
+//! names, types, defaults and possible values, generated from their definitions,
+//! so that other tools can validate a configuration.
+//!
+//! ### Picking from the previous command
+//!
+//! Outside of tmux, `copyrat pick-last` presents the hints over the output of the
+//! previous shell command, and prints the picked span quoted for the command line
+//! (`--shell posix` or `fish`). Source [`shell/copyrat.zsh`](shell/copyrat.zsh)
+//! from your `.zshrc`: it captures the output of each command, and
+//! <kbd>Ctrl-x p</kbd> inserts the picked span on the command line. Fish cannot
+//! capture the output of each command, so with
+//! [`shell/copyrat.fish`](shell/copyrat.fish), run the command with
+//! `copyrat-capture` first, such as `copyrat-capture git status`.
+//!
+//! ### History of the selections
+//!
+//! The last 20 selections (`--history-size`) are kept in
+//! `~/.local/state/copyrat/history`, except the secrets. `copyrat history` lists
+//! them from the most recent, and `copyrat repeat` prints the most recent one
+//! again without the UI (`--nth 2` for the one before), such as
+//! `copyrat repeat | tmux load-buffer -`. In the UI, <kbd>Ctrl-h</kbd> cycles
+//! through them in the status bar, and the yank keys select the one shown.
+//!
+//! ### Keeping the regexes compiled
+//!
+//! Each key binding press starts `tmux-copyrat run`, which compiles the regexes
+//! again. A long-lived `copyrat serve --socket PATH` process can find the spans
+//! instead: set `@copyrat-server-socket` (or `--server-socket`) to the same path,
+//! see [CONFIGURATION.md]. If the server is not running, the spans are found as
+//! usual.
+//!
+//! ### Reusing the pattern matching from other tools
+//!
+//! The pattern matching lives in the `copyrat-core` crate, which Rust programs can
+//! depend on without pulling the terminal UI and the tmux integration. It also
+//! compiles to WebAssembly, for instance to preview the spans in a web page:
+//!
+//! ```sh
+//! cargo build -p copyrat-core --features ffi --target wasm32-unknown-unknown
+//! ```
+//!
+//! With the `ffi` feature, `copyrat-core` can be built as a shared library
+//! exporting `copyrat_find_spans`, which returns the spans found in a text as JSON,
+//! so that other tools (an editor plugin, a daemon) highlight exactly the same
+//! spans. See
+//! [`copyrat-core/include/copyrat.h`](copyrat-core/include/copyrat.h) and the
+//! `ffi` module documentation.
+//!
+//! Within Rust terminal applications, the `copyrat` crate can also find and
+//! render the spans without taking over the terminal: `copyrat::engine::scan`
+//! returns the spans found in some lines, and `copyrat::engine::render_overlay`
+//! writes them with their hints to any `io::Write`, so the embedding application
+//! keeps reading the keys itself.
+//!
+//! Their configuration is built with `copyrat::config::builder::ConfigBuilder`,
+//! which starts from the defaults of the command line options, such as
+//! `ConfigBuilder::new().alphabet("qwerty").pattern_name("url").build()`.
+//!
+//! ### Exit codes
+//!
+//! `copyrat` and `tmux-copyrat` exit with a distinct code for each kind of
+//! failure, so that scripts can tell them apart. `tmux-copyrat` also reports the
+//! failure in the tmux status line, and appends it to
+//! `~/.local/state/copyrat/tmux-copyrat.log` (or in `$XDG_STATE_HOME` if set),
+//! where the panics are logged as well. In any case, the active pane is swapped
+//! back in place of the temporary window.
+//!
+//! | code | meaning                                             |
+//! | ---- | --------------------------------------------------- |
+//! | `0`  | success                                             |
+//! | `1`  | nothing was selected, or no span was found          |
+//! | `2`  | invalid options, configuration or patterns          |
+//! | `3`  | not running in tmux, or a tmux command failed       |
+//! | `4`  | the selection could not be copied to the clipboard  |
+//! | `5`  | the post-hook failed                                |
+//! | `6`  | the copyrat server failed                           |
+//! | `7`  | the self-update failed                              |
+//! | `8`  | reading or writing a file or the terminal failed    |
+//!
+//! When copyrat seems to do nothing, `--debug-log FILE` (or the
+//! `@copyrat-debug-log` tmux option) appends to `FILE` the options read, the
+//! captured panes, the spans found and the selection, without the selected
+//! texts.
+//!
+//! To see which options tmux actually supplied, `tmux-copyrat run --dump-config`
+//! (or `copyrat --dump-config`) prints the effective configuration as TOML, with
+//! the source of each value: the command line, tmux, the config file or the
+//! default. The unknown options, which are otherwise silently ignored, such as a
+//! misspelled `@copyrat-revers`, are listed at the top. The output can be used as
+//! a config file.
+//!
 //! ## Run code-coverage
 //!
 //! Install the llvm-tools-preview component and grcov
@@ -174,49 +471,202 @@
 //! [MIT license]: http://opensource.org/licenses/MIT
 //!
 
+pub mod actions;
+pub mod audit;
+pub mod clipboard;
 pub mod config;
+pub mod diff;
+pub mod doctor;
+pub mod engine;
 pub mod error;
-pub mod textbuf;
+pub mod history;
+pub mod log;
+pub mod release;
+pub mod server;
+pub mod shell;
+pub mod template;
 pub mod tmux;
+pub mod transforms;
 pub mod ui;
+#[cfg(feature = "updater")]
+pub mod updater;
+
+use copyrat_core::json;
+pub use copyrat_core::textbuf;
 
 pub use error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Run copyrat on an input string `buffer`, configured by `Opt`.
 ///
+/// The `screen_size` (width, height) is used to wrap the lines; if `None`, it
+/// is obtained from tmux or from the terminal.
+///
+/// Returns `None` if nothing was selected, and fails if the terminal cannot
+/// be used.
+///
 /// # Note
 ///
 /// Maybe the decision to take ownership of the buffer is a bit bold.
-pub fn run(lines: &[&str], opt: &config::basic::Config) -> Option<ui::Selection> {
-    let model = textbuf::Model::new(
-        lines,
-        &opt.alphabet,
-        opt.use_all_patterns,
-        &opt.named_patterns,
-        &opt.custom_patterns,
-        opt.reverse,
-        opt.unique_hint,
-    );
-
-    if model.spans.is_empty() {
-        return None;
+pub fn run(
+    lines: &[&str],
+    opt: &config::basic::Config,
+    screen_size: Option<(u16, u16)>,
+) -> Result<Option<ui::Selection>> {
+    with_styles(lines, opt, |lines, styles| {
+        let model = engine::model(lines, opt)?;
+
+        present(&model, styles, opt, screen_size)
+    })
+}
+
+/// Run copyrat like [`run`], but with the spans found by the copyrat server
+/// listening on the unix socket at `socket`, see [`server`].
+///
+/// If the server cannot be reached, the spans are found by this process.
+pub fn run_with_server(
+    lines: &[&str],
+    opt: &config::basic::Config,
+    screen_size: Option<(u16, u16)>,
+    socket: &std::path::Path,
+) -> Result<Option<ui::Selection>> {
+    with_styles(lines, opt, |lines, styles| {
+        with_model(lines, opt, Some(socket), |model| {
+            present(model, styles, opt, screen_size)
+        })
+    })
+}
+
+/// Run copyrat like [`run`] or [`run_with_server`], but keep the UI open after
+/// each selection, which is passed to `on_select` right away, until `Esc` is
+/// pressed. See `--loop` and `--stay-open`.
+pub fn run_loop(
+    lines: &[&str],
+    opt: &config::basic::Config,
+    screen_size: Option<(u16, u16)>,
+    socket: Option<&std::path::Path>,
+    on_select: &mut dyn FnMut(ui::Selection) -> Result<()>,
+) -> Result<()> {
+    with_styles(lines, opt, |lines, styles| {
+        with_model(lines, opt, socket, |model| {
+            if !has_spans(model, opt) {
+                return Ok(());
+            }
+            if engine::is_picked(model, opt) {
+                return engine::pick(model, opt).map_or(Ok(()), on_select);
+            }
+
+            let default_output_destination = opt.default_output.clone();
+            let mut ui =
+                ui::ViewController::new(model, opt, default_output_destination, screen_size)
+                    .with_styles(styles);
+            ui.present_loop(on_select)
+        })
+    })
+}
+
+/// Run copyrat like [`run_loop`] on the `buffer`, but capture the buffer
+/// again with `capture` and find its spans again on the refresh key or every
+/// `--refresh-interval`, without closing the UI. See
+/// [`ui::present_refreshing`].
+///
+/// Without `--loop` nor `--stay-open`, this returns after the first
+/// selection.
+pub fn run_refreshing(
+    buffer: String,
+    opt: &config::basic::Config,
+    screen_size: Option<(u16, u16)>,
+    capture: &mut dyn FnMut() -> Result<String>,
+    on_select: &mut dyn FnMut(ui::Selection) -> Result<()>,
+) -> Result<()> {
+    ui::present_refreshing(buffer, opt, screen_size, capture, on_select)
+}
+
+/// Present the spans of the `model`, rendered over its lines with their
+/// `styles` if any, and return the selection.
+fn present(
+    model: &textbuf::Model,
+    styles: Option<&textbuf::Styles>,
+    opt: &config::basic::Config,
+    screen_size: Option<(u16, u16)>,
+) -> Result<Option<ui::Selection>> {
+    if !has_spans(model, opt) {
+        return Ok(None);
     }
+    if engine::is_picked(model, opt) {
+        return Ok(engine::pick(model, opt));
+    }
+
+    let default_output_destination = opt.default_output.clone();
 
-    let default_output_destination = config::extended::OutputDestination::Tmux;
+    let mut ui = ui::ViewController::new(model, opt, default_output_destination, screen_size)
+        .with_styles(styles);
 
-    let selection: Option<ui::Selection> = {
-        let mut ui = ui::ViewController::new(
-            &model,
-            opt.focus_wrap_around,
-            default_output_destination,
-            &opt.colors,
-            &opt.hint_alignment,
-            opt.hint_style(),
-        );
+    ui.present()
+}
 
-        ui.present()
+/// Call `f` with the `lines`, or with the `lines` without their escape
+/// sequences and with their SGR sequences if `--preserve-colors`.
+fn with_styles<T>(
+    lines: &[&str],
+    opt: &config::basic::Config,
+    f: impl FnOnce(&[&str], Option<&textbuf::Styles>) -> T,
+) -> T {
+    if !opt.preserve_colors {
+        return f(lines, None);
+    }
+    let (buffer, styles) = textbuf::Styles::parse(&lines.join("\n"));
+    let lines = buffer.split('\n').collect::<Vec<_>>();
+    f(&lines, Some(&styles))
+}
+
+/// Calls `f` with the model of the `lines`, whose spans are found by the
+/// copyrat server listening on the unix socket at `socket`, or by this process
+/// if there is no `socket` or the server cannot be reached.
+fn with_model<T>(
+    lines: &[&str],
+    opt: &config::basic::Config,
+    socket: Option<&std::path::Path>,
+    f: impl FnOnce(&textbuf::Model) -> Result<T>,
+) -> Result<T> {
+    let found = socket.and_then(|socket| {
+        server::request_spans(socket, opt, lines)
+            .map_err(|err| log::debug(|| format!("copyrat server unreachable: {err}")))
+            .ok()
+    });
+    let model = match &found {
+        Some(found) => textbuf::Model::from_raw_spans(
+            lines,
+            server::raw_spans(found),
+            &opt.alphabet,
+            &opt.bound_chars,
+            opt.reverse,
+            opt.unique_hint,
+        ),
+        None => engine::model(lines, opt)?,
     };
 
-    selection
+    f(&model)
+}
+
+/// Returns the `buffer`, or the `buffer` without its escape sequences and its
+/// SGR sequences if `--preserve-colors`.
+pub(crate) fn strip_styles<'b>(
+    buffer: &'b str,
+    opt: &config::basic::Config,
+) -> (std::borrow::Cow<'b, str>, Option<textbuf::Styles>) {
+    if !opt.preserve_colors {
+        return (std::borrow::Cow::Borrowed(buffer), None);
+    }
+    let (buffer, styles) = textbuf::Styles::parse(buffer);
+    (std::borrow::Cow::Owned(buffer), Some(styles))
+}
+
+/// Returns `true` if the `model` has spans, ringing the bell otherwise if
+/// enabled.
+fn has_spans(model: &textbuf::Model, opt: &config::basic::Config) -> bool {
+    if model.spans.is_empty() && opt.bell.contains(&ui::BellEvent::NoMatch) {
+        ui::bell::ring_tty();
+    }
+    !model.spans.is_empty()
 }
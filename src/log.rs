@@ -0,0 +1,111 @@
+//! Logs of the failures of `tmux-copyrat`, and of what copyrat does.
+//!
+//! The temporary window running `tmux-copyrat` closes as soon as it exits, so
+//! its errors only flash in the tmux status line. They are also appended, with
+//! the panics, to `~/.local/state/copyrat/tmux-copyrat.log` (or in
+//! `$XDG_STATE_HOME` if set), each after the UTC time it occurred.
+//!
+//! With `--debug-log FILE`, both binaries also append to `FILE` the steps
+//! explaining why nothing was selected: the options read from tmux and from
+//! the config file, the captured panes, the spans found and the selection.
+//! The selected texts are not logged, as they may be secrets.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::audit::{rfc3339, utc_time};
+use crate::config::trust;
+use crate::Result;
+
+/// Returns the path of the log, in `$XDG_STATE_HOME` or `~/.local/state`, or
+/// `None` if neither is set.
+pub fn default_path() -> Option<PathBuf> {
+    trust::state_path(
+        env::var_os("XDG_STATE_HOME").as_deref(),
+        env::var_os("HOME").as_deref(),
+        "tmux-copyrat.log",
+    )
+}
+
+/// Append the `message` to the log at `path`, creating it if needed.
+pub fn append(path: &Path, message: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // A single write, so that concurrent messages are not interleaved.
+    file.write_all(line(&utc_time(), message).as_bytes())?;
+    Ok(())
+}
+
+/// Append the `message` to the default log.
+///
+/// Failures are ignored, as this is only called while failing already.
+pub fn record(message: &str) {
+    if let Some(path) = default_path() {
+        let _ = append(&path, message);
+    }
+}
+
+/// File of the debug log, once set with `start_debug_log`.
+static DEBUG_LOG: OnceLock<PathBuf> = OnceLock::new();
+
+/// Append the debug messages to the file at `path` from now on, see
+/// `--debug-log`.
+///
+/// The file is only set once: later calls are ignored.
+pub fn start_debug_log(path: &Path) {
+    if DEBUG_LOG.set(path.to_path_buf()).is_ok() {
+        debug(|| format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+    }
+}
+
+/// Append the message returned by `message` to the debug log, if it was
+/// started. The message is only formatted in that case.
+///
+/// Failures are ignored, as the debug log is only a diagnostic.
+pub fn debug(message: impl FnOnce() -> String) {
+    if let Some(path) = DEBUG_LOG.get() {
+        let _ = append(path, &message());
+    }
+}
+
+/// Returns the line of the log recording the `message` at `time`, whose next
+/// lines, if any, are indented.
+fn line(time: &libc::tm, message: &str) -> String {
+    format!(
+        "{} {}\n",
+        rfc3339(time),
+        message.trim_end().replace('\n', "\n    ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_line() {
+        // SAFETY: an all-zero `tm` is valid.
+        let mut time = unsafe { std::mem::zeroed::<libc::tm>() };
+        time.tm_year = 124;
+        time.tm_mon = 4;
+        time.tm_mday = 1;
+
+        assert_eq!(
+            line(
+                &time,
+                "Cannot open the terminal `/dev/tty`: No such device\n"
+            ),
+            "2024-05-01T00:00:00Z Cannot open the terminal `/dev/tty`: No such device\n"
+        );
+        assert_eq!(
+            line(&time, "panicked at src/ui/vc.rs:12:5:\nindex out of bounds"),
+            "2024-05-01T00:00:00Z panicked at src/ui/vc.rs:12:5:\n    index out of bounds\n"
+        );
+    }
+}
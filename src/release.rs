@@ -0,0 +1,136 @@
+//! Metadata about the release artifacts matching the running binary.
+//!
+//! This is the single source of truth for platform detection: the `updater`
+//! downloads these artifacts, and `tmux-copyrat release-info --json` prints
+//! their names for the scripts installing the binary themselves.
+
+use std::fmt;
+
+use crate::json;
+
+/// Name of the binary inside the release archives.
+pub const BINARY_NAME: &str = "tmux-copyrat";
+
+/// Version of the running binary.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Returns the target triple of the artifacts matching the current platform,
+/// if such artifacts are published.
+pub fn target_triple() -> Option<&'static str> {
+    if cfg!(all(target_arch = "x86_64", target_os = "linux")) {
+        Some("x86_64-unknown-linux-gnu")
+    } else if cfg!(all(target_arch = "x86_64", target_os = "macos")) {
+        Some("x86_64-apple-darwin")
+    } else if cfg!(all(target_arch = "aarch64", target_os = "macos")) {
+        Some("aarch64-apple-darwin")
+    } else {
+        None
+    }
+}
+
+/// Returns the name of the release archive for the `target` triple.
+pub fn asset_name(target: &str) -> String {
+    let extension = if target.ends_with("apple-darwin") {
+        "zip"
+    } else {
+        "tar.xz"
+    };
+    format!("{BINARY_NAME}-{target}.{extension}")
+}
+
+/// Returns the name of the file holding the SHA-256 checksum of the
+/// `asset_name` archive.
+pub fn checksum_name(asset_name: &str) -> String {
+    format!("{asset_name}.sha256")
+}
+
+/// Version, platform and artifact names of the running binary.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReleaseInfo {
+    pub version: &'static str,
+    /// `None` if no artifact is published for this platform.
+    pub target: Option<&'static str>,
+}
+
+impl ReleaseInfo {
+    /// Returns the release info of the running binary.
+    pub fn current() -> ReleaseInfo {
+        ReleaseInfo {
+            version: CURRENT_VERSION,
+            target: target_triple(),
+        }
+    }
+
+    /// Returns the name of the archive matching this platform.
+    pub fn asset(&self) -> Option<String> {
+        self.target.map(asset_name)
+    }
+
+    /// Format as a single-line JSON object, with `null` names if no artifact
+    /// is published for this platform.
+    ///
+    /// ```
+    /// use copyrat::release::ReleaseInfo;
+    ///
+    /// let info = ReleaseInfo { version: "0.5.7", target: Some("aarch64-apple-darwin") };
+    /// assert_eq!(
+    ///     info.to_json(),
+    ///     r#"{"version":"0.5.7","target":"aarch64-apple-darwin","asset":"tmux-copyrat-aarch64-apple-darwin.zip","checksum":"tmux-copyrat-aarch64-apple-darwin.zip.sha256"}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let asset = self.asset();
+        let checksum = asset.as_deref().map(checksum_name);
+
+        format!(
+            r#"{{"version":{},"target":{},"asset":{},"checksum":{}}}"#,
+            json::string(Some(self.version)),
+            json::string(self.target),
+            json::string(asset.as_deref()),
+            json::string(checksum.as_deref()),
+        )
+    }
+}
+
+impl fmt::Display for ReleaseInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        match self.asset() {
+            Some(asset) => {
+                writeln!(f, "target: {}", self.target.unwrap_or_default())?;
+                writeln!(f, "asset: {asset}")?;
+                write!(f, "checksum: {}", checksum_name(&asset))
+            }
+            None => write!(f, "target: unsupported"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_names() {
+        assert_eq!(
+            asset_name("aarch64-apple-darwin"),
+            "tmux-copyrat-aarch64-apple-darwin.zip"
+        );
+        assert_eq!(
+            asset_name("x86_64-unknown-linux-gnu"),
+            "tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz"
+        );
+    }
+
+    #[test]
+    fn unsupported_platform_json() {
+        let info = ReleaseInfo {
+            version: "0.5.7",
+            target: None,
+        };
+        assert_eq!(
+            info.to_json(),
+            r#"{"version":"0.5.7","target":null,"asset":null,"checksum":null}"#
+        );
+    }
+}
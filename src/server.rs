@@ -0,0 +1,439 @@
+//! Long-lived process finding the spans, so that the regexes are compiled
+//! once instead of on every invocation of `tmux-copyrat run`.
+//!
+//! `copyrat serve --socket PATH` listens on a unix socket, and `tmux-copyrat
+//! run --server-socket PATH` sends it the captured text. Each connection
+//! carries one request: the client writes a JSON object, shuts down its
+//! writing half, then reads the response.
+//!
+//! ```json
+//! {"patterns":["url"],"include_quotes":[],"include_brackets":[],"custom_patterns":[],"named_custom_patterns":{},"pattern_priority":[],"all_patterns":false,"prompt_regex":null,"text":"see https://example.com"}
+//! ```
+//!
+//! The `named_custom_patterns` object maps the names of the custom patterns
+//! given with `--custom-pattern` to their regex.
+//!
+//! The response lists the spans found in the text, without hints: these are
+//! associated by the client, with its own alphabet. The named capture groups
+//! of a span, if any, are listed as its `groups` object.
+//!
+//! ```json
+//! {"spans":[{"x":4,"y":0,"pattern":"url","text":"https://example.com"}]}
+//! ```
+//!
+//! If the request is invalid, an object such as `{"error":"unknown pattern
+//! name `foo`"}` is returned instead.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::Duration;
+
+use regex::{Regex, RegexSet};
+
+use crate::config::basic;
+use crate::json::{self, Value};
+use crate::textbuf::{self, regexes, RawSpan};
+use crate::Error;
+
+/// Maximum duration of reading or writing a message, so that a stuck peer
+/// cannot block the other one.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Listen on the unix socket at `path` and answer the requests, until the
+/// process is killed.
+///
+/// A socket left over by a server which is no longer running is replaced.
+pub fn serve(path: &Path) -> crate::Result<()> {
+    if path.exists() && UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let mut server = Server::default();
+
+    for stream in listener.incoming() {
+        // A failing client must not stop the server.
+        let _ = stream
+            .map_err(Error::from)
+            .and_then(|stream| server.handle(stream));
+    }
+
+    Ok(())
+}
+
+/// State kept across requests.
+#[derive(Default)]
+struct Server {
+    /// Compiled regexes, by pattern.
+    regexes: HashMap<String, Regex>,
+    /// Compiled regex sets, by patterns.
+    regex_sets: HashMap<Vec<String>, RegexSet>,
+}
+
+impl Server {
+    fn handle(&mut self, mut stream: UnixStream) -> crate::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let mut request = String::new();
+        stream.read_to_string(&mut request)?;
+        stream.write_all(self.respond(&request).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Returns the response to the `request`, see the module documentation.
+    fn respond(&mut self, request: &str) -> String {
+        match self.find_spans(request) {
+            Ok(response) => response,
+            Err(err) => format!(r#"{{"error":{}}}"#, json::string(Some(&err))),
+        }
+    }
+
+    fn find_spans(&mut self, request: &str) -> Result<String, String> {
+        let request = json::parse(request).map_err(|err| format!("invalid request: {err}"))?;
+
+        let include_quotes = json::strings(&request, "include_quotes")?;
+        let include_brackets = json::strings(&request, "include_brackets")?;
+        let named_patterns = json::strings(&request, "patterns")?
+            .iter()
+            .map(|name| match regexes::parse_pattern_name(name) {
+                Ok(pattern) if include_quotes.contains(name) => Ok(pattern.including_quotes()),
+                Ok(pattern) if include_brackets.contains(name) => Ok(pattern.including_brackets()),
+                Ok(pattern) => Ok(pattern),
+                Err(_) => Err(format!("unknown pattern name `{name}`")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let named_custom_patterns = match request.get("named_custom_patterns") {
+            None | Some(Value::Null) => vec![],
+            Some(Value::Object(members)) => members
+                .iter()
+                .map(|(name, regex)| match regex {
+                    Value::String(regex) => Ok(regexes::NamedPattern(name.clone(), regex.clone())),
+                    _ => Err("`named_custom_patterns` must only contain strings".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err("`named_custom_patterns` must be an object".to_string()),
+        };
+        // Before the other patterns, as in `ConfigBuilder::build`.
+        let named_patterns = [named_custom_patterns.clone(), named_patterns].concat();
+        let custom_patterns = json::strings(&request, "custom_patterns")?;
+        let pattern_priority = json::strings(&request, "pattern_priority")?;
+        let use_all_patterns = json::boolean(&request, "all_patterns")?;
+        let prompt_pattern = match request.get("prompt_regex") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(pattern)) => Some(pattern.as_str()),
+            Some(_) => return Err("`prompt_regex` must be a string".to_string()),
+        };
+        let Some(Value::String(text)) = request.get("text") else {
+            return Err("`text` must be a string".to_string());
+        };
+
+        // Report invalid regexes to the client, instead of panicking below.
+        let named_custom_regexes = named_custom_patterns
+            .iter()
+            .map(|regexes::NamedPattern(_, regex)| regex);
+        for pattern in custom_patterns.iter().chain(named_custom_regexes) {
+            let regex = compile(&mut self.regexes, pattern)
+                .map_err(|err| format!("invalid pattern: {err}"))?;
+            regexes::check_capture_group(&regex)
+                .map_err(|err| format!("invalid pattern: {err}"))?;
+        }
+
+        let lines: Vec<&str> = text.split('\n').collect();
+        let raw_spans = textbuf::find_raw_spans(
+            &lines,
+            &named_patterns,
+            &custom_patterns,
+            use_all_patterns,
+            prompt_pattern,
+            &pattern_priority,
+            &mut |pattern| compile(&mut self.regexes, pattern),
+            &mut |patterns| compile_set(&mut self.regex_sets, patterns),
+        )
+        .map_err(|err| err.to_string())?;
+
+        let spans: Vec<String> = raw_spans
+            .iter()
+            .map(|span| {
+                let groups = if span.groups.is_empty() {
+                    String::new()
+                } else {
+                    let members: Vec<String> = span
+                        .groups
+                        .iter()
+                        .map(|(name, text)| {
+                            format!("{}:{}", json::string(Some(name)), json::string(Some(text)))
+                        })
+                        .collect();
+                    format!(r#","groups":{{{}}}"#, members.join(","))
+                };
+                format!(
+                    r#"{{"x":{},"y":{},"pattern":{},"text":{}{}}}"#,
+                    span.x,
+                    span.y,
+                    json::string(Some(span.pattern)),
+                    json::string(Some(span.text)),
+                    groups,
+                )
+            })
+            .collect();
+
+        Ok(format!(r#"{{"spans":[{}]}}"#, spans.join(",")))
+    }
+}
+
+/// Returns the regex of the `pattern`, compiling it only the first time.
+fn compile(regexes: &mut HashMap<String, Regex>, pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = regexes.get(pattern) {
+        // Clones share the compiled regex.
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(pattern)?;
+    regexes.insert(pattern.to_string(), regex.clone());
+    Ok(regex)
+}
+
+/// Returns the set of the `patterns`, compiling it only the first time.
+fn compile_set(
+    regex_sets: &mut HashMap<Vec<String>, RegexSet>,
+    patterns: &[&str],
+) -> Result<RegexSet, regex::Error> {
+    let key: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+    if let Some(regex_set) = regex_sets.get(&key) {
+        return Ok(regex_set.clone());
+    }
+    let regex_set = RegexSet::new(patterns)?;
+    regex_sets.insert(key, regex_set.clone());
+    Ok(regex_set)
+}
+
+/// Span found by the server.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FoundSpan {
+    pub x: i32,
+    pub y: i32,
+    pub pattern: String,
+    pub text: String,
+    pub groups: Vec<(String, String)>,
+}
+
+/// Ask the server listening on the unix socket at `path` for the spans in
+/// the `lines`, searched with the patterns of `opt`.
+pub fn request_spans(
+    path: &Path,
+    opt: &basic::Config,
+    lines: &[&str],
+) -> crate::Result<Vec<FoundSpan>> {
+    let array = |items: Vec<&str>| {
+        let items: Vec<String> = items.into_iter().map(|s| json::string(Some(s))).collect();
+        format!("[{}]", items.join(","))
+    };
+    // The named custom patterns are sent with their regex, the patterns of the
+    // catalog by name.
+    let (named_custom_patterns, catalog_patterns): (Vec<_>, Vec<_>) = opt
+        .named_patterns
+        .iter()
+        .partition(|regexes::NamedPattern(name, _)| regexes::parse_pattern_name(name).is_err());
+    let request = format!(
+        r#"{{"patterns":{},"include_quotes":{},"include_brackets":{},"custom_patterns":{},"named_custom_patterns":{{{}}},"pattern_priority":{},"all_patterns":{},"prompt_regex":{},"text":{}}}"#,
+        array(
+            catalog_patterns
+                .iter()
+                .map(|regexes::NamedPattern(name, _)| name.as_str())
+                .collect()
+        ),
+        array(opt.include_quotes.iter().map(|q| q.name()).collect()),
+        array(opt.include_brackets.iter().map(|b| b.name()).collect()),
+        array(opt.custom_patterns.iter().map(String::as_str).collect()),
+        named_custom_patterns
+            .iter()
+            .map(|regexes::NamedPattern(name, regex)| {
+                format!("{}:{}", json::string(Some(name)), json::string(Some(regex)))
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        array(opt.pattern_priority.iter().map(String::as_str).collect()),
+        opt.use_all_patterns,
+        json::string(opt.prompt_pattern()),
+        json::string(Some(&lines.join("\n"))),
+    );
+
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.write_all(request.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    parse_response(&response).map_err(Error::Server)
+}
+
+fn parse_response(response: &str) -> Result<Vec<FoundSpan>, String> {
+    let response = json::parse(response).map_err(|err| format!("invalid response: {err}"))?;
+
+    if let Some(Value::String(err)) = response.get("error") {
+        return Err(err.clone());
+    }
+    let Some(Value::Array(spans)) = response.get("spans") else {
+        return Err("invalid response: missing spans".to_string());
+    };
+
+    spans
+        .iter()
+        .map(|span| {
+            match (
+                span.get("x"),
+                span.get("y"),
+                span.get("pattern"),
+                span.get("text"),
+            ) {
+                (
+                    Some(&Value::Number(x)),
+                    Some(&Value::Number(y)),
+                    Some(Value::String(pattern)),
+                    Some(Value::String(text)),
+                ) => Ok(FoundSpan {
+                    x: x as i32,
+                    y: y as i32,
+                    pattern: pattern.clone(),
+                    text: text.clone(),
+                    groups: parse_groups(span.get("groups"))?,
+                }),
+                _ => Err("invalid response: malformed span".to_string()),
+            }
+        })
+        .collect()
+}
+
+fn parse_groups(groups: Option<&Value>) -> Result<Vec<(String, String)>, String> {
+    match groups {
+        None => Ok(vec![]),
+        Some(Value::Object(members)) => members
+            .iter()
+            .map(|(name, text)| match text {
+                Value::String(text) => Ok((name.clone(), text.clone())),
+                _ => Err("invalid response: malformed groups".to_string()),
+            })
+            .collect(),
+        Some(_) => Err("invalid response: malformed groups".to_string()),
+    }
+}
+
+/// Borrow the `found` spans as raw spans, to build the model.
+pub(crate) fn raw_spans(found: &[FoundSpan]) -> Vec<RawSpan<'_>> {
+    found
+        .iter()
+        .map(|span| RawSpan {
+            x: span.x,
+            y: span.y,
+            pattern: &span.pattern,
+            text: &span.text,
+            groups: span
+                .groups
+                .iter()
+                .map(|(name, text)| (name.clone(), text.as_str()))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::thread;
+
+    #[test]
+    fn respond_to_requests() {
+        let mut server = Server::default();
+
+        let request = r#"{"patterns":["ipv4"],"text":"lorem 127.0.0.1\n10.0.0.1 lorem"}"#;
+        assert_eq!(
+            server.respond(request),
+            concat!(
+                r#"{"spans":["#,
+                r#"{"x":6,"y":0,"pattern":"ipv4","text":"127.0.0.1"},"#,
+                r#"{"x":0,"y":1,"pattern":"ipv4","text":"10.0.0.1"}"#,
+                r#"]}"#,
+            )
+        );
+        assert_eq!(
+            server.respond(r#"{"patterns":["version"],"text":"v1.2.3+b1"}"#),
+            concat!(
+                r#"{"spans":[{"x":0,"y":0,"pattern":"version","text":"v1.2.3+b1","#,
+                r#""groups":{"version":"1.2.3"}}]}"#,
+            )
+        );
+        let regexes::NamedPattern(_, ipv4) = regexes::parse_pattern_name("ipv4").unwrap();
+        assert!(server.regexes.contains_key(&ipv4));
+
+        assert_eq!(
+            server.respond(r#"{"patterns":["foo"],"text":""}"#),
+            r#"{"error":"unknown pattern name `foo`"}"#
+        );
+        assert!(server
+            .respond(r#"{"custom_patterns":["(a"],"text":""}"#)
+            .starts_with(r#"{"error":"invalid pattern"#));
+        assert_eq!(
+            server.respond(r#"{"patterns":[]}"#),
+            r#"{"error":"`text` must be a string"}"#
+        );
+    }
+
+    #[test]
+    fn request_spans_over_socket() {
+        let path = std::env::temp_dir().join(format!("copyrat-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            Server::default().handle(stream).unwrap();
+        });
+
+        let config = basic::Config::parse_from([
+            "copyrat",
+            "-X",
+            r"(JIRA-\d+)",
+            "--custom-pattern",
+            r"ticket=(T-\d+)",
+        ])
+        .build()
+        .unwrap();
+        let found = request_spans(&path, &config, &["see JIRA-42", "and JIRA-7 T-3"]).unwrap();
+        handle.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            found,
+            [
+                FoundSpan {
+                    x: 4,
+                    y: 0,
+                    pattern: "custom".to_string(),
+                    text: "JIRA-42".to_string(),
+                    groups: vec![],
+                },
+                FoundSpan {
+                    x: 4,
+                    y: 1,
+                    pattern: "custom".to_string(),
+                    text: "JIRA-7".to_string(),
+                    groups: vec![],
+                },
+                FoundSpan {
+                    x: 11,
+                    y: 1,
+                    pattern: "ticket".to_string(),
+                    text: "T-3".to_string(),
+                    groups: vec![],
+                },
+            ]
+        );
+    }
+}
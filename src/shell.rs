@@ -0,0 +1,64 @@
+//! Picking spans of the output of the previous shell command, outside of
+//! tmux, see `copyrat pick-last`.
+//!
+//! The scripts of the `shell` directory capture the output of each command in
+//! a file, and bind a key to a widget running `copyrat pick-last` on it. The
+//! picked span is inserted on the command line, quoted for the shell.
+
+use std::env;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+
+/// Shell whose command line receives the picked span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    /// `sh`, `bash` and `zsh`.
+    Posix,
+    Fish,
+}
+
+impl Shell {
+    /// Returns the `text` quoted so that the shell reads it as a single word,
+    /// or unchanged if the shell reads all its chars literally.
+    pub fn quote(&self, text: &str) -> String {
+        let is_plain = |c: char| c.is_alphanumeric() || "%+,-./:@_".contains(c);
+        if !text.is_empty() && text.chars().all(is_plain) {
+            return text.to_string();
+        }
+
+        match self {
+            Self::Posix => format!("'{}'", text.replace('\'', r"'\''")),
+            Self::Fish => format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'")),
+        }
+    }
+}
+
+/// Returns the path of the file holding the output of the previous command:
+/// `$COPYRAT_LAST_OUTPUT` if set, or `copyrat-last-output-UID` in the
+/// temporary directory, like the shell scripts.
+pub fn last_output_path() -> PathBuf {
+    match env::var_os("COPYRAT_LAST_OUTPUT") {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => {
+            let uid = unsafe { libc::getuid() };
+            env::temp_dir().join(format!("copyrat-last-output-{uid}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_for_shells() {
+        assert_eq!(Shell::Posix.quote("src/main.rs:12"), "src/main.rs:12");
+        assert_eq!(Shell::Posix.quote("été-1.0"), "été-1.0");
+        assert_eq!(Shell::Posix.quote(""), "''");
+        assert_eq!(Shell::Posix.quote("it's $HOME"), r"'it'\''s $HOME'");
+        assert_eq!(Shell::Posix.quote("a=b"), "'a=b'");
+        assert_eq!(Shell::Fish.quote(r"it's C:\dir"), r"'it\'s C:\\dir'");
+        assert_eq!(Shell::Fish.quote("~/notes"), "'~/notes'");
+    }
+}
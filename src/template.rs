@@ -0,0 +1,341 @@
+//! Templates configured per pattern, such as the actions and the capture
+//! templates.
+//!
+//! A capture template rewrites the text copied from the spans of a pattern,
+//! with the named capture groups of its regex: for instance,
+//! `--capture-template version='{version}'` copies `v1.2.3-rc1+build5` as
+//! `1.2.3-rc1`, because downstream tools often reject the prefixed form.
+//!
+//! The capture templates and the output formats also expand `%{env:VAR}` to
+//! the environment variable `VAR`, and the strftime conversions such as `%Y` or
+//! `%F` to the current local time, for instance `[%F] {text}` for notes. `%%`
+//! is a literal percent sign.
+
+use std::env;
+use std::ffi::CStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{textbuf::Span, ui::Selection, Error, Result};
+
+/// Template used for the spans of a pattern, see `--action` and
+/// `--capture-template`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternTemplate {
+    /// Name of the pattern, such as `url`, or `custom` for the custom
+    /// patterns.
+    pub pattern: String,
+    pub template: String,
+}
+
+/// Parse a `PATTERN=TEMPLATE` string into a `PatternTemplate`, used during
+/// CLI parsing.
+pub fn parse_pattern_template(src: &str) -> Result<PatternTemplate> {
+    match src.split_once('=') {
+        Some((pattern, template)) if !pattern.is_empty() && !template.is_empty() => {
+            Ok(PatternTemplate {
+                pattern: pattern.to_string(),
+                template: template.to_string(),
+            })
+        }
+        _ => Err(Error::InvalidPatternTemplate(src.to_string())),
+    }
+}
+
+/// Returns the template configured for the `pattern`, the last one if there
+/// are several.
+pub fn find<'a>(templates: &'a [PatternTemplate], pattern: &str) -> Option<&'a str> {
+    templates
+        .iter()
+        .rev()
+        .find(|template| template.pattern == pattern)
+        .map(|template| template.template.as_str())
+}
+
+/// Returns the text to copy from the `span`: its text, rewritten by the
+/// capture template of its pattern if any.
+pub fn span_text(templates: &[PatternTemplate], span: &Span) -> String {
+    match find(templates, span.pattern) {
+        Some(template) => render_capture(template, span),
+        None => span.text.to_string(),
+    }
+}
+
+/// Replace the placeholders of the capture `template`: `{text}` by the text of
+/// the `span`, and `{NAME}` by its capture group `NAME`, which is empty if
+/// the group did not participate in the match. `{{` and `}}` are literal
+/// braces, and the `%` placeholders are expanded, see `fill`.
+pub fn render_capture(template: &str, span: &Span) -> String {
+    fill(template, local_time, |result, name| {
+        if name == "text" {
+            result.push_str(span.text);
+        } else if let Some((_, group)) = span.groups.iter().find(|(n, _)| n == name) {
+            result.push_str(group);
+        }
+    })
+}
+
+/// Replace the placeholders of the output `format` of the `selection`, see
+/// `--format`: `{text}`, `{pattern}`, `{line}` and `{col}`. Unknown
+/// placeholders are removed, and the `%` placeholders are expanded, see
+/// `fill`. The `\t`, `\n` and `\\` escapes are also replaced, so that tabs
+/// can be given in a shell argument.
+pub fn render_output(format: &str, selection: &Selection) -> String {
+    let format = unescape(format);
+    fill(&format, local_time, |result, name| match name {
+        "text" => result.push_str(&selection.text),
+        "pattern" => result.push_str(&selection.pattern),
+        "line" => result.push_str(&selection.position.0.to_string()),
+        "col" => result.push_str(&selection.position.1.to_string()),
+        _ => (),
+    })
+}
+
+/// Replace the `\t`, `\n` and `\\` escapes of the `text`.
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Replace the `{NAME}` placeholders of the `template`, by calling
+/// `push_value` with the result and the `NAME`. `{{` and `}}` are literal
+/// braces.
+///
+/// `%{env:VAR}` is replaced by the environment variable `VAR`, empty if it is
+/// not set, and the strftime conversions such as `%Y` by the time returned by
+/// `now`, called once at most. `%%` is a literal percent sign, and a `%`
+/// followed by anything else is kept as is. The values are not expanded
+/// again, so that a `%` or a brace in the text is copied verbatim.
+fn fill(
+    template: &str,
+    now: fn() -> libc::tm,
+    mut push_value: impl FnMut(&mut String, &str),
+) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    let mut time = None;
+
+    while let Some(start) = rest.find(['{', '}', '%']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") || rest.starts_with("%%") {
+            result.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        if let Some(placeholder) = rest.strip_prefix("%{") {
+            if let Some(end) = placeholder.find('}') {
+                if let Some(name) = placeholder[..end].strip_prefix("env:") {
+                    result.push_str(&env::var(name).unwrap_or_default());
+                    rest = &placeholder[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        if let Some(conversion) = rest.strip_prefix('%').and_then(|r| r.chars().next()) {
+            let time = time.get_or_insert_with(now);
+            if let Some(formatted) = strftime(conversion, time) {
+                result.push_str(&formatted);
+                rest = &rest[1 + conversion.len_utf8()..];
+                continue;
+            }
+        }
+
+        match rest[1..].find('}').filter(|_| rest.starts_with('{')) {
+            Some(end) => {
+                push_value(&mut result, &rest[1..end + 1]);
+                rest = &rest[end + 2..];
+            }
+            None => {
+                // Unbalanced brace, or unknown percent placeholder.
+                result.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Returns the current local time.
+fn local_time() -> libc::tm {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs()) as libc::time_t;
+
+    // SAFETY: `localtime_r` only writes to the `tm` it is given, and an
+    // all-zero `tm` is valid.
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        libc::localtime_r(&seconds, &mut tm);
+        tm
+    }
+}
+
+/// Returns the strftime `conversion` of the `time`, or `None` if it is not an
+/// ASCII letter.
+fn strftime(conversion: char, time: &libc::tm) -> Option<String> {
+    if !conversion.is_ascii_alphabetic() {
+        return None;
+    }
+
+    let format = [b'%', conversion as u8, 0];
+    let mut buffer = [0u8; 128];
+    // SAFETY: the format is nul-terminated, and `strftime` writes at most
+    // `buffer.len()` bytes, including the terminating nul.
+    let len = unsafe {
+        libc::strftime(
+            buffer.as_mut_ptr().cast(),
+            buffer.len(),
+            format.as_ptr().cast(),
+            time,
+        )
+    };
+
+    let formatted = CStr::from_bytes_until_nul(&buffer[..=len]).ok()?;
+    Some(formatted.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span<'a>(text: &'a str, groups: &[(&str, &'a str)]) -> Span<'a> {
+        Span {
+            x: 0,
+            y: 0,
+            pattern: "version",
+            text,
+            hint: "a".to_string(),
+            groups: groups
+                .iter()
+                .map(|&(name, group)| (name.to_string(), group))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parse_templates() {
+        assert_eq!(
+            parse_pattern_template("url=open {}").unwrap(),
+            PatternTemplate {
+                pattern: "url".to_string(),
+                template: "open {}".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_pattern_template("path=vi {} +a=b").unwrap().template,
+            "vi {} +a=b"
+        );
+        assert!(parse_pattern_template("url").is_err());
+        assert!(parse_pattern_template("=open {}").is_err());
+        assert!(parse_pattern_template("url=").is_err());
+    }
+
+    #[test]
+    fn find_last_template_of_pattern() {
+        let templates = [
+            parse_pattern_template("url=xdg-open {}").unwrap(),
+            parse_pattern_template("path=vi {}").unwrap(),
+            parse_pattern_template("url=open {}").unwrap(),
+        ];
+
+        assert_eq!(find(&templates, "url"), Some("open {}"));
+        assert_eq!(find(&templates, "path"), Some("vi {}"));
+        assert_eq!(find(&templates, "sha"), None);
+    }
+
+    #[test]
+    fn render_capture_templates() {
+        let version = span("v1.2.3-rc1+build5", &[("version", "1.2.3-rc1")]);
+
+        assert_eq!(render_capture("{version}", &version), "1.2.3-rc1");
+        assert_eq!(
+            render_capture("{text} -> {version}", &version),
+            "v1.2.3-rc1+build5 -> 1.2.3-rc1"
+        );
+        assert_eq!(render_capture("{{{version}}}", &version), "{1.2.3-rc1}");
+        assert_eq!(render_capture("[{other}] {", &version), "[] {");
+
+        let templates = [parse_pattern_template("version={version}").unwrap()];
+        assert_eq!(span_text(&templates, &version), "1.2.3-rc1");
+        assert_eq!(span_text(&[], &version), "v1.2.3-rc1+build5");
+    }
+
+    #[test]
+    fn render_output_formats() {
+        let selection = Selection {
+            text: "https://a.b".to_string(),
+            texts: vec!["https://a.b".to_string()],
+            pattern: "url".to_string(),
+            position: (3, 7),
+            hint: "a".to_string(),
+            uppercased: false,
+            output_destination: crate::config::extended::OutputDestination::Tmux,
+            action: None,
+        };
+
+        assert_eq!(
+            render_output(r"{text}\t{pattern}\t{line}:{col}", &selection),
+            "https://a.b\turl\t3:7"
+        );
+        assert_eq!(
+            render_output(r"{{{line}}}\n{other}\\t\x", &selection),
+            "{3}\n\\t\\x"
+        );
+    }
+
+    fn may_first() -> libc::tm {
+        // SAFETY: an all-zero `tm` is valid.
+        let mut tm = unsafe { std::mem::zeroed::<libc::tm>() };
+        tm.tm_year = 124;
+        tm.tm_mon = 4;
+        tm.tm_mday = 1;
+        tm.tm_hour = 9;
+        tm.tm_min = 5;
+        tm.tm_wday = 3;
+        tm
+    }
+
+    #[test]
+    fn fill_percent_placeholders() {
+        env::set_var("COPYRAT_TEST_PROJECT", "copyrat");
+        let fill_text = |template: &str| {
+            fill(template, may_first, |result, name| {
+                if name == "text" {
+                    result.push_str("https://a.b/%Y{x}");
+                }
+            })
+        };
+
+        assert_eq!(fill_text("[%F] {text}"), "[2024-05-01] https://a.b/%Y{x}");
+        assert_eq!(fill_text("%d/%m/%y %H:%M"), "01/05/24 09:05");
+        assert_eq!(
+            fill_text("%{env:COPYRAT_TEST_PROJECT}: {text}"),
+            "copyrat: https://a.b/%Y{x}"
+        );
+        assert_eq!(fill_text("[%{env:COPYRAT_TEST_UNSET}]"), "[]");
+        assert_eq!(fill_text("100%% %{{%Y}} 5% %"), "100% %{2024} 5% %");
+        assert_eq!(fill_text("%{other} %é"), "% %é");
+    }
+}
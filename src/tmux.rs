@@ -3,8 +3,11 @@
 //! The main use cases are running Tmux commands & parsing Tmux panes
 //! information.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
 use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use regex::Regex;
@@ -20,6 +23,8 @@ pub struct Pane {
     pub id: PaneId,
     /// Describes if the pane is in copy mode.
     pub is_copy_mode: bool,
+    /// Number of columns in the pane.
+    pub width: i32,
     /// Number of lines in the pane.
     pub height: i32,
     /// Optional offset from the bottom if the pane is in some mode.
@@ -32,48 +37,72 @@ pub struct Pane {
     pub is_active: bool,
 }
 
+/// Format of the lines printed by `tmux list-panes`, one `name=value` field
+/// per pane variable, separated by tabs.
+///
+/// The values are numbers, booleans and pane ids, which contain no tabs, and
+/// the fields are matched by name so that their order does not matter.
+const PANE_FORMAT: &str = "id=#{pane_id}\tin_mode=#{?pane_in_mode,true,false}\twidth=#{pane_width}\theight=#{pane_height}\tscroll_position=#{scroll_position}\tactive=#{?pane_active,true,false}";
+
 impl FromStr for Pane {
     type Err = Error;
 
-    /// Parse a string containing tmux panes status into a new `Pane`.
-    ///
-    /// This returns a `Result<Pane, ParseError>` as this call can obviously
-    /// fail if provided an invalid format.
+    /// Parse a line of `tmux list-panes -F PANE_FORMAT` into a new `Pane`.
     ///
-    /// The expected format of the tmux status is "%52:false:62:3:false",
-    /// or "%53:false:23::true".
-    ///
-    /// This status line is obtained with `tmux list-panes -F '#{pane_id}:#{?pane_in_mode,true,false}:#{pane_height}:#{scroll_position}:#{?pane_active,true,false}'`.
+    /// The expected line is
+    /// "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false",
+    /// where `scroll_position` is empty if the pane is not in copy mode.
+    /// Unknown fields are ignored, and a missing or malformed field is an
+    /// `Error::InvalidPane`.
     ///
     /// For definitions, look at `Pane` type,
     /// and at the tmux man page for definitions.
     fn from_str(src: &str) -> std::result::Result<Self, Self::Err> {
-        let items: Vec<&str> = src.split(':').collect();
-        assert_eq!(items.len(), 5, "tmux should have returned 5 items per line");
-
-        let mut iter = items.iter();
+        let line = src.trim_end_matches(['\r', '\n']);
+        let invalid = |reason: String| Error::InvalidPane {
+            line: line.to_string(),
+            reason,
+        };
 
-        // Pane id must be start with '%' followed by a `u32`
-        let id_str = iter.next().unwrap();
-        let id = PaneId::from_str(id_str)?;
+        let mut fields = HashMap::new();
+        for field in line.split('\t') {
+            let (name, value) = field
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected `name=value`, found `{field}`")))?;
+            fields.insert(name, value);
+        }
+        let field = |name: &str| {
+            fields
+                .get(name)
+                .copied()
+                .ok_or_else(|| invalid(format!("missing field `{name}`")))
+        };
+        fn parse<T: FromStr>(value: &str, name: &str) -> std::result::Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid `{name}`: `{value}`"))
+        }
 
-        let is_copy_mode = iter.next().unwrap().parse::<bool>()?;
+        // Pane id must be start with '%' followed by a `u16`
+        let id_str = field("id")?;
+        let id =
+            PaneId::from_str(id_str).map_err(|_| invalid(format!("invalid `id`: `{id_str}`")))?;
 
-        let height = iter.next().unwrap().parse::<i32>()?;
+        let is_copy_mode = parse(field("in_mode")?, "in_mode").map_err(invalid)?;
+        let width = parse(field("width")?, "width").map_err(invalid)?;
+        let height = parse(field("height")?, "height").map_err(invalid)?;
 
-        let scroll_position = iter.next().unwrap();
-        let scroll_position = if scroll_position.is_empty() {
-            "0"
-        } else {
-            scroll_position
+        let scroll_position = match field("scroll_position")? {
+            "" => 0,
+            value => parse(value, "scroll_position").map_err(invalid)?,
         };
-        let scroll_position = scroll_position.parse::<i32>()?;
 
-        let is_active = iter.next().unwrap().parse::<bool>()?;
+        let is_active = parse(field("active")?, "active").map_err(invalid)?;
 
         Ok(Pane {
             id,
             is_copy_mode,
+            width,
             height,
             scroll_position,
             is_active,
@@ -101,8 +130,13 @@ impl Pane {
     ///   be specified when capturing the pane's content.
     ///   index is `-3`. The index of the last line is `(40-1) - 3 = 36`.
     ///
-    pub fn capture(&self, region: &CaptureRegion) -> Result<String> {
+    /// With `escapes`, the output includes the escape sequences of the text
+    /// colors and attributes, see `--preserve-colors`.
+    pub fn capture(&self, region: &CaptureRegion, escapes: bool) -> Result<String> {
         let mut args_str = format!("capture-pane -t {pane_id} -J -p", pane_id = self.id);
+        if escapes {
+            args_str.push_str(" -e");
+        }
 
         let region_str = match region {
             CaptureRegion::VisibleArea => {
@@ -123,11 +157,113 @@ impl Pane {
 
         let args: Vec<&str> = args_str.split(' ').collect();
 
-        let output = duct::cmd("tmux", &args).read()?;
+        let output = read(&args)?;
         Ok(output)
     }
 }
 
+impl Pane {
+    /// Returns the width and height of the pane, as used by the UI.
+    pub fn size(&self) -> Option<(u16, u16)> {
+        let width = u16::try_from(self.width).ok()?;
+        let height = u16::try_from(self.height).ok()?;
+        Some((width, height))
+    }
+
+    /// Returns the current working directory of the pane.
+    pub fn current_path(&self) -> Result<PathBuf> {
+        let output = read(&[
+            "display-message",
+            "-p",
+            "-t",
+            self.id.as_str(),
+            "#{pane_current_path}",
+        ])?;
+
+        Ok(PathBuf::from(output))
+    }
+
+    /// Type the `text` into the pane, as if it were typed on the keyboard,
+    /// leaving copy mode first if needed.
+    pub fn type_text(&self, text: &str) -> Result<()> {
+        self.leave_copy_mode()?;
+        run(&["send-keys", "-t", self.id.as_str(), "-l", text])?;
+
+        Ok(())
+    }
+
+    /// Paste the most recent tmux buffer into the pane, leaving copy mode
+    /// first if needed.
+    pub fn paste_buffer(&self) -> Result<()> {
+        self.leave_copy_mode()?;
+        run(&["paste-buffer", "-t", self.id.as_str()])?;
+
+        Ok(())
+    }
+
+    /// Leave copy mode, if the pane was in copy mode when it was listed.
+    fn leave_copy_mode(&self) -> Result<()> {
+        if self.is_copy_mode {
+            run(&["copy-mode", "-t", self.id.as_str(), "-q"])?;
+        }
+
+        Ok(())
+    }
+
+    /// Enter copy mode in the pane, with the cursor on the captured line and
+    /// column `position`, counted from 1, then send it the `keys`, if any,
+    /// such as `v` to begin a selection.
+    pub fn enter_copy_mode(
+        &self,
+        region: &CaptureRegion,
+        position: (usize, usize),
+        keys: Option<&str>,
+    ) -> Result<()> {
+        let target = self.id.as_str();
+        run(&["copy-mode", "-t", target])?;
+
+        for command in copy_mode_commands(region, position) {
+            let mut args = vec!["send-keys", "-t", target, "-X"];
+            args.extend(command.iter().map(String::as_str));
+            run(&args)?;
+        }
+
+        if let Some(keys) = keys {
+            let mut args = vec!["send-keys", "-t", target];
+            args.extend(keys.split_whitespace());
+            run(&args)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the copy mode commands moving the cursor to the captured line and
+/// column `position`, counted from 1, from the first captured line.
+fn copy_mode_commands(region: &CaptureRegion, position: (usize, usize)) -> Vec<Vec<String>> {
+    let first_line = match region {
+        CaptureRegion::VisibleArea => "top-line",
+        CaptureRegion::EntireHistory => "history-top",
+    };
+    let mut commands = vec![
+        vec![first_line.to_string()],
+        vec!["start-of-line".to_string()],
+    ];
+
+    let (line, column) = position;
+    for (count, command) in [(line - 1, "cursor-down"), (column - 1, "cursor-right")] {
+        if count > 0 {
+            commands.push(vec![
+                "-N".to_string(),
+                count.to_string(),
+                command.to_string(),
+            ]);
+        }
+    }
+
+    commands
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct PaneId(String);
 
@@ -160,71 +296,323 @@ impl fmt::Display for PaneId {
 
 /// Returns a list of `Pane` from the current tmux session.
 pub fn available_panes() -> Result<Vec<Pane>> {
-    let args = vec![
-        "list-panes",
-        "-F",
-        "#{pane_id}:#{?pane_in_mode,true,false}:#{pane_height}:#{scroll_position}:#{?pane_active,true,false}",
-        ];
+    list_panes(None)
+}
 
-    let output = duct::cmd("tmux", &args).read()?;
+/// Returns the list of `Pane` of the `target_window`, such as `{last}`.
+pub fn window_panes(target_window: &str) -> Result<Vec<Pane>> {
+    list_panes(Some(target_window))
+}
+
+fn list_panes(target_window: Option<&str>) -> Result<Vec<Pane>> {
+    let mut args = vec!["list-panes", "-F", PANE_FORMAT];
+    if let Some(target_window) = target_window {
+        args.extend(["-t", target_window]);
+    }
+
+    let output = read(&args)?;
 
     // Each call to `Pane::parse` returns a `Result<Pane>`. All results
     // are collected into a Result<Vec<Pane>>, thanks to `collect()`.
     let result: Result<Vec<Pane>> = output
-        .trim_end() // trim last '\n' as it would create an empty line
-        .split('\n')
+        .lines()
+        .filter(|line| !line.is_empty())
         .map(Pane::from_str) // .map(|line| Pane::from_str(line))
         .collect();
 
     result
 }
 
-/// Returns tmux global options as a `HashMap`.
+/// Returns tmux global options as a `BTreeMap`.
 ///
 /// The prefix argument is for convenience, in order to target only some of our options. For
-/// instance, `get_options("@copyrat-")` will return a `HashMap` which keys are tmux options names
+/// instance, `get_options("@copyrat-")` will return a `BTreeMap` which keys are tmux options names
 /// like `@copyrat-command`, and associated values.
 ///
+/// The options are sorted by name, so that the options adding to a list, such as
+/// `@copyrat-custom-pattern-NAME`, are applied in the same order on every run.
+///
 /// # Example
 /// ```get_options("@copyrat-")```
-pub fn get_options(prefix: &str) -> Result<HashMap<String, String>> {
-    let output = duct::cmd!("tmux", "show-options", "-g").read()?;
-    let lines: Vec<&str> = output.split('\n').collect();
+pub fn get_options(prefix: &str) -> Result<BTreeMap<String, String>> {
+    let output = read(&["show-options", "-g"])?;
+    Ok(parse_options(&output, prefix))
+}
 
-    let pattern = format!(r#"({prefix}[\w\-0-9]+) "?(\w+)"?"#);
-    let re = Regex::new(&pattern).unwrap();
+/// Parse the output of `tmux show-options` into a `BTreeMap`, keeping only
+/// the options starting with `prefix`.
+///
+/// Values containing spaces are quoted by tmux: these quotes are removed.
+fn parse_options(output: &str, prefix: &str) -> BTreeMap<String, String> {
+    // The prefix is escaped, so that the pattern is always valid.
+    let pattern = format!(r#"^({}[\w\-0-9]+) (.*)$"#, regex::escape(prefix));
+    let re = Regex::new(&pattern).expect("The pattern of the options is valid.");
 
-    let args: HashMap<String, String> = lines
-        .iter()
+    output
+        .split('\n')
         .flat_map(|line| match re.captures(line) {
             None => None,
             Some(captures) => {
                 let key = captures[1].to_string();
-                let value = captures[2].to_string();
+                let value = unquote(&captures[2]);
                 Some((key, value))
             }
         })
-        .collect();
+        .collect()
+}
+
+/// Remove the quotes tmux puts around option values containing spaces or
+/// special chars.
+fn unquote(value: &str) -> String {
+    let is_quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+
+    if is_quoted {
+        value[1..value.len() - 1].replace("\\\"", "\"")
+    } else {
+        value.to_string()
+    }
+}
 
-    Ok(args)
+/// Returns `true` if this process runs inside a tmux session, as told by the
+/// `$TMUX` variable, which tmux sets in its panes.
+pub fn is_inside_session() -> bool {
+    is_session_var(std::env::var_os("TMUX").as_deref())
+}
+
+/// Returns `true` if the `$TMUX` variable `value` denotes a tmux session.
+fn is_session_var(value: Option<&OsStr>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Asks tmux to display a message in the status line of the current client.
+pub fn display_message(message: &str) -> Result<()> {
+    run(&["display-message", message])?;
+
+    Ok(())
+}
+
+/// Asks tmux to prompt for confirmation with the `prompt` in the status line
+/// of the current client, and to run the tmux command made of the `args` if
+/// confirmed.
+///
+/// This returns without waiting for the answer.
+pub fn confirm_before(prompt: &str, args: &[&str]) -> Result<()> {
+    let command: Vec<String> = args.iter().map(|arg| quote(arg)).collect();
+    run(&[
+        "confirm-before",
+        "-p",
+        &prompt.replace('#', "##"),
+        &command.join(" "),
+    ])?;
+
+    Ok(())
+}
+
+/// Quote the `arg` for the tmux command parser. Its `#` are escaped too, as
+/// commands such as `run-shell` expand formats.
+fn quote(arg: &str) -> String {
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('#', "##");
+    format!("\"{escaped}\"")
+}
+
+/// Asks tmux to run the shell `command` in the background after `seconds`.
+///
+/// Unlike a child process, the command keeps running after copyrat exits and
+/// its temporary window is killed.
+pub fn run_shell_after(seconds: u64, command: &str) -> Result<()> {
+    let command = format!("sleep {seconds}; {command}");
+    // `run-shell` expands formats.
+    run(&["run-shell", "-b", &command.replace('#', "##")])?;
+
+    Ok(())
+}
+
+/// Copies the `text` to a new tmux buffer.
+pub fn set_buffer(text: &str) -> Result<()> {
+    run(&["set-buffer", text])?;
+
+    Ok(())
+}
+
+/// Copies the content of the file at `path` to a new tmux buffer.
+pub fn load_buffer(path: &Path) -> Result<()> {
+    run(&["load-buffer", &path.to_string_lossy()])?;
+
+    Ok(())
+}
+
+/// Returns the name of the most recent tmux buffer, such as `buffer0012`.
+pub fn latest_buffer_name() -> Result<String> {
+    let output = read(&["list-buffers", "-F", "#{buffer_name}"])?;
+    output
+        .lines()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| Error::ExpectedString("<buffer_name>".into()))
+}
+
+/// Returns the path of the terminal of the current client, for instance to
+/// write escape sequences to the terminal emulator, bypassing tmux.
+pub fn client_tty() -> Result<String> {
+    let output = read(&["display-message", "-p", "#{client_tty}"])?;
+    Ok(output.trim_end().to_string())
+}
+
+/// Returns the width and height of the `target` pane, or of the pane running
+/// this process if `None`.
+pub fn pane_size(target: Option<&str>) -> Result<(u16, u16)> {
+    let mut args = vec!["display-message", "-p"];
+    if let Some(target) = target {
+        args.extend(["-t", target]);
+    }
+    args.push("#{pane_width}:#{pane_height}");
+
+    let output = read(&args)?;
+    parse_size(&output)
+}
+
+/// Parse the `width:height` output of `pane_size`.
+fn parse_size(output: &str) -> Result<(u16, u16)> {
+    let (width, height) = output
+        .trim_end()
+        .split_once(':')
+        .ok_or_else(|| Error::ExpectedString("<width>:<height>".into()))?;
+
+    Ok((width.parse()?, height.parse()?))
 }
 
 /// Asks tmux to swap the current Pane with the target_pane (uses Tmux format).
 pub fn swap_pane_with(target_pane: &str) -> Result<()> {
     // -Z: keep the window zoomed if it was zoomed.
-    duct::cmd!("tmux", "swap-pane", "-Z", "-s", target_pane).run()?;
+    run(&["swap-pane", "-Z", "-s", target_pane])?;
 
     Ok(())
 }
 
+/// Asks tmux to make the target_window the current window.
+pub fn select_window(target_window: &str) -> Result<()> {
+    run(&["select-window", "-t", target_window])?;
+
+    Ok(())
+}
+
+/// Asks tmux to set the window option `name` of the target_window.
+pub fn set_window_option(target_window: &str, name: &str, value: &str) -> Result<()> {
+    run(&["set-option", "-w", "-t", target_window, name, value])?;
+
+    Ok(())
+}
+
+/// Asks tmux to run the shell `command` in a borderless popup of `size`,
+/// closed when the command exits.
+///
+/// The bottom-left corner of the popup is the one of the active pane, so that
+/// a popup of the size of the pane covers it.
+pub fn display_popup(size: (u16, u16), command: &str) -> Result<()> {
+    let (width, height) = size;
+    run(&[
+        "display-popup",
+        "-E",
+        "-B",
+        "-x",
+        "P",
+        "-y",
+        "P",
+        "-w",
+        &width.to_string(),
+        "-h",
+        &height.to_string(),
+        command,
+    ])?;
+
+    Ok(())
+}
+
+/// A tmux command which could not be run, or which failed.
+#[derive(thiserror::Error, Debug)]
+#[error("`tmux {command}` failed: {source}")]
+pub struct CommandError {
+    /// Arguments of the command, separated by spaces.
+    pub command: String,
+    source: io::Error,
+}
+
+impl CommandError {
+    pub(crate) fn new(args: &[&str], source: io::Error) -> CommandError {
+        CommandError {
+            command: args.join(" "),
+            source,
+        }
+    }
+}
+
+/// Run the tmux command made of the `args`.
+fn run(args: &[&str]) -> std::result::Result<(), CommandError> {
+    duct::cmd("tmux", args)
+        .run()
+        .map(|_| ())
+        .map_err(|source| CommandError::new(args, source))
+}
+
+/// Run the tmux command made of the `args`, and return its output.
+fn read(args: &[&str]) -> std::result::Result<String, CommandError> {
+    duct::cmd("tmux", args)
+        .read()
+        .map_err(|source| CommandError::new(args, source))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::str::FromStr;
 
+    #[test]
+    fn move_cursor_in_copy_mode() {
+        assert_eq!(
+            copy_mode_commands(&CaptureRegion::VisibleArea, (3, 7)),
+            [
+                vec!["top-line"],
+                vec!["start-of-line"],
+                vec!["-N", "2", "cursor-down"],
+                vec!["-N", "6", "cursor-right"],
+            ]
+        );
+        assert_eq!(
+            copy_mode_commands(&CaptureRegion::EntireHistory, (1, 1)),
+            [vec!["history-top"], vec!["start-of-line"]]
+        );
+    }
+
+    #[test]
+    fn quote_tmux_arguments() {
+        assert_eq!(quote("run-shell"), r#""run-shell""#);
+        assert_eq!(
+            quote(r#"'/bin/x' trust '/a "b" $c #d\e'"#),
+            r#""'/bin/x' trust '/a \"b\" \$c ##d\\e'""#
+        );
+    }
+
+    #[test]
+    fn detect_tmux_session() {
+        let var = OsStr::new("/tmp/tmux-1000/default,4124,0");
+        assert!(is_session_var(Some(var)));
+        assert!(!is_session_var(Some(OsStr::new(""))));
+        assert!(!is_session_var(None));
+    }
+
     #[test]
     fn test_parse_pass() {
-        let output = ["%52:false:62:3:false", "%53:false:23::true"];
+        let output = [
+            "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false",
+            // Fields in another order, an unknown field, and a `\r\n` ending.
+            "active=true\tid=%53\tzoomed=0\tin_mode=false\twidth=80\theight=23\tscroll_position=\r\n",
+        ];
         let panes: Result<Vec<Pane>> = output.iter().map(|&line| Pane::from_str(line)).collect();
         let panes = panes.expect("Could not parse tmux panes");
 
@@ -232,6 +620,7 @@ mod tests {
             Pane {
                 id: PaneId::from_str("%52").unwrap(),
                 is_copy_mode: false,
+                width: 212,
                 height: 62,
                 scroll_position: 3,
                 is_active: false,
@@ -240,6 +629,7 @@ mod tests {
                 // id: PaneId::from_str("%53").unwrap(),
                 id: PaneId(String::from("%53")),
                 is_copy_mode: false,
+                width: 80,
                 height: 23,
                 scroll_position: 0,
                 is_active: true,
@@ -248,4 +638,81 @@ mod tests {
 
         assert_eq!(panes, expected);
     }
+
+    #[test]
+    fn test_parse_malformed_panes() {
+        let valid = "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false";
+        assert!(Pane::from_str(valid).is_ok());
+
+        let malformed = [
+            "",
+            "%52:false:212:62:3:false",
+            "id=%52",
+            "id=52\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false",
+            "id=%\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false",
+            "id=%52\tin_mode=yes\twidth=212\theight=62\tscroll_position=3\tactive=false",
+            "id=%52\tin_mode=false\twidth=-\theight=62\tscroll_position=3\tactive=false",
+            "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=x\tactive=false",
+            "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=3",
+            "id=%52\tin_mode=false width=212\theight=62\tscroll_position=3\tactive=false",
+            "id=%52\tin_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false\t",
+            "id=%52\u{1f}in_mode=false\twidth=212\theight=62\tscroll_position=3\tactive=false",
+        ];
+        for line in malformed {
+            let err = Pane::from_str(line).expect_err(line);
+            assert!(matches!(err, Error::InvalidPane { .. }), "{line:?}: {err}");
+        }
+
+        // Every truncation of a valid line fails without panicking.
+        for end in 0..valid.len() {
+            let _ = Pane::from_str(&valid[..end]);
+        }
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("212:53\n").unwrap(), (212, 53));
+        assert!(parse_size("212").is_err());
+        assert!(parse_size("212:").is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let output = r#"@copyrat-alphabet qwerty
+@copyrat-clipboard-exe "xclip -selection clipboard"
+@copyrat-window-name "[copyrat]"
+@copyrat-trusted-dirs /home/user/work:/srv/repos
+@other-plugin-option foo
+status on"#;
+
+        let options = parse_options(output, "@copyrat-");
+
+        assert_eq!(options.len(), 4);
+        assert_eq!(options["@copyrat-alphabet"], "qwerty");
+        assert_eq!(
+            options["@copyrat-clipboard-exe"],
+            "xclip -selection clipboard"
+        );
+        assert_eq!(options["@copyrat-window-name"], "[copyrat]");
+        assert_eq!(
+            options["@copyrat-trusted-dirs"],
+            "/home/user/work:/srv/repos"
+        );
+    }
+
+    #[test]
+    fn test_options_order() {
+        let output =
+            "@copyrat-custom-pattern-b b+\n@copyrat-custom-pattern-a a+\n@copyrat-alphabet qwerty";
+
+        let names: Vec<String> = parse_options(output, "@copyrat-").into_keys().collect();
+        assert_eq!(
+            names,
+            [
+                "@copyrat-alphabet",
+                "@copyrat-custom-pattern-a",
+                "@copyrat-custom-pattern-b"
+            ]
+        );
+    }
 }
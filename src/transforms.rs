@@ -0,0 +1,180 @@
+//! Transforms of the text selected from the spans of a pattern, see
+//! `--transform`.
+//!
+//! For instance, `--transform url=trim-punct` drops the punctuation ending a
+//! sentence after a URL, and `--transform path=expand-tilde` replaces a
+//! leading `~` by the home directory. The transforms of a pattern are applied
+//! in order, after its capture template. `sh:COMMAND` pipes the text through a
+//! shell command, such as `--transform 'sha=sh:cut -c1-7'`.
+
+use std::env;
+
+use crate::{Error, Result};
+
+/// Transform of a selected text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transform {
+    /// Remove the trailing punctuation, such as `.`, `,` or `'`, and the
+    /// trailing closing brackets which are not opened in the text.
+    TrimPunct,
+    /// Replace a leading `~` by `$HOME`.
+    ExpandTilde,
+    /// Remove the `a/` or `b/` prefix of a path in a diff.
+    StripDiffPrefix,
+    Lowercase,
+    Uppercase,
+    /// Pipe the text through the `sh` command, and use its output without
+    /// its trailing newline.
+    Command(String),
+}
+
+impl Transform {
+    /// Returns the transform named `name`, such as `trim-punct`, or the
+    /// shell command of `sh:COMMAND`.
+    pub fn parse(name: &str) -> Result<Transform> {
+        let transform = match name {
+            "trim-punct" => Transform::TrimPunct,
+            "expand-tilde" => Transform::ExpandTilde,
+            "strip-diff-prefix" => Transform::StripDiffPrefix,
+            "lowercase" => Transform::Lowercase,
+            "uppercase" => Transform::Uppercase,
+            _ => match name.strip_prefix("sh:") {
+                Some(command) if !command.is_empty() => Transform::Command(command.to_string()),
+                _ => return Err(Error::UnknownTransform(name.to_string())),
+            },
+        };
+
+        Ok(transform)
+    }
+
+    /// Returns the transformed `text`.
+    ///
+    /// The text is kept if the shell command fails, so that the selection is
+    /// not lost.
+    pub fn apply(&self, text: String) -> String {
+        match self {
+            Transform::TrimPunct => trim_punct(&text).to_string(),
+            Transform::ExpandTilde => match (text.strip_prefix('~'), env::var("HOME")) {
+                (Some(rest), Ok(home)) if rest.is_empty() || rest.starts_with('/') => {
+                    format!("{home}{rest}")
+                }
+                _ => text,
+            },
+            Transform::StripDiffPrefix => match text.strip_prefix("a/").or(text.strip_prefix("b/"))
+            {
+                Some(path) => path.to_string(),
+                None => text,
+            },
+            Transform::Lowercase => text.to_lowercase(),
+            Transform::Uppercase => text.to_uppercase(),
+            Transform::Command(command) => {
+                let output = duct::cmd!("sh", "-c", command)
+                    .stdin_bytes(text.as_str())
+                    .stderr_null()
+                    .read();
+                output.unwrap_or(text)
+            }
+        }
+    }
+}
+
+/// Transform applied to the text selected from the spans of a pattern, see
+/// `--transform`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternTransform {
+    /// Name of the pattern, such as `url`, or `custom` for the custom
+    /// patterns.
+    pub pattern: String,
+    pub transform: Transform,
+}
+
+/// Parse a `PATTERN=TRANSFORM` string into a `PatternTransform`, used during
+/// CLI parsing.
+pub fn parse_pattern_transform(src: &str) -> Result<PatternTransform> {
+    match src.split_once('=') {
+        Some((pattern, transform)) if !pattern.is_empty() => Ok(PatternTransform {
+            pattern: pattern.to_string(),
+            transform: Transform::parse(transform)?,
+        }),
+        _ => Err(Error::UnknownTransform(src.to_string())),
+    }
+}
+
+/// Returns the `text` selected from a span of the `pattern`, rewritten by the
+/// transforms of the pattern in order.
+pub fn apply(transforms: &[PatternTransform], pattern: &str, text: String) -> String {
+    transforms
+        .iter()
+        .filter(|transform| transform.pattern == pattern)
+        .fold(text, |text, transform| transform.transform.apply(text))
+}
+
+/// Returns the `text` without its trailing punctuation, nor its trailing
+/// closing brackets which are not opened in it.
+fn trim_punct(mut text: &str) -> &str {
+    while let Some(last) = text.chars().last() {
+        let unbalanced = |open: char| text.matches(open).count() < text.matches(last).count();
+        let trimmed = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' | '\'' | '"' => true,
+            ')' => unbalanced('('),
+            ']' => unbalanced('['),
+            '}' => unbalanced('{'),
+            _ => false,
+        };
+        if !trimmed {
+            break;
+        }
+        text = &text[..text.len() - last.len_utf8()];
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_transforms() {
+        let apply = |name: &str, text: &str| Transform::parse(name).unwrap().apply(text.into());
+
+        assert_eq!(apply("trim-punct", "https://a.io/x."), "https://a.io/x");
+        assert_eq!(apply("trim-punct", "https://a.io/x)."), "https://a.io/x");
+        assert_eq!(
+            apply(
+                "trim-punct",
+                "https://en.wikipedia.org/wiki/Rust_(language)"
+            ),
+            "https://en.wikipedia.org/wiki/Rust_(language)"
+        );
+        assert_eq!(apply("strip-diff-prefix", "b/src/main.rs"), "src/main.rs");
+        assert_eq!(apply("strip-diff-prefix", "src/a/b.rs"), "src/a/b.rs");
+        assert_eq!(apply("lowercase", "DEADBEEF"), "deadbeef");
+        assert_eq!(apply("expand-tilde", "~user/notes"), "~user/notes");
+        let home = env::var("HOME").unwrap();
+        assert_eq!(apply("expand-tilde", "~/notes"), format!("{home}/notes"));
+        assert_eq!(apply("sh:tr a-z A-Z", "v1.2"), "V1.2");
+        // A failing command keeps the text.
+        assert_eq!(apply("sh:exit 1", "v1.2"), "v1.2");
+
+        assert!(Transform::parse("reverse").is_err());
+        assert!(Transform::parse("sh:").is_err());
+    }
+
+    #[test]
+    fn transforms_of_pattern() {
+        let transforms = [
+            parse_pattern_transform("url=trim-punct").unwrap(),
+            parse_pattern_transform("url=uppercase").unwrap(),
+            parse_pattern_transform("sha=sh:cut -c1-7").unwrap(),
+        ];
+        assert_eq!(
+            apply(&transforms, "url", "https://a.io,".into()),
+            "HTTPS://A.IO"
+        );
+        assert_eq!(apply(&transforms, "sha", "4f1a2b3c4d5e".into()), "4f1a2b3");
+        assert_eq!(apply(&transforms, "path", "a.".into()), "a.");
+
+        assert!(parse_pattern_transform("trim-punct").is_err());
+        assert!(parse_pattern_transform("=trim-punct").is_err());
+    }
+}
@@ -0,0 +1,29 @@
+use std::io;
+
+use clap::{Parser, ValueEnum};
+
+/// Events for which the terminal bell can be rung, as non-visual feedback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum BellEvent {
+    /// A span was selected.
+    Yank,
+    /// No span matched the patterns, copyrat exits right away.
+    NoMatch,
+    /// A key matching no hint and no command was pressed.
+    UnknownKey,
+}
+
+/// Ring the terminal bell on the `writer`.
+///
+/// Failures are ignored, as the bell is only a convenience.
+pub fn ring(writer: &mut dyn io::Write) {
+    let _ = writer.write_all(b"\x07").and_then(|_| writer.flush());
+}
+
+/// Ring the terminal bell on the controlling terminal, when the `ViewController`
+/// is not running (stdout may be piped).
+pub fn ring_tty() {
+    if let Ok(mut tty) = termion::get_tty() {
+        ring(&mut tty);
+    }
+}
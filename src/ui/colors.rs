@@ -76,6 +76,100 @@ pub fn parse_color(src: &str) -> Result<Color> {
     Color::from_str(src)
 }
 
+/// Colors of the spans of a pattern, instead of `span_fg` and `span_bg`, see
+/// `--pattern-color`.
+#[derive(Debug, Clone)]
+pub struct PatternColor {
+    /// Name of the pattern, such as `url`, or `custom` for the custom
+    /// patterns.
+    pub pattern: String,
+    pub fg: Color,
+    /// Background color, `span_bg` if `None`.
+    pub bg: Option<Color>,
+}
+
+/// Parse a `PATTERN:FG[:BG]` string, such as `url:blue`, into a
+/// `PatternColor`, used during CLI parsing.
+pub fn parse_pattern_color(src: &str) -> Result<PatternColor> {
+    let mut parts = src.split(':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(pattern), Some(fg), bg, None) if !pattern.is_empty() => Ok(PatternColor {
+            pattern: pattern.to_string(),
+            fg: parse_color(fg)?,
+            bg: bg.map(parse_color).transpose()?,
+        }),
+        _ => Err(Error::InvalidPatternColor(src.to_string())),
+    }
+}
+
+/// Minimum contrast ratio between the foreground and background colors, as
+/// recommended by WCAG for large text.
+pub const MIN_CONTRAST: f64 = 3.0;
+
+/// The first 16 colors of the xterm palette, which terminals often customize.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// Returns the approximate RGB value of the color in the xterm 256-color
+    /// palette, or `None` for the default color of the terminal.
+    fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let index = self.0?;
+        let rgb = match index {
+            0..=15 => ANSI_RGB[index as usize],
+            16..=231 => {
+                let level = |value: u8| if value == 0 { 0 } else { 55 + 40 * value };
+                let index = index - 16;
+                (level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            232..=255 => {
+                let gray = 8 + 10 * (index - 232);
+                (gray, gray, gray)
+            }
+        };
+        Some(rgb)
+    }
+
+    /// Returns the relative luminance of the color, as defined by WCAG.
+    fn luminance(&self) -> Option<f64> {
+        let linear = |value: u8| {
+            let value = value as f64 / 255.0;
+            if value <= 0.03928 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = self.rgb()?;
+        Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+    }
+}
+
+/// Returns the contrast ratio between the `fg` and `bg` colors, from 1 to 21,
+/// or `None` if one of them is the default color of the terminal, which is
+/// unknown.
+pub fn contrast(fg: &Color, bg: &Color) -> Option<f64> {
+    let (fg, bg) = (fg.luminance()?, bg.luminance()?);
+    let (lighter, darker) = if fg > bg { (fg, bg) } else { (bg, fg) };
+    Some((lighter + 0.05) / (darker + 0.05))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +190,71 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn contrast_ratio() {
+        let ratio = contrast(&BLACK, &BRIGHTWHITE).unwrap();
+        assert!((ratio - 21.0).abs() < 1e-9);
+        assert!(contrast(&YELLOW, &BRIGHTWHITE).unwrap() < MIN_CONTRAST);
+        assert!(contrast(&YELLOW, &BLACK).unwrap() > MIN_CONTRAST);
+        assert_eq!(contrast(&YELLOW, &RESET), None);
+
+        // The 256-color cube and grayscale ramp.
+        assert_eq!(Color(Some(196)).rgb(), Some((255, 0, 0)));
+        assert_eq!(Color(Some(244)).rgb(), Some((128, 128, 128)));
+    }
+
+    #[test]
+    fn enforce_minimum_contrast() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Opt {
+            #[command(flatten)]
+            colors: UiColors,
+        }
+
+        let mut colors = Opt::parse_from(["copyrat", "--hint-bg", "bright-white"]).colors;
+        assert_eq!(colors.low_contrast_pairs(), ["hint"]);
+
+        colors.enforce_contrast();
+        assert!(colors.low_contrast_pairs().is_empty());
+        assert_eq!(colors.hint_fg.0, BLACK.0);
+    }
+
+    #[test]
+    fn pattern_colors() {
+        use clap::Parser;
+
+        #[derive(Parser)]
+        struct Opt {
+            #[command(flatten)]
+            colors: UiColors,
+        }
+
+        let colors = Opt::parse_from([
+            "copyrat",
+            "--span-fg",
+            "white",
+            "--span-bg",
+            "black",
+            "--pattern-color",
+            "url:blue",
+            "--pattern-color",
+            "sha:black:yellow",
+        ])
+        .colors;
+        assert_eq!(colors.span_colors("url").0 .0, BLUE.0);
+        assert_eq!(colors.span_colors("url").1 .0, BLACK.0);
+        assert_eq!(colors.span_colors("sha").1 .0, YELLOW.0);
+        assert_eq!(colors.span_colors("ipv4").0 .0, colors.span_fg.0);
+        assert_eq!(colors.low_contrast_pairs(), ["pattern-url"]);
+
+        assert!(parse_pattern_color("url").is_err());
+        assert!(parse_pattern_color(":blue").is_err());
+        assert!(parse_pattern_color("url:blue:red:green").is_err());
+        assert!(parse_pattern_color("url:wat").is_err());
+    }
+
     #[test]
     fn no_span_color() {
         assert!(
@@ -108,7 +267,9 @@ mod tests {
 /// Holds color-related data.
 ///
 /// - `focus_*` colors are used to render the currently focused text span.
+/// - `selected_*` colors are used to render the selected or marked text spans.
 /// - `normal_*` colors are used to render other text spans.
+/// - `recent_*` colors are used to render text spans on the most recent lines.
 /// - `hint_*` colors are used to render the hints.
 #[derive(Args, Debug)]
 // #[clap(about)] // Needed to avoid this doc comment to be used as overall `about`.
@@ -129,6 +290,16 @@ pub struct UiColors {
     #[clap(long, default_value = "none", value_parser(parse_color))]
     pub span_bg: Color,
 
+    /// Foreground color for spans on the most recent lines, see
+    /// `--recent-lines`.
+    #[clap(long, default_value = "bright-blue", value_parser(parse_color))]
+    pub recent_fg: Color,
+
+    /// Background color for spans on the most recent lines, see
+    /// `--recent-lines`.
+    #[clap(long, default_value = "none", value_parser(parse_color))]
+    pub recent_bg: Color,
+
     /// Foreground color for the focused span.
     #[clap(long, default_value = "magenta", value_parser(parse_color))]
     pub focused_fg: Color,
@@ -137,6 +308,16 @@ pub struct UiColors {
     #[clap(long, default_value = "none", value_parser(parse_color))]
     pub focused_bg: Color,
 
+    /// Foreground color for the spans already selected with `--loop` or
+    /// `--stay-open`, and the spans marked in multi-select mode.
+    #[clap(long, default_value = "green", value_parser(parse_color))]
+    pub selected_fg: Color,
+
+    /// Background color for the spans already selected with `--loop` or
+    /// `--stay-open`, and the spans marked in multi-select mode.
+    #[clap(long, default_value = "none", value_parser(parse_color))]
+    pub selected_bg: Color,
+
     /// Foreground color for hints.
     #[clap(long, default_value = "yellow", value_parser(parse_color))]
     pub hint_fg: Color,
@@ -144,4 +325,93 @@ pub struct UiColors {
     /// Background color for hints.
     #[clap(long, default_value = "none", value_parser(parse_color))]
     pub hint_bg: Color,
+
+    /// Colors of the spans of a pattern, instead of the span colors, such as
+    /// `url:blue` or `sha:black:yellow`.
+    ///
+    /// Name the custom patterns `custom`. Without background color, the span
+    /// background color is used.
+    #[arg(
+        long = "pattern-color",
+        value_name = "PATTERN:FG[:BG]",
+        value_parser(parse_pattern_color)
+    )]
+    pub pattern_colors: Vec<PatternColor>,
+}
+
+impl UiColors {
+    /// Returns the foreground and background colors of the spans of the
+    /// `pattern`: its pattern colors if any, or the span colors.
+    pub fn span_colors(&self, pattern: &str) -> (Color, Color) {
+        match self
+            .pattern_colors
+            .iter()
+            .rev()
+            .find(|colors| colors.pattern == pattern)
+        {
+            Some(colors) => (colors.fg, colors.bg.unwrap_or(self.span_bg)),
+            None => (self.span_fg, self.span_bg),
+        }
+    }
+
+    /// Returns the foreground and background colors of the text, spans and
+    /// hints, with their names, then the pattern colors, named like
+    /// `pattern-url`.
+    fn pairs(&self) -> Vec<(String, Color, Color)> {
+        let pairs = [
+            ("text", self.text_fg, self.text_bg),
+            ("span", self.span_fg, self.span_bg),
+            ("recent", self.recent_fg, self.recent_bg),
+            ("focused", self.focused_fg, self.focused_bg),
+            ("selected", self.selected_fg, self.selected_bg),
+            ("hint", self.hint_fg, self.hint_bg),
+        ];
+        let pattern_pairs = self.pattern_colors.iter().map(|colors| {
+            let (fg, bg) = self.span_colors(&colors.pattern);
+            (format!("pattern-{}", colors.pattern), fg, bg)
+        });
+
+        pairs
+            .into_iter()
+            .map(|(name, fg, bg)| (name.to_string(), fg, bg))
+            .chain(pattern_pairs)
+            .collect()
+    }
+
+    /// Returns the names of the color pairs, such as `hint` for `hint_fg` and
+    /// `hint_bg`, whose contrast is below `MIN_CONTRAST`.
+    pub fn low_contrast_pairs(&self) -> Vec<String> {
+        self.pairs()
+            .into_iter()
+            .filter(|(_, fg, bg)| contrast(fg, bg).is_some_and(|ratio| ratio < MIN_CONTRAST))
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+
+    /// Replace the foreground colors whose contrast with their background is
+    /// below `MIN_CONTRAST` by black or bright white, whichever contrasts
+    /// most.
+    pub fn enforce_contrast(&mut self) {
+        let adjust = |fg: &mut Color, bg: Color| {
+            if contrast(fg, &bg).is_some_and(|ratio| ratio < MIN_CONTRAST) {
+                let on_black = contrast(&BLACK, &bg).unwrap_or(0.0);
+                let on_white = contrast(&BRIGHTWHITE, &bg).unwrap_or(0.0);
+                *fg = if on_black > on_white {
+                    BLACK
+                } else {
+                    BRIGHTWHITE
+                };
+            }
+        };
+
+        adjust(&mut self.text_fg, self.text_bg);
+        adjust(&mut self.span_fg, self.span_bg);
+        adjust(&mut self.recent_fg, self.recent_bg);
+        adjust(&mut self.focused_fg, self.focused_bg);
+        adjust(&mut self.selected_fg, self.selected_bg);
+        adjust(&mut self.hint_fg, self.hint_bg);
+        for colors in &mut self.pattern_colors {
+            adjust(&mut colors.fg, colors.bg.unwrap_or(self.span_bg));
+        }
+    }
 }
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use clap::{Parser, ValueEnum};
+
+use super::Selection;
+use crate::config::extended::OutputDestination;
+
+/// Describes the alternate action triggered when a hint is typed twice
+/// quickly, as an alternative to uppercase hints.
+#[derive(Debug, Clone, ValueEnum, Parser)]
+pub enum DoubleTapAction {
+    /// Paste the selected text into the active pane, like uppercase hints.
+    Paste,
+    /// Copy the selected text to the system clipboard.
+    Clipboard,
+}
+
+impl DoubleTapAction {
+    /// Modify the `selection` according to this alternate action.
+    pub fn apply(&self, selection: &mut Selection) {
+        match self {
+            Self::Paste => selection.uppercased = true,
+            Self::Clipboard => selection.output_destination = OutputDestination::Clipboard,
+        }
+    }
+}
+
+/// Double-tap detection settings: which action is triggered, and the maximum
+/// delay between both taps.
+pub struct DoubleTap {
+    pub action: DoubleTapAction,
+    pub delay: Duration,
+}
@@ -0,0 +1,156 @@
+//! Expansions of the focused span, grown with `grow` and shrunk with `shrink`
+//! before yanking it.
+//!
+//! The span grows to the enclosing word, then to the enclosing quoted strings
+//! or brackets, first without then with their delimiters, and finally to its
+//! whole line.
+
+use std::ops::Range;
+
+/// Pairs of delimiters enclosing quoted strings or brackets. A quote closes
+/// itself.
+const DELIMITERS: [(char, char); 6] = [
+    ('"', '"'),
+    ('\'', '\''),
+    ('`', '`'),
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+];
+
+/// Returns the byte ranges of the `line` which the `span` range grows to,
+/// from the span itself to the line without its surrounding whitespace. Each
+/// range strictly contains the previous one.
+pub fn expansions(line: &str, span: Range<usize>) -> Vec<Range<usize>> {
+    let mut range = word(line, span.clone());
+    let mut ranges = vec![span];
+    let mut push = |range: Range<usize>| {
+        let last = ranges.last().unwrap();
+        if range.start <= last.start && range.end >= last.end && range != *last {
+            ranges.push(range);
+        }
+    };
+
+    push(range.clone());
+    while let Some(enclosing) = enclosing(line, range.clone()) {
+        push(enclosing.clone());
+        range = enclosing;
+    }
+    let start = line.len() - line.trim_start().len();
+    push(start..line.trim_end().len().max(start));
+
+    ranges
+}
+
+/// Returns the `range` extended to the enclosing word: the chars which are
+/// neither whitespace nor delimiters.
+fn word(line: &str, range: Range<usize>) -> Range<usize> {
+    let is_word = |c: char| !c.is_whitespace() && !is_delimiter(c);
+
+    let start = line[..range.start]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(range.start, |(i, _)| i);
+    let end = line[range.end..]
+        .char_indices()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map_or(range.end, |(i, c)| range.end + i + c.len_utf8());
+
+    start..end
+}
+
+/// Returns the range between the innermost pair of delimiters enclosing the
+/// `range`, or including them if the `range` is already between them.
+fn enclosing(line: &str, range: Range<usize>) -> Option<Range<usize>> {
+    line[..range.start]
+        .char_indices()
+        .rev()
+        .find_map(|(open_at, open)| {
+            let close_at = closing(line, open_at, open)?;
+            (close_at >= range.end).then_some((open_at, close_at))
+        })
+        .map(|(open_at, close_at)| {
+            let inner = open_at + 1..close_at;
+            if inner == range {
+                open_at..close_at + 1
+            } else {
+                inner
+            }
+        })
+}
+
+/// Returns the index of the delimiter closing the `open` delimiter found at
+/// `open_at` in the `line`, if it opens a pair.
+///
+/// A quote opens a pair if an even number of the same quotes precedes it.
+/// Nested brackets are skipped.
+fn closing(line: &str, open_at: usize, open: char) -> Option<usize> {
+    let &(_, close) = DELIMITERS.iter().find(|&&(o, _)| o == open)?;
+    let after = open_at + open.len_utf8();
+
+    if open == close {
+        if line[..open_at].matches(open).count() & 1 == 1 {
+            return None;
+        }
+        return line[after..].find(close).map(|i| after + i);
+    }
+
+    let mut depth = 0;
+    for (i, c) in line[after..].char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(after + i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Returns `true` if `c` opens or closes a pair of delimiters.
+fn is_delimiter(c: char) -> bool {
+    DELIMITERS
+        .iter()
+        .any(|&(open, close)| c == open || c == close)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'l>(line: &'l str, span: &str) -> Vec<&'l str> {
+        let start = line.find(span).unwrap();
+        expansions(line, start..start + span.len())
+            .into_iter()
+            .map(|range| &line[range])
+            .collect()
+    }
+
+    #[test]
+    fn expand_to_word_quotes_and_line() {
+        assert_eq!(
+            texts(r#"  run f(a, "b:c d") now  "#, "c"),
+            [
+                "c",
+                "b:c",
+                "b:c d",
+                r#""b:c d""#,
+                r#"a, "b:c d""#,
+                r#"(a, "b:c d")"#,
+                r#"run f(a, "b:c d") now"#,
+            ]
+        );
+        // The quotes of another string do not enclose the span.
+        assert_eq!(
+            texts(r#""a" path/é.rs "c""#, "é"),
+            ["é", "path/é.rs", r#""a" path/é.rs "c""#]
+        );
+        assert_eq!(texts("[x[1]]", "1"), ["1", "[1]", "x[1]", "[x[1]]"]);
+        assert_eq!(texts("word", "word"), ["word"]);
+    }
+}
@@ -0,0 +1,232 @@
+//! Decoding of the keys entered by the user.
+//!
+//! With the legacy input of terminals, some key combinations cannot be told
+//! apart: `Ctrl-i` is `Tab`, `Ctrl-Shift-y` is `Ctrl-y`, and `Esc` followed by
+//! a key quickly is `Alt` with that key. Terminals supporting the kitty
+//! keyboard protocol, such as kitty, WezTerm, foot or Ghostty, send these
+//! combinations as unambiguous `CSI code ; modifiers u` sequences once the
+//! protocol is enabled.
+//!
+//! The decoded keys are termion keys. As termion never reads `Ctrl` with an
+//! uppercase letter, `Ctrl-Shift-y` is decoded as `Key::Ctrl('Y')`.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::fd::AsRawFd;
+
+use clap::{Parser, ValueEnum};
+use termion::event::{Event, Key};
+use termion::input::TermRead;
+
+/// Keys entered by the user, as decoded by the `KeyboardProtocol`.
+pub type KeyIterator<'r> = dyn Iterator<Item = io::Result<Key>> + 'r;
+
+/// Describes how the terminal reports the keys.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Parser)]
+pub enum KeyboardProtocol {
+    /// The legacy input of all terminals.
+    #[default]
+    Legacy,
+    /// The kitty keyboard protocol, enabled while copyrat is displayed.
+    ///
+    /// Terminals which do not support it ignore the request and keep sending
+    /// legacy input.
+    Kitty,
+}
+
+impl KeyboardProtocol {
+    /// Returns the escape sequence asking the terminal to report the keys
+    /// with this protocol.
+    pub fn enable_sequence(&self) -> &'static str {
+        match self {
+            // Push the "disambiguate escape codes" flag on the stack.
+            Self::Kitty => "\x1b[>1u",
+            Self::Legacy => "",
+        }
+    }
+
+    /// Returns the escape sequence restoring the keyboard mode in use before
+    /// `enable_sequence`.
+    pub fn disable_sequence(&self) -> &'static str {
+        match self {
+            // Pop the flags pushed by `enable_sequence`.
+            Self::Kitty => "\x1b[<u",
+            Self::Legacy => "",
+        }
+    }
+
+    /// Returns the keys read from the `reader`.
+    ///
+    /// The same iterator must be used for the whole session: it may hold a
+    /// leftover byte between two keys.
+    pub fn keys<'r>(&self, reader: &'r mut dyn io::Read) -> Box<KeyIterator<'r>> {
+        match self {
+            Self::Legacy => Box::new(reader.keys()),
+            Self::Kitty => Box::new(reader.events().filter_map(|event| match event {
+                Ok(Event::Key(key)) => Some(Ok(key)),
+                // termion does not know these sequences.
+                Ok(Event::Unsupported(bytes)) => decode_csi_u(&bytes).map(Ok),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })),
+        }
+    }
+}
+
+/// Reader of the terminal input which never blocks: it reads nothing if no
+/// byte was entered.
+///
+/// Unlike `termion::async_stdin`, no thread reads the terminal in the
+/// background, so that a command run meanwhile, such as the pager, receives
+/// all the keys.
+pub struct TtyReader {
+    tty: File,
+}
+
+impl TtyReader {
+    /// Open the controlling terminal, `/dev/tty`.
+    pub fn new() -> io::Result<TtyReader> {
+        Ok(TtyReader {
+            tty: File::open("/dev/tty")?,
+        })
+    }
+}
+
+impl Read for TtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut pollfd = libc::pollfd {
+            fd: self.tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut pollfd, 1, 0) };
+        match ready {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Ok(0),
+            _ => self.tty.read(buf),
+        }
+    }
+}
+
+/// Modifier bits of the kitty keyboard protocol, once 1 is subtracted from
+/// the transmitted value.
+const SHIFT: u32 = 0b1;
+const ALT: u32 = 0b10;
+const CTRL: u32 = 0b100;
+
+/// Returns the key of a `CSI code ; modifiers u` sequence, such as
+/// `\x1b[121;5u` for `Ctrl-y`, or `None` if it is not a key that copyrat
+/// can handle.
+fn decode_csi_u(bytes: &[u8]) -> Option<Key> {
+    let params = std::str::from_utf8(bytes)
+        .ok()?
+        .strip_prefix("\x1b[")?
+        .strip_suffix('u')?;
+    let (code, modifiers) = params.split_once(';').unwrap_or((params, "1"));
+
+    // Ignore the alternate key codes, if any.
+    let code: u32 = code.split(':').next()?.parse().ok()?;
+    let (modifiers, event_type) = modifiers.split_once(':').unwrap_or((modifiers, "1"));
+    if event_type != "1" {
+        // A repeat or a release.
+        return None;
+    }
+    let modifiers = modifiers.parse::<u32>().ok()?.checked_sub(1)?;
+
+    let key = match code {
+        27 => Key::Esc,
+        13 => Key::Char('\n'),
+        9 => Key::Char('\t'),
+        127 => Key::Backspace,
+        code => Key::Char(char::from_u32(code)?),
+    };
+    let Key::Char(ch) = key else {
+        return (modifiers == 0).then_some(key);
+    };
+    // Alt-Enter is reported like with the legacy protocol, `ESC CR`.
+    if ch == '\n' && modifiers == ALT {
+        return Some(Key::Alt('\r'));
+    }
+    if ch == '\n' || ch == '\t' {
+        return (modifiers == 0).then_some(key);
+    }
+
+    let ch = if modifiers & SHIFT != 0 {
+        ch.to_uppercase().next()?
+    } else {
+        ch
+    };
+    match modifiers & !SHIFT {
+        0 => Some(Key::Char(ch)),
+        ALT => Some(Key::Alt(ch)),
+        CTRL => Some(Key::Ctrl(ch)),
+        // Super, Hyper, Meta, and Ctrl-Alt.
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::FromRawFd;
+
+    #[test]
+    fn decode_kitty_keys() {
+        assert_eq!(decode_csi_u(b"\x1b[27u"), Some(Key::Esc));
+        assert_eq!(decode_csi_u(b"\x1b[121;5u"), Some(Key::Ctrl('y')));
+        assert_eq!(decode_csi_u(b"\x1b[121;6u"), Some(Key::Ctrl('Y')));
+        assert_eq!(decode_csi_u(b"\x1b[97;3u"), Some(Key::Alt('a')));
+        assert_eq!(decode_csi_u(b"\x1b[97;4u"), Some(Key::Alt('A')));
+        assert_eq!(decode_csi_u(b"\x1b[105;5u"), Some(Key::Ctrl('i')));
+        assert_eq!(decode_csi_u(b"\x1b[97;3:1u"), Some(Key::Alt('a')));
+        assert_eq!(decode_csi_u(b"\x1b[13;3u"), Some(Key::Alt('\r')));
+
+        // Releases, Ctrl-Alt, and keys with modifiers that copyrat ignores.
+        assert_eq!(decode_csi_u(b"\x1b[97;3:3u"), None);
+        assert_eq!(decode_csi_u(b"\x1b[97;7u"), None);
+        assert_eq!(decode_csi_u(b"\x1b[13;5u"), None);
+        assert_eq!(decode_csi_u(b"\x1b[1;5A"), None);
+    }
+
+    #[test]
+    fn read_tty_without_blocking() {
+        let mut master = 0;
+        let mut slave = 0;
+        let ret = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        assert_eq!(ret, 0, "cannot open a pseudo-terminal");
+        let (mut master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        // Deliver the bytes right away, as in raw mode.
+        let _raw = termion::raw::IntoRawMode::into_raw_mode(slave.try_clone().unwrap()).unwrap();
+
+        let mut reader = TtyReader { tty: slave };
+        let mut buf = [0; 2];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        io::Write::write_all(&mut master, b"y").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(reader.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'y');
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_kitty_keys() {
+        let mut reader = "a\x1b[121;5u\x1b[27u\n".as_bytes();
+        let keys: Vec<Key> = KeyboardProtocol::Kitty
+            .keys(&mut reader)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            keys,
+            [Key::Char('a'), Key::Ctrl('y'), Key::Esc, Key::Char('\n')]
+        );
+    }
+}
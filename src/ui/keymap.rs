@@ -0,0 +1,295 @@
+//! Keys of the commands of the UI.
+//!
+//! Each command, such as `yank`, is bound to default keys, which are replaced
+//! with `--key yank='y Enter'` or the `@copyrat-key-yank` tmux option. The
+//! keys are separated by spaces, and named like in tmux: a character,
+//! `Enter`, `Tab`, `Space`, `Escape`, `Up`, `PageUp`..., or `C-x` and `M-x`
+//! for `x` with `Ctrl` and `Alt`.
+//!
+//! The characters bound to no command type the hints.
+
+use std::collections::HashMap;
+
+use clap::{Parser, ValueEnum};
+use termion::event::Key;
+
+use crate::{Error, Result};
+
+/// Commands of the UI which can be bound to keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Parser)]
+pub enum Command {
+    /// Exit without selecting anything.
+    Exit,
+    /// Move the focus to the previous span.
+    Up,
+    /// Move the focus to the next span.
+    Down,
+    /// Move the focus to the next span in the hint order, which depends on
+    /// `--reverse`.
+    Next,
+    /// Move the focus to the previous span in the hint order.
+    Previous,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    /// Collapse or expand the group of the focused span, or toggle
+    /// multi-select mode.
+    Toggle,
+    /// Select the focused span, or the marked spans.
+    Yank,
+    /// Select and paste the focused span, or the marked spans.
+    YankPaste,
+    /// Select all the spans.
+    YankAll,
+    /// Select and paste all the spans.
+    YankAllPaste,
+    /// Select the whole line of the focused span.
+    YankLine,
+    /// Type the focused span, or the marked spans, into the pane.
+    TypeIntoPane,
+    /// Grow the focused span to the enclosing word, then quoted string or
+    /// brackets, then line, see `expansion`.
+    Grow,
+    /// Shrink the focused span back to its previous expansion.
+    Shrink,
+    /// Show the next selection of the history in the status bar, selected
+    /// instead of the focused span, see `--history-size`.
+    History,
+    /// Run the action of the focused span, see `--action`.
+    Action,
+    /// Type a filter narrowing down the spans.
+    Filter,
+    /// Toggle the output destination between the tmux buffer and the
+    /// clipboard.
+    ToggleDestination,
+    /// Display the whole buffer in the pager, see `--pager`.
+    Pager,
+    /// Capture the pane again and find its spans, see `--refresh-interval`.
+    Refresh,
+}
+
+/// Default keys of the commands.
+const DEFAULT_BINDINGS: [(Command, &[Key]); 24] = [
+    (Command::Exit, &[Key::Esc]),
+    (Command::Up, &[Key::Up, Key::Left]),
+    (Command::Down, &[Key::Down, Key::Right]),
+    (Command::Next, &[Key::Char('n')]),
+    (Command::Previous, &[Key::Char('N')]),
+    (Command::PageUp, &[Key::PageUp]),
+    (Command::PageDown, &[Key::PageDown]),
+    (Command::HalfPageUp, &[Key::Ctrl('u')]),
+    (Command::HalfPageDown, &[Key::Ctrl('d')]),
+    (Command::Toggle, &[Key::Char('\t')]),
+    (Command::Yank, &[Key::Char('y'), Key::Char('\n')]),
+    (Command::YankPaste, &[Key::Char('Y')]),
+    (Command::YankAll, &[Key::Ctrl('y')]),
+    (Command::YankAllPaste, &[Key::Ctrl('Y')]),
+    (Command::YankLine, &[Key::Ctrl('l')]),
+    (Command::TypeIntoPane, &[Key::Alt('\r')]),
+    // Not `+` and `-`, nor `M-+` and `M--`: `+` and `-` are hints of the
+    // `longest` alphabet, and `Alt` and a hint run the alternate action.
+    (Command::Grow, &[Key::Ctrl('e')]),
+    (Command::Shrink, &[Key::Ctrl('w')]),
+    (Command::History, &[Key::Ctrl('h')]),
+    (Command::Action, &[Key::Char('o')]),
+    (Command::Filter, &[Key::Char('/')]),
+    (Command::ToggleDestination, &[Key::Char(' ')]),
+    (Command::Pager, &[Key::Ctrl('p')]),
+    (Command::Refresh, &[Key::Ctrl('r')]),
+];
+
+/// Keys bound to a command, replacing its default keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub command: Command,
+    pub keys: Vec<Key>,
+}
+
+impl KeyBinding {
+    /// Returns the binding of the `command`, named like `yank` or
+    /// `half-page-up`, to the space-separated `keys`.
+    pub fn new(command: &str, keys: &str) -> Result<KeyBinding> {
+        let case_insensitive = true;
+        let command =
+            Command::from_str(command, case_insensitive).map_err(Error::ExpectedEnumVariant)?;
+        let keys = keys
+            .split_whitespace()
+            .map(parse_key)
+            .collect::<Result<_>>()?;
+
+        Ok(KeyBinding { command, keys })
+    }
+}
+
+/// Parse a `COMMAND=KEYS` key binding, such as `yank=y Enter`.
+pub fn parse_key_binding(src: &str) -> Result<KeyBinding> {
+    match src.split_once('=') {
+        Some((command, keys)) if !command.is_empty() => KeyBinding::new(command, keys),
+        _ => Err(Error::InvalidKeyBinding(src.to_string())),
+    }
+}
+
+/// Parse a key named like in tmux, such as `y`, `Enter` or `C-u`.
+pub fn parse_key(name: &str) -> Result<Key> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(ch));
+    }
+
+    if let Some(key) = name.strip_prefix("C-").and_then(single_char) {
+        return Ok(Key::Ctrl(key));
+    }
+    if let Some(key) = name.strip_prefix("M-").and_then(single_char) {
+        return Ok(Key::Alt(key));
+    }
+
+    let key = match name.to_lowercase().as_str() {
+        "enter" => Key::Char('\n'),
+        // Alt-Enter, sent as `ESC CR`.
+        "m-enter" => Key::Alt('\r'),
+        "tab" => Key::Char('\t'),
+        "btab" => Key::BackTab,
+        "space" => Key::Char(' '),
+        "escape" | "esc" => Key::Esc,
+        "bspace" | "backspace" => Key::Backspace,
+        "dc" | "delete" => Key::Delete,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "ppage" | "pageup" | "pgup" => Key::PageUp,
+        "npage" | "pagedown" | "pgdn" => Key::PageDown,
+        _ => return Err(Error::UnknownKey(name.to_string())),
+    };
+
+    Ok(key)
+}
+
+/// Returns the only character of `s`, if any.
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
+    }
+}
+
+/// Commands of the keys, from the default bindings and the `KeyBinding`s.
+#[derive(Debug)]
+pub struct KeyMap {
+    commands: HashMap<Key, Command>,
+}
+
+impl KeyMap {
+    /// Returns the default key map, where the commands of the `bindings` are
+    /// bound to their keys instead, the last binding of a command winning.
+    ///
+    /// A key bound to a command is unbound from the others.
+    pub fn new(bindings: &[KeyBinding]) -> KeyMap {
+        let mut commands = HashMap::new();
+        for (command, keys) in DEFAULT_BINDINGS {
+            if !bindings.iter().any(|binding| binding.command == command) {
+                commands.extend(keys.iter().map(|&key| (key, command)));
+            }
+        }
+
+        for binding in bindings {
+            commands.retain(|_, command| *command != binding.command);
+            commands.extend(binding.keys.iter().map(|&key| (key, binding.command)));
+        }
+
+        KeyMap { commands }
+    }
+
+    /// Returns the command bound to the `key`, if any.
+    pub fn command(&self, key: &Key) -> Option<Command> {
+        self.commands.get(key).copied()
+    }
+
+    /// Returns the characters bound to a command, alone or with `Alt`, and
+    /// the command, which cannot be used in the hints: `Alt` and a hint
+    /// letter run the alternate action.
+    pub fn chars(&self) -> impl Iterator<Item = (char, Command)> + '_ {
+        self.commands.iter().filter_map(|(key, command)| match key {
+            Key::Char(ch) | Key::Alt(ch) => Some((*ch, *command)),
+            _ => None,
+        })
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> KeyMap {
+        KeyMap::new(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::textbuf::alphabet;
+
+    #[test]
+    fn parse_key_names() {
+        assert_eq!(parse_key("y").unwrap(), Key::Char('y'));
+        assert_eq!(parse_key("Enter").unwrap(), Key::Char('\n'));
+        assert_eq!(parse_key("space").unwrap(), Key::Char(' '));
+        assert_eq!(parse_key("NPage").unwrap(), Key::PageDown);
+        assert_eq!(parse_key("C-u").unwrap(), Key::Ctrl('u'));
+        assert_eq!(parse_key("M-a").unwrap(), Key::Alt('a'));
+        assert_eq!(parse_key("M-Enter").unwrap(), Key::Alt('\r'));
+        assert_eq!(parse_key("M--").unwrap(), Key::Alt('-'));
+        assert!(parse_key("C-Enter").is_err());
+        assert!(parse_key("yank").is_err());
+    }
+
+    #[test]
+    fn override_default_keys() {
+        let bindings = [
+            parse_key_binding("yank=Enter").unwrap(),
+            parse_key_binding("next=j").unwrap(),
+            parse_key_binding("previous=k n").unwrap(),
+        ];
+        let keymap = KeyMap::new(&bindings);
+
+        assert_eq!(keymap.command(&Key::Char('\n')), Some(Command::Yank));
+        assert_eq!(keymap.command(&Key::Char('y')), None);
+        assert_eq!(keymap.command(&Key::Char('j')), Some(Command::Next));
+        // `n` moved from `next` to `previous`.
+        assert_eq!(keymap.command(&Key::Char('n')), Some(Command::Previous));
+        assert_eq!(keymap.command(&Key::Char('N')), None);
+        assert_eq!(keymap.command(&Key::Esc), Some(Command::Exit));
+
+        assert!(parse_key_binding("yank").is_err());
+        assert!(parse_key_binding("jump=j").is_err());
+    }
+
+    #[test]
+    fn default_keys_do_not_shadow_alt_hints() {
+        // `Alt` and a hint letter run the alternate action, unless bound.
+        let keymap = KeyMap::default();
+        let layouts = ["qwerty", "azerty", "qwertz", "dvorak", "colemak"];
+        let variants = ["", "-homerow", "-left-hand", "-right-hand"];
+        let names = layouts
+            .iter()
+            .flat_map(|layout| {
+                variants
+                    .iter()
+                    .map(move |variant| format!("{layout}{variant}"))
+            })
+            .chain(["numeric".to_string(), "longest".to_string()]);
+        for name in names {
+            let alphabet = alphabet::parse_alphabet(&name).unwrap();
+            for letter in alphabet.0.chars() {
+                let command = keymap.command(&Key::Alt(letter));
+                assert_eq!(command, None, "`M-{letter}` of the {name} alphabet");
+            }
+        }
+
+        // The characters bound with `Alt` are not hints either.
+        let keymap = KeyMap::new(&[parse_key_binding("history=M-j").unwrap()]);
+        assert!(keymap.chars().any(|bound| bound == ('j', Command::History)));
+    }
+}
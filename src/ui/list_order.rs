@@ -0,0 +1,10 @@
+use clap::{Parser, ValueEnum};
+
+/// Describes how the rows of the list view are sorted.
+#[derive(Debug, Clone, ValueEnum, Parser)]
+pub enum ListOrder {
+    /// In order of appearance in the buffer.
+    Position,
+    /// By pattern name, then in order of appearance.
+    Pattern,
+}
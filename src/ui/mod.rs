@@ -15,15 +15,32 @@
 //! - move the focus from one span to another
 //! - select one of the available spans
 //! - toggle the output destination (tmux buffer or clipboard)
+//! - optionally choose an action to perform on the selected span, in the
+//!   action menu
 //!
 
+pub mod bell;
 pub mod colors;
+pub mod double_tap;
+pub mod expansion;
 pub mod hint_alignment;
 pub mod hint_style;
+pub mod input;
+pub mod keymap;
+pub mod list_order;
+pub mod render;
 mod selection;
+mod terminal;
 mod vc;
+pub mod wrap;
 
+pub use bell::BellEvent;
+pub use double_tap::{DoubleTap, DoubleTapAction};
 pub use hint_alignment::HintAlignment;
 pub use hint_style::HintStyle;
-pub use selection::Selection;
-pub use vc::ViewController;
+pub use input::KeyboardProtocol;
+pub use keymap::KeyMap;
+pub use list_order::ListOrder;
+pub use selection::{Action, Selection};
+pub use terminal::TerminalError;
+pub use vc::{present_refreshing, ViewController};
@@ -0,0 +1,287 @@
+//! Rendering of the spans grouped under their pattern name.
+
+use std::io;
+
+use termion::{clear, color, cursor, style};
+
+use super::{clip, max_hint_width, render_span_hint, render_span_text, Frame, Renderer};
+use crate::textbuf;
+
+/// Lists the spans under collapsible headings, one per pattern.
+pub struct Grouped {
+    groups: Vec<Group>,
+}
+
+impl Grouped {
+    pub fn new(spans: &[textbuf::Span]) -> Self {
+        Grouped {
+            groups: group_spans(spans),
+        }
+    }
+}
+
+impl Renderer for Grouped {
+    fn full_render(&self, stdout: &mut dyn io::Write, frame: &Frame) {
+        render_grouped(stdout, &self.groups, frame);
+    }
+
+    fn toggle_section(&mut self, focus_index: usize) -> bool {
+        if let Some(group) = self
+            .groups
+            .iter_mut()
+            .find(|group| group.span_indices.contains(&focus_index))
+        {
+            group.collapsed = !group.collapsed;
+        }
+        true
+    }
+
+    fn update_spans(&mut self, spans: &[textbuf::Span]) {
+        let mut groups = group_spans(spans);
+        for group in &mut groups {
+            group.collapsed = self
+                .groups
+                .iter()
+                .any(|old| old.pattern == group.pattern && old.collapsed);
+        }
+        self.groups = groups;
+    }
+}
+
+/// Column of the hints under the headings, in the grouped view.
+const HEADING_INDENT: usize = 2;
+
+/// Spans sharing the same pattern, in the grouped view.
+#[derive(Debug, PartialEq, Eq)]
+struct Group {
+    pattern: String,
+    /// Indices of the spans in the model.
+    span_indices: Vec<usize>,
+    /// Only the heading of a collapsed group is rendered.
+    collapsed: bool,
+}
+
+/// Group the `spans` by pattern, in order of first appearance.
+fn group_spans(spans: &[textbuf::Span]) -> Vec<Group> {
+    let mut groups: Vec<Group> = vec![];
+
+    for (index, span) in spans.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|group| group.pattern == span.pattern)
+        {
+            Some(group) => group.span_indices.push(index),
+            None => groups.push(Group {
+                pattern: span.pattern.to_string(),
+                span_indices: vec![index],
+                collapsed: false,
+            }),
+        }
+    }
+
+    groups
+}
+
+/// Render the spans as a list grouped under their pattern name, instead of
+/// on top of the buffer.
+///
+/// Each group starts with a heading showing the pattern name and the
+/// number of spans, followed by one row per span: its hint, then its
+/// text. The spans of collapsed groups are not listed; if the focused
+/// span is in a collapsed group, its heading is rendered as focused.
+///
+/// Rows below the bottom of the screen are not rendered, and texts wider
+/// than the screen are clipped.
+fn render_grouped(stdout: &mut dyn io::Write, groups: &[Group], frame: &Frame) {
+    let (term_width, term_height) = frame.screen_size;
+    let (spans, focus_index) = (&frame.model.spans, frame.focus_index);
+    let (colors, hint_style) = (frame.colors, frame.hint_style);
+
+    let text_x = HEADING_INDENT + max_hint_width(spans, hint_style) + 1;
+
+    write!(stdout, "{}", clear::All).unwrap();
+
+    let mut pos_y = 0;
+    for group in groups {
+        if pos_y >= term_height as usize {
+            break;
+        }
+
+        let has_focus = group.collapsed && group.span_indices.contains(&focus_index);
+        let (fg_color, bg_color) = if has_focus {
+            (colors.focused_fg, colors.focused_bg)
+        } else {
+            (colors.text_fg, colors.text_bg)
+        };
+        let marker = if group.collapsed { '▸' } else { '▾' };
+        let heading = format!(
+            "{marker} {pattern} ({count})",
+            pattern = group.pattern,
+            count = group.span_indices.len()
+        );
+        let limit = term_width as usize;
+        write!(
+            stdout,
+            "{goto}{bg_color}{fg_color}{bold}{heading}{reset}{fg_reset}{bg_reset}",
+            goto = cursor::Goto(1, pos_y as u16 + 1),
+            bg_color = color::Bg(bg_color),
+            fg_color = color::Fg(fg_color),
+            bold = style::Bold,
+            heading = clip(&heading, 0, limit, term_width),
+            reset = style::Reset,
+            fg_reset = color::Fg(color::Reset),
+            bg_reset = color::Bg(color::Reset),
+        )
+        .unwrap();
+        pos_y += 1;
+
+        if group.collapsed {
+            continue;
+        }
+
+        for &index in &group.span_indices {
+            if pos_y >= term_height as usize {
+                break;
+            }
+
+            let span = &spans[index];
+            render_span_hint(
+                stdout,
+                &span.hint,
+                frame.typed_hint,
+                (HEADING_INDENT, pos_y),
+                colors,
+                hint_style,
+            );
+            render_span_text(
+                stdout,
+                &frame.span_text(clip(span.text, text_x, limit, term_width), span.pattern),
+                span.pattern,
+                index == focus_index,
+                frame.is_recent(span),
+                frame.is_marked(index),
+                frame.is_selected(span),
+                (text_x, pos_y),
+                colors,
+            );
+            pos_y += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::basic;
+    use crate::textbuf::alphabet;
+    use clap::Parser;
+
+    #[test]
+    fn test_render_grouped() {
+        let lines = ["see https://a.io and /tmp/x", "or https://b.io"];
+        let config = basic::Config::parse_from(["copyrat", "-x", "url", "-x", "path"]);
+        let alphabet = alphabet::Alphabet("abcd".to_string());
+        let model = textbuf::Model::new(
+            &lines,
+            &alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+
+        let mut groups = group_spans(&model.spans);
+        let patterns: Vec<(&str, &[usize])> = groups
+            .iter()
+            .map(|group| (group.pattern.as_str(), group.span_indices.as_slice()))
+            .collect();
+        assert_eq!(patterns, [("url", &[0, 2][..]), ("path", &[1][..])]);
+
+        // Collapse the urls, which contain the focused span.
+        groups[0].collapsed = true;
+
+        let colors = &config.colors;
+        let mut writer = vec![];
+        let frame = Frame {
+            model: &model,
+            focus_index: 0,
+            screen_size: (80, 30),
+            colors,
+            hint_style: &None,
+            recent_from: None,
+            marked: &[],
+            selected: &[],
+            scroll: 0,
+            base_text: true,
+            reveal_secrets: false,
+            typed_hint: "",
+            styles: None,
+            expanded: None,
+        };
+        render_grouped(&mut writer, &groups, &frame);
+
+        let heading = |pos_y: u16, text: &str, fg, bg| {
+            format!(
+                "{goto}{bg}{fg}{bold}{text}{reset}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(1, pos_y),
+                bg = color::Bg(bg),
+                fg = color::Fg(fg),
+                bold = style::Bold,
+                reset = style::Reset,
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+        };
+        let expected = [
+            clear::All.to_string(),
+            heading(1, "▸ url (2)", colors.focused_fg, colors.focused_bg),
+            heading(2, "▾ path (1)", colors.text_fg, colors.text_bg),
+            format!(
+                "{goto}{hint_bg}{hint_fg}{hint}{fg_reset}{bg_reset}\
+                {goto_text}{span_bg}{span_fg}/tmp/x{fg_reset}{bg_reset}",
+                goto = cursor::Goto(3, 3),
+                goto_text = cursor::Goto(5, 3),
+                hint = model.spans[1].hint,
+                hint_fg = color::Fg(colors.hint_fg),
+                hint_bg = color::Bg(colors.hint_bg),
+                span_fg = color::Fg(colors.span_fg),
+                span_bg = color::Bg(colors.span_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            ),
+        ]
+        .concat();
+
+        assert_eq!(std::str::from_utf8(&writer).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_toggle_section() {
+        let lines = ["see https://a.io and /tmp/x"];
+        let config = basic::Config::parse_from(["copyrat", "-x", "url", "-x", "path"]);
+        let model = textbuf::Model::new(
+            &lines,
+            &config.alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+
+        let mut grouped = Grouped::new(&model.spans);
+        assert!(grouped.toggle_section(1));
+        assert!(!grouped.groups[0].collapsed);
+        assert!(grouped.groups[1].collapsed);
+
+        assert!(grouped.toggle_section(1));
+        assert!(!grouped.groups[1].collapsed);
+    }
+}
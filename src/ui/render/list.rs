@@ -0,0 +1,208 @@
+//! Rendering of the spans as a list, one per row.
+
+use std::io;
+
+use termion::{clear, color, cursor, style};
+
+use super::{clip, max_hint_width, render_span_hint, render_span_text, Frame, Renderer};
+use crate::textbuf;
+use crate::ui::{wrap, ListOrder};
+
+/// Lists the spans one per row, along with the line they were found on.
+pub struct List {
+    /// Indices of the spans in the model, in display order.
+    rows: Vec<usize>,
+    order: ListOrder,
+}
+
+impl List {
+    pub fn new(spans: &[textbuf::Span], order: &ListOrder) -> Self {
+        List {
+            rows: list_rows(spans, order),
+            order: order.clone(),
+        }
+    }
+}
+
+impl Renderer for List {
+    fn full_render(&self, stdout: &mut dyn io::Write, frame: &Frame) {
+        render_list(stdout, &self.rows, frame);
+    }
+
+    fn update_spans(&mut self, spans: &[textbuf::Span]) {
+        self.rows = list_rows(spans, &self.order);
+    }
+}
+
+/// Returns the indices of the `spans`, sorted for the list view.
+fn list_rows(spans: &[textbuf::Span], order: &ListOrder) -> Vec<usize> {
+    let mut rows: Vec<usize> = (0..spans.len()).collect();
+    match order {
+        ListOrder::Position => rows.sort_by_key(|&index| (spans[index].y, spans[index].x)),
+        ListOrder::Pattern => {
+            rows.sort_by_key(|&index| (spans[index].pattern, spans[index].y, spans[index].x))
+        }
+    }
+    rows
+}
+
+/// Render the spans as a list, one per row, instead of on top of the
+/// buffer.
+///
+/// Each row shows the span's hint, its text, and the (faint) line it was
+/// found on. The `rows` are the indices of the spans in the model, in
+/// display order.
+///
+/// Rows below the bottom of the screen are not rendered, and texts wider
+/// than their column are clipped.
+fn render_list(stdout: &mut dyn io::Write, rows: &[usize], frame: &Frame) {
+    let (term_width, term_height) = frame.screen_size;
+    let (model, spans, focus_index) = (frame.model, &frame.model.spans, frame.focus_index);
+    let (colors, hint_style) = (frame.colors, frame.hint_style);
+
+    // Columns: hints, then texts (at most a third of the screen), then
+    // source lines.
+    let hint_width = max_hint_width(spans, hint_style);
+    let text_x = hint_width + 1;
+    let text_width = spans
+        .iter()
+        .map(|span| wrap::str_width(span.text))
+        .max()
+        .unwrap_or(0)
+        .min(term_width as usize / 3);
+    let line_x = text_x + text_width + 2;
+    let limit = term_width as usize;
+
+    write!(stdout, "{}", clear::All).unwrap();
+
+    for (pos_y, &index) in rows.iter().enumerate().take(term_height as usize) {
+        let span = &spans[index];
+
+        render_span_hint(
+            stdout,
+            &span.hint,
+            frame.typed_hint,
+            (0, pos_y),
+            colors,
+            hint_style,
+        );
+        render_span_text(
+            stdout,
+            &frame.span_text(
+                clip(span.text, text_x, text_x + text_width, term_width),
+                span.pattern,
+            ),
+            span.pattern,
+            index == focus_index,
+            frame.is_recent(span),
+            frame.is_marked(index),
+            frame.is_selected(span),
+            (text_x, pos_y),
+            colors,
+        );
+
+        if line_x < limit {
+            let line = model.lines[span.y as usize].trim();
+            write!(
+                stdout,
+                "{goto}{bg_color}{fg_color}{faint}{line}{reset}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(line_x as u16 + 1, pos_y as u16 + 1),
+                bg_color = color::Bg(colors.text_bg),
+                fg_color = color::Fg(colors.text_fg),
+                faint = style::Faint,
+                line = clip(line, line_x, limit, term_width),
+                reset = style::Reset,
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::basic;
+    use crate::textbuf::alphabet;
+    use clap::Parser;
+
+    #[test]
+    fn test_render_list() {
+        let lines = ["see https://a.io and /tmp/x"];
+        let config = basic::Config::parse_from(["copyrat", "-x", "url", "-x", "path"]);
+        let alphabet = alphabet::Alphabet("abcd".to_string());
+        let model = textbuf::Model::new(
+            &lines,
+            &alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(list_rows(&model.spans, &ListOrder::Position), [0, 1]);
+        assert_eq!(list_rows(&model.spans, &ListOrder::Pattern), [1, 0]);
+
+        let colors = &config.colors;
+        let mut writer = vec![];
+        let rows = list_rows(&model.spans, &ListOrder::Pattern);
+        let frame = Frame {
+            model: &model,
+            focus_index: 0,
+            screen_size: (36, 30),
+            colors,
+            hint_style: &None,
+            recent_from: None,
+            marked: &[],
+            selected: &[],
+            scroll: 0,
+            base_text: true,
+            reveal_secrets: false,
+            typed_hint: "",
+            styles: None,
+            expanded: None,
+        };
+        render_list(&mut writer, &rows, &frame);
+
+        // The text column is at most a third of the screen wide.
+        let row = |pos_y: u16, span: &textbuf::Span, text: &str, focused: bool| {
+            let (span_fg, span_bg) = if focused {
+                (colors.focused_fg, colors.focused_bg)
+            } else {
+                (colors.span_fg, colors.span_bg)
+            };
+            format!(
+                "{goto_hint}{hint_bg}{hint_fg}{hint}{fg_reset}{bg_reset}\
+                {goto_text}{span_bg}{span_fg}{text}{fg_reset}{bg_reset}\
+                {goto_line}{text_bg}{text_fg}{faint}see https://a.io an…{reset}{fg_reset}{bg_reset}",
+                goto_hint = cursor::Goto(1, pos_y),
+                goto_text = cursor::Goto(3, pos_y),
+                goto_line = cursor::Goto(17, pos_y),
+                hint = span.hint,
+                hint_fg = color::Fg(colors.hint_fg),
+                hint_bg = color::Bg(colors.hint_bg),
+                span_fg = color::Fg(span_fg),
+                span_bg = color::Bg(span_bg),
+                text_fg = color::Fg(colors.text_fg),
+                text_bg = color::Bg(colors.text_bg),
+                faint = style::Faint,
+                reset = style::Reset,
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+        };
+        let expected = [
+            clear::All.to_string(),
+            row(1, &model.spans[1], "/tmp/x", false),
+            row(2, &model.spans[0], "https://a.io", true),
+        ]
+        .concat();
+
+        assert_eq!(std::str::from_utf8(&writer).unwrap(), expected);
+    }
+}
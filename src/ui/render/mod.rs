@@ -0,0 +1,781 @@
+//! Rendering strategies of the spans and their hints.
+//!
+//! The `ViewController` manages the focus and the keys, and delegates
+//! rendering to a `Renderer`, chosen from the configuration:
+//!
+//! - the `Overlay` highlights the spans on top of the buffer lines,
+//! - the `Grouped` view lists the spans under their pattern name,
+//! - the `List` view lists the spans one per row, along with their line.
+//!
+//! Renderers only write to the provided writer, which makes them testable
+//! without a terminal.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io;
+
+use termion::{color, cursor, style};
+
+use super::colors::UiColors;
+use super::{wrap, HintStyle};
+use crate::textbuf::{self, regexes};
+
+mod grouped;
+mod list;
+mod overlay;
+
+pub use grouped::Grouped;
+pub use list::List;
+pub use overlay::Overlay;
+
+/// State of the selection, shared by all renderers.
+pub struct Frame<'a> {
+    pub model: &'a textbuf::Model<'a>,
+    /// Index of the focused span in the model.
+    pub focus_index: usize,
+    /// Width and height of the screen.
+    pub screen_size: (u16, u16),
+    pub colors: &'a UiColors,
+    pub hint_style: &'a Option<HintStyle>,
+    /// Index of the first of the most recent lines, whose spans are rendered
+    /// with the `recent_*` colors.
+    pub recent_from: Option<usize>,
+    /// Indices of the spans marked in multi-select mode, which are rendered
+    /// underlined.
+    pub marked: &'a [usize],
+    /// Positions `(x, y)` of the spans already selected with `--loop` or
+    /// `--stay-open`, which are rendered with the `selected_*` colors.
+    pub selected: &'a [(i32, i32)],
+    /// Index of the content line displayed at the top of the screen, when
+    /// the content is taller than the screen.
+    pub scroll: usize,
+    /// Whether the `Overlay` renders the buffer lines below the spans on a
+    /// full render. With `--minimal-render`, they are only rendered again on
+    /// a cleared screen.
+    pub base_text: bool,
+    /// Whether the spans of the sensitive patterns are rendered unmasked, see
+    /// `--reveal-secrets`.
+    pub reveal_secrets: bool,
+    /// Keys of the hint typed so far: the hints not starting with them are
+    /// hidden, and their typed part is rendered faint.
+    pub typed_hint: &'a str,
+    /// SGR sequences of the buffer lines, rendered by the `Overlay` with
+    /// `--preserve-colors`.
+    pub styles: Option<&'a textbuf::Styles>,
+    /// Focused span grown to an expansion, rendered by the `Overlay` instead
+    /// of the focused span.
+    pub expanded: Option<&'a textbuf::Span<'a>>,
+}
+
+impl Frame<'_> {
+    /// Returns `true` if the `span` is located on one of the most recent
+    /// lines.
+    fn is_recent(&self, span: &textbuf::Span) -> bool {
+        self.recent_from
+            .is_some_and(|recent_from| span.y as usize >= recent_from)
+    }
+
+    /// Returns `true` if the span at `index` is marked in multi-select mode.
+    fn is_marked(&self, index: usize) -> bool {
+        self.marked.contains(&index)
+    }
+
+    /// Returns `true` if the `span` was already selected, see `selected`.
+    fn is_selected(&self, span: &textbuf::Span) -> bool {
+        self.selected.contains(&(span.x, span.y))
+    }
+
+    /// Returns the `text` of a span of the `pattern` as rendered: masked if
+    /// the pattern is sensitive, unless the secrets are revealed.
+    fn span_text<'t>(&self, text: Cow<'t, str>, pattern: &str) -> Cow<'t, str> {
+        if self.reveal_secrets || !regexes::is_sensitive(pattern) {
+            return text;
+        }
+        Cow::Owned(mask(&text))
+    }
+}
+
+/// Char replacing each char of the masked secrets.
+const SECRET_MASK: char = '*';
+
+/// Returns the `text` masked by as many `SECRET_MASK` chars.
+pub fn mask(text: &str) -> String {
+    text.chars().map(|_| SECRET_MASK).collect()
+}
+
+/// Returns the `lines` containing spans of the sensitive patterns, by index,
+/// with the text of these spans masked.
+pub fn mask_secrets(lines: &[&str], spans: &[textbuf::Span]) -> HashMap<usize, String> {
+    let mut secrets: Vec<&textbuf::Span> = spans
+        .iter()
+        .filter(|span| regexes::is_sensitive(span.pattern))
+        .collect();
+    // From the end of the lines, so that the offsets of the spans left are
+    // kept.
+    secrets.sort_by_key(|span| std::cmp::Reverse((span.y, span.x)));
+
+    let mut masked_lines = HashMap::new();
+    for span in secrets {
+        let (x, y) = (span.x as usize, span.y as usize);
+        let line = masked_lines
+            .entry(y)
+            .or_insert_with(|| lines[y].to_string());
+        line.replace_range(x..x + span.text.len(), &mask(span.text));
+    }
+
+    masked_lines
+}
+
+/// Returns the index of the first of the `count` last lines, not counting
+/// trailing blank lines.
+pub fn first_recent_line(lines: &[&str], count: usize) -> usize {
+    let len = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |index| index + 1);
+    len.saturating_sub(count)
+}
+
+/// Strategy to render the spans and their hints on screen.
+pub trait Renderer {
+    /// Render the whole screen on the provided writer.
+    fn full_render(&self, stdout: &mut dyn io::Write, frame: &Frame);
+
+    /// Render the screen after the focus moved away from the span at
+    /// `old_focus_index`.
+    ///
+    /// By default, the whole screen is rendered again.
+    fn diff_render(&self, stdout: &mut dyn io::Write, frame: &Frame, _old_focus_index: usize) {
+        self.full_render(stdout, frame);
+    }
+
+    /// Collapse or expand the section containing the span at `focus_index`.
+    ///
+    /// Returns `false` if the renderer has no sections.
+    fn toggle_section(&mut self, _focus_index: usize) -> bool {
+        false
+    }
+
+    /// Take into account new `spans`, such as the spans left by the filter.
+    ///
+    /// By default, nothing depends on the spans.
+    fn update_spans(&mut self, _spans: &[textbuf::Span]) {}
+
+    /// Returns the number of screen lines of the whole content, which can
+    /// exceed the screen height, see `Frame::scroll`.
+    ///
+    /// By default, the content cannot be scrolled.
+    fn content_height(&self) -> usize {
+        0
+    }
+
+    /// Returns the content line on which the span at `index` is displayed,
+    /// if the content can be scrolled.
+    fn span_line(&self, _frame: &Frame, _index: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// Render the Span's `text` field on provided writer using the `span_*g` color,
+/// or the `--pattern-color` of its `pattern`.
+///
+/// If a Mach is "focused", it is then rendered with the `focused_*g` colors.
+/// Otherwise, if it is "marked" or "selected", it is rendered with the
+/// `selected_*g` colors, and if it is "recent", with the `recent_*g` colors.
+/// A "marked" span is also underlined.
+///
+/// # Note
+///
+/// This writes directly on the writer, avoiding extra allocation.
+#[allow(clippy::too_many_arguments)] // one per rendering state
+fn render_span_text(
+    stdout: &mut dyn io::Write,
+    text: &str,
+    pattern: &str,
+    focused: bool,
+    recent: bool,
+    marked: bool,
+    selected: bool,
+    pos: (usize, usize),
+    colors: &UiColors,
+) {
+    // To help identify it, the span thas has focus is rendered with a dedicated color.
+    let (fg_color, bg_color) = if focused {
+        (colors.focused_fg, colors.focused_bg)
+    } else if marked || selected {
+        (colors.selected_fg, colors.selected_bg)
+    } else if recent {
+        (colors.recent_fg, colors.recent_bg)
+    } else {
+        colors.span_colors(pattern)
+    };
+
+    let (underline, no_underline): (&dyn Display, &dyn Display) = if marked {
+        (&style::Underline, &style::NoUnderline)
+    } else {
+        (&"", &"")
+    };
+
+    // Render just the Span's text on top of existing content.
+    write!(
+        stdout,
+        "{goto}{bg_color}{fg_color}{underline}{text}{no_underline}{fg_reset}{bg_reset}",
+        goto = cursor::Goto(pos.0 as u16 + 1, pos.1 as u16 + 1),
+        fg_color = color::Fg(fg_color),
+        bg_color = color::Bg(bg_color),
+        fg_reset = color::Fg(color::Reset),
+        bg_reset = color::Bg(color::Reset),
+        text = &text,
+    )
+    .unwrap();
+}
+
+/// Render a Span's `hint` field on the provided writer.
+///
+/// This renders the hint according to some provided style:
+/// - just colors
+/// - styled (bold, italic, underlined) with colors
+/// - surrounding the hint's text with some delimiters, see
+///   `HintStyle::Delimited`.
+///
+/// Once the first keys of a hint are `typed`, they are rendered faint, and
+/// only the remaining keys are styled. A hint not starting with the `typed`
+/// keys is not rendered.
+///
+/// # Note
+///
+/// This writes directly on the writer, avoiding extra allocation.
+fn render_span_hint(
+    stdout: &mut dyn io::Write,
+    hint_text: &str,
+    typed: &str,
+    pos: (usize, usize),
+    colors: &UiColors,
+    hint_style: &Option<HintStyle>,
+) {
+    let Some(hint_text) = hint_text.strip_prefix(typed) else {
+        return;
+    };
+
+    let fg_color = color::Fg(colors.hint_fg);
+    let bg_color = color::Bg(colors.hint_bg);
+    let fg_reset = color::Fg(color::Reset);
+    let bg_reset = color::Bg(color::Reset);
+
+    if !typed.is_empty() {
+        write!(
+            stdout,
+            "{goto}{bg_color}{fg_color}{faint}{typed}{reset}{fg_reset}{bg_reset}",
+            goto = cursor::Goto(pos.0 as u16 + 1, pos.1 as u16 + 1),
+            faint = style::Faint,
+            reset = style::Reset,
+        )
+        .unwrap();
+    }
+    let pos_x = pos.0 + typed.chars().count();
+    let goto = cursor::Goto(pos_x as u16 + 1, pos.1 as u16 + 1);
+
+    match hint_style {
+        None => {
+            write!(
+                stdout,
+                "{goto}{bg_color}{fg_color}{hint_text}{fg_reset}{bg_reset}",
+            )
+            .unwrap();
+        }
+        Some(hint_style) => match hint_style {
+            HintStyle::Bold => {
+                write!(
+                    stdout,
+                    "{goto}{bg_color}{fg_color}{sty}{hint}{sty_reset}{fg_reset}{bg_reset}",
+                    goto = goto,
+                    fg_color = fg_color,
+                    bg_color = bg_color,
+                    fg_reset = fg_reset,
+                    bg_reset = bg_reset,
+                    sty = style::Bold,
+                    sty_reset = style::Reset, // NoBold is not sufficient
+                    hint = hint_text,
+                )
+                .unwrap();
+            }
+            HintStyle::Italic => {
+                write!(
+                    stdout,
+                    "{goto}{bg_color}{fg_color}{sty}{hint}{sty_reset}{fg_reset}{bg_reset}",
+                    goto = goto,
+                    fg_color = fg_color,
+                    bg_color = bg_color,
+                    fg_reset = fg_reset,
+                    bg_reset = bg_reset,
+                    sty = style::Italic,
+                    sty_reset = style::NoItalic,
+                    hint = hint_text,
+                )
+                .unwrap();
+            }
+            HintStyle::Underline => {
+                write!(
+                    stdout,
+                    "{goto}{bg_color}{fg_color}{sty}{hint}{sty_reset}{fg_reset}{bg_reset}",
+                    goto = goto,
+                    fg_color = fg_color,
+                    bg_color = bg_color,
+                    fg_reset = fg_reset,
+                    bg_reset = bg_reset,
+                    sty = style::Underline,
+                    sty_reset = style::NoUnderline,
+                    hint = hint_text,
+                )
+                .unwrap();
+            }
+            HintStyle::Surround(opening, closing) => {
+                write!(
+                    stdout,
+                    "{goto}{bg_color}{fg_color}{opening}{hint_text}{closing}{fg_reset}{bg_reset}",
+                )
+                .unwrap();
+            }
+        },
+    }
+}
+
+/// Rendered in the last visible cell of spans clipped at the bottom of the
+/// screen.
+const CONTINUATION_MARKER: char = '…';
+
+/// Returns the `text` if it fits on screen when printed from the cell `start`
+/// (see `wrap::fit`), or its visible prefix followed by the
+/// `CONTINUATION_MARKER`.
+fn clip(text: &str, start: usize, limit: usize, term_width: u16) -> Cow<'_, str> {
+    if wrap::fit(text, start, limit, term_width) == text {
+        Cow::Borrowed(text)
+    } else {
+        let visible = wrap::fit(text, start, limit.saturating_sub(1), term_width);
+        Cow::Owned(format!("{visible}{CONTINUATION_MARKER}"))
+    }
+}
+
+/// Returns the width of the longest hint, including its surroundings, so that
+/// texts can be aligned after the hints.
+fn max_hint_width(spans: &[textbuf::Span], hint_style: &Option<HintStyle>) -> usize {
+    let surroundings = match hint_style {
+        Some(HintStyle::Surround(..)) => 2,
+        _ => 0,
+    };
+    spans.iter().map(|span| span.hint.len()).max().unwrap_or(0) + surroundings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::colors;
+
+    #[test]
+    fn test_render_focused_span_text() {
+        let mut writer = vec![];
+        let text = "https://en.wikipedia.org/wiki/Barcelona";
+        let focused = true;
+        let position: (usize, usize) = (3, 1);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        render_span_text(
+            &mut writer,
+            text,
+            "url",
+            focused,
+            false,
+            false,
+            false,
+            position,
+            &colors,
+        );
+
+        assert_eq!(
+            writer,
+            format!(
+                "{goto}{bg}{fg}{text}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(colors.focused_fg),
+                bg = color::Bg(colors.focused_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                text = &text,
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_render_span_text() {
+        let mut writer = vec![];
+        let text = "https://en.wikipedia.org/wiki/Barcelona";
+        let focused = false;
+        let position: (usize, usize) = (3, 1);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        render_span_text(
+            &mut writer,
+            text,
+            "url",
+            focused,
+            false,
+            false,
+            false,
+            position,
+            &colors,
+        );
+
+        assert_eq!(
+            writer,
+            format!(
+                "{goto}{bg}{fg}{text}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(colors.span_fg),
+                bg = color::Bg(colors.span_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                text = &text,
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_render_unstyled_span_hint() {
+        let mut writer = vec![];
+        let hint_text = "eo";
+        let position: (usize, usize) = (3, 1);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        let offset = 0;
+        let hint_style = None;
+
+        render_span_hint(
+            &mut writer,
+            hint_text,
+            "",
+            (position.0 + offset, position.1),
+            &colors,
+            &hint_style,
+        );
+
+        assert_eq!(
+            writer,
+            format!(
+                "{goto}{bg}{fg}{text}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(colors.hint_fg),
+                bg = color::Bg(colors.hint_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                text = "eo",
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_render_typed_span_hint() {
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+        let hint_style = Some(HintStyle::Surround('[', ']'));
+
+        // The typed key is rendered faint, and the remaining key surrounded.
+        let mut writer = vec![];
+        render_span_hint(&mut writer, "eo", "e", (3, 1), &colors, &hint_style);
+        assert_eq!(
+            writer,
+            format!(
+                "{goto_typed}{bg}{fg}{faint}e{reset}{fg_reset}{bg_reset}\
+                 {goto}{bg}{fg}[o]{fg_reset}{bg_reset}",
+                goto_typed = cursor::Goto(4, 2),
+                goto = cursor::Goto(5, 2),
+                fg = color::Fg(colors.hint_fg),
+                bg = color::Bg(colors.hint_bg),
+                faint = style::Faint,
+                reset = style::Reset,
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+            .as_bytes()
+        );
+
+        // A hint not starting with the typed key is hidden.
+        let mut writer = vec![];
+        render_span_hint(&mut writer, "ao", "e", (3, 1), &colors, &hint_style);
+        assert!(writer.is_empty());
+    }
+
+    #[test]
+    fn test_render_underlined_span_hint() {
+        let mut writer = vec![];
+        let hint_text = "eo";
+        let position: (usize, usize) = (3, 1);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        let offset = 0;
+        let hint_style = Some(HintStyle::Underline);
+
+        render_span_hint(
+            &mut writer,
+            hint_text,
+            "",
+            (position.0 + offset, position.1),
+            &colors,
+            &hint_style,
+        );
+
+        assert_eq!(
+            writer,
+            format!(
+                "{goto}{bg}{fg}{sty}{text}{sty_reset}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(colors.hint_fg),
+                bg = color::Bg(colors.hint_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                sty = style::Underline,
+                sty_reset = style::NoUnderline,
+                text = "eo",
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_render_bracketed_span_hint() {
+        let mut writer = vec![];
+        let hint_text = "eo";
+        let position: (usize, usize) = (3, 1);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        let offset = 0;
+        let hint_style = Some(HintStyle::Surround('{', '}'));
+
+        render_span_hint(
+            &mut writer,
+            hint_text,
+            "",
+            (position.0 + offset, position.1),
+            &colors,
+            &hint_style,
+        );
+
+        assert_eq!(
+            writer,
+            format!(
+                "{goto}{bg}{fg}{bra}{text}{bra_close}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(colors.hint_fg),
+                bg = color::Bg(colors.hint_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                bra = '{',
+                bra_close = '}',
+                text = "eo",
+            )
+            .as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_render_recent_span_text() {
+        let mut writer = vec![];
+        let text = "https://en.wikipedia.org/wiki/Barcelona";
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        // Recent spans are rendered with the recent colors, unless focused.
+        render_span_text(
+            &mut writer,
+            text,
+            "url",
+            false,
+            true,
+            false,
+            false,
+            (3, 1),
+            &colors,
+        );
+        render_span_text(
+            &mut writer,
+            text,
+            "url",
+            true,
+            true,
+            false,
+            false,
+            (3, 1),
+            &colors,
+        );
+
+        let expected = |fg, bg| {
+            format!(
+                "{goto}{bg}{fg}{text}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(4, 2),
+                fg = color::Fg(fg),
+                bg = color::Bg(bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+        };
+        assert_eq!(
+            std::str::from_utf8(&writer).unwrap(),
+            expected(colors.recent_fg, colors.recent_bg)
+                + &expected(colors.focused_fg, colors.focused_bg)
+        );
+    }
+
+    #[test]
+    fn test_render_marked_span_text() {
+        let mut writer = vec![];
+        let text = "127.0.0.1";
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        render_span_text(
+            &mut writer,
+            text,
+            "url",
+            false,
+            false,
+            true,
+            false,
+            (3, 1),
+            &colors,
+        );
+
+        let expected = format!(
+            "{goto}{bg}{fg}{underline}{text}{no_underline}{fg_reset}{bg_reset}",
+            goto = cursor::Goto(4, 2),
+            fg = color::Fg(colors.selected_fg),
+            bg = color::Bg(colors.selected_bg),
+            underline = style::Underline,
+            no_underline = style::NoUnderline,
+            fg_reset = color::Fg(color::Reset),
+            bg_reset = color::Bg(color::Reset),
+        );
+        assert_eq!(std::str::from_utf8(&writer).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_first_recent_line() {
+        let lines = ["a", "b", "c", "d", "  ", ""];
+
+        assert_eq!(first_recent_line(&lines, 2), 2);
+        assert_eq!(first_recent_line(&lines, 10), 0);
+        assert_eq!(first_recent_line(&lines, 0), 4);
+        assert_eq!(first_recent_line(&["", ""], 1), 0);
+    }
+}
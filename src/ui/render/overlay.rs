@@ -0,0 +1,750 @@
+//! Rendering of the spans on top of the buffer lines.
+
+use std::collections::HashMap;
+use std::io;
+
+use termion::{color, cursor, style};
+
+use super::{clip, mask_secrets, render_span_hint, render_span_text, Frame, Renderer};
+use crate::textbuf;
+use crate::ui::colors::UiColors;
+use crate::ui::wrap::{self, compute_wrapped_lines, WrappedLine};
+use crate::ui::{HintAlignment, HintStyle};
+
+/// Renders the buffer lines, then highlights the spans and their hints in
+/// place.
+pub struct Overlay<'a> {
+    term_width: u16,
+    wrapped_lines: Vec<WrappedLine>,
+    hint_alignment: &'a HintAlignment,
+    /// Style of the leading hints which coincide with the start of their
+    /// span's text, such as `ab` on `abc`, if any.
+    colliding_hint_style: Option<HintStyle>,
+    /// Lines rendered instead of the buffer lines, by line index, where the
+    /// secrets are masked.
+    masked_lines: HashMap<usize, String>,
+    /// Render the number of spans sharing a hint, see `--count-badges`.
+    count_badges: bool,
+}
+
+impl<'a> Overlay<'a> {
+    /// Prepare rendering the `lines` in a terminal of width `term_width`.
+    pub fn new(
+        lines: &[&str],
+        term_width: u16,
+        hint_alignment: &'a HintAlignment,
+        colliding_hint_style: Option<HintStyle>,
+    ) -> Self {
+        Overlay {
+            term_width,
+            wrapped_lines: compute_wrapped_lines(lines, term_width),
+            hint_alignment,
+            colliding_hint_style,
+            masked_lines: HashMap::new(),
+            count_badges: false,
+        }
+    }
+
+    /// Render a badge such as `×3` next to the first of the spans sharing a
+    /// hint, which happens with `--unique-hint`.
+    pub fn with_count_badges(mut self) -> Self {
+        self.count_badges = true;
+        self
+    }
+
+    /// Mask the text of the `spans` of the sensitive patterns in the rendered
+    /// `lines`, including the spans hidden by a filter.
+    pub fn with_masked_secrets(mut self, lines: &[&str], spans: &[textbuf::Span]) -> Self {
+        self.masked_lines = mask_secrets(lines, spans);
+        self
+    }
+
+    /// Convert the `Span` text into the coordinates of the wrapped lines.
+    ///
+    /// Compute the new x position of the text as the remainder of the line width
+    /// (e.g. the Span could start at position 120 in a 80-width terminal, the new
+    /// position being 40).
+    ///
+    /// Compute the new y position of the text as the initial y position plus any
+    /// additional offset due to previous split lines. This is obtained thanks to
+    /// the `wrapped_lines` field.
+    fn map_coords_to_wrapped_space(&self, pos_x: usize, pos_y: usize) -> (usize, usize) {
+        let line_width = self.term_width as usize;
+
+        let new_pos_x = pos_x % line_width;
+        let new_pos_y = self.wrapped_lines[pos_y].pos_y + pos_x / line_width;
+
+        (new_pos_x, new_pos_y)
+    }
+
+    /// Render entire model lines on provided writer.
+    ///
+    /// This renders the basic content on which spans and hints can be rendered.
+    ///
+    /// # Notes
+    /// - All trailing whitespaces are trimmed, empty lines are skipped.
+    /// - Only the screen lines from `scroll` are rendered. Lines are clipped
+    ///   at the bottom of the screen, so that the terminal does not scroll.
+    /// - This writes directly on the writer, avoiding extra allocation.
+    /// - With `--preserve-colors`, the lines are rendered with their `styles`
+    ///   instead of the text colors, which are then restored.
+    fn render_base_text(
+        stdout: &mut dyn io::Write,
+        lines: &[&str],
+        styles: Option<&textbuf::Styles>,
+        wrapped_lines: &[WrappedLine],
+        screen_size: (u16, u16),
+        scroll: usize,
+        colors: &UiColors,
+    ) {
+        let (term_width, term_height) = screen_size;
+
+        write!(
+            stdout,
+            "{bg_color}{fg_color}",
+            fg_color = color::Fg(colors.text_fg),
+            bg_color = color::Bg(colors.text_bg),
+        )
+        .unwrap();
+
+        for (line_index, line) in lines.iter().enumerate() {
+            let mut trimmed_line = line.trim_end();
+
+            let WrappedLine { pos_y, size, .. } = wrapped_lines[line_index];
+
+            if pos_y + size <= scroll {
+                continue;
+            }
+            // Skip the screen lines of a wrapped line above the top of the
+            // screen.
+            let hidden_rows = scroll.saturating_sub(pos_y);
+            let mut start = 0;
+            if hidden_rows > 0 {
+                let hidden_cells = hidden_rows * term_width as usize;
+                start = wrap::fit(trimmed_line, 0, hidden_cells, term_width).len();
+                trimmed_line = &trimmed_line[start..];
+            }
+            let pos_y = pos_y + hidden_rows - scroll;
+
+            if pos_y >= term_height as usize {
+                break;
+            }
+
+            if !trimmed_line.is_empty() {
+                let limit = (term_height as usize - pos_y) * term_width as usize;
+                let text = wrap::fit(trimmed_line, 0, limit, term_width);
+                let goto = cursor::Goto(1, pos_y as u16 + 1);
+
+                match styles.filter(|styles| styles.is_styled(line_index)) {
+                    Some(styles) => write!(
+                        stdout,
+                        "{goto}{text}{reset}{bg_color}{fg_color}",
+                        text = styles.apply(line_index, line, start..start + text.len()),
+                        reset = style::Reset,
+                        fg_color = color::Fg(colors.text_fg),
+                        bg_color = color::Bg(colors.text_bg),
+                    ),
+                    None => write!(stdout, "{goto}{text}"),
+                }
+                .unwrap();
+            }
+        }
+
+        write!(
+            stdout,
+            "{fg_reset}{bg_reset}",
+            fg_reset = color::Fg(color::Reset),
+            bg_reset = color::Bg(color::Reset),
+        )
+        .unwrap();
+    }
+
+    /// Render the number of spans sharing a hint, such as `×3`, next to the
+    /// first of them which is on screen.
+    ///
+    /// The badge is rendered right after the span if it fits on its last
+    /// screen line, otherwise right before the span, otherwise not at all.
+    fn render_count_badges(&self, stdout: &mut dyn io::Write, frame: &Frame) {
+        let spans = &frame.model.spans;
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for span in spans {
+            *counts.entry(span.hint.as_str()).or_default() += 1;
+        }
+
+        let (term_width, term_height) = frame.screen_size;
+        let on_screen =
+            |pos_y: usize| pos_y >= frame.scroll && pos_y - frame.scroll < term_height as usize;
+
+        for span in spans {
+            let Some(count) = counts.get(span.hint.as_str()).copied() else {
+                continue;
+            };
+            if count < 2 {
+                continue;
+            }
+
+            let badge = format!("{COUNT_BADGE_PREFIX}{count}");
+            let badge_width = badge.chars().count();
+
+            let (pos_x, pos_y) = adjusted_span_position(frame.model.lines, span, self.term_width);
+            let (start_x, start_y) = self.map_coords_to_wrapped_space(pos_x, pos_y);
+            let line = frame.model.lines[span.y as usize];
+            let end = wrap::cell_at(line, span.x as usize + span.text.len(), term_width);
+            let (end_x, end_y) = self.map_coords_to_wrapped_space(end, pos_y);
+
+            let position = if end_x > 0 && end_x + badge_width <= term_width as usize {
+                Some((end_x, end_y))
+            } else if start_x >= badge_width {
+                Some((start_x - badge_width, start_y))
+            } else {
+                None
+            };
+            let Some((badge_x, badge_y)) = position.filter(|&(_, pos_y)| on_screen(pos_y)) else {
+                continue;
+            };
+
+            // Only the first span on screen gets the badge.
+            counts.remove(span.hint.as_str());
+
+            write!(
+                stdout,
+                "{goto}{bg_color}{fg_color}{badge}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(badge_x as u16 + 1, (badge_y - frame.scroll) as u16 + 1),
+                fg_color = color::Fg(frame.colors.hint_fg),
+                bg_color = color::Bg(frame.colors.hint_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+            .unwrap();
+        }
+    }
+
+    /// Convenience function that renders both the text span and its hint,
+    /// if focused.
+    ///
+    /// Spans starting above the top or below the bottom of the screen are not
+    /// rendered. Spans extending below the bottom of the screen are clipped,
+    /// their last visible cell showing the `CONTINUATION_MARKER`, and their
+    /// hint is always rendered at their start.
+    fn render_span(&self, stdout: &mut dyn io::Write, frame: &Frame, index: usize) {
+        let focused = index == frame.focus_index;
+        let span = match frame.expanded {
+            Some(expanded) if focused => expanded,
+            _ => &frame.model.spans[index],
+        };
+
+        let (pos_x, pos_y) = adjusted_span_position(frame.model.lines, span, self.term_width);
+        let (pos_x, pos_y) = self.map_coords_to_wrapped_space(pos_x, pos_y);
+
+        let (term_width, term_height) = frame.screen_size;
+        if pos_y < frame.scroll || pos_y - frame.scroll >= term_height as usize {
+            return;
+        }
+        let pos_y = pos_y - frame.scroll;
+
+        // Number of cells available from the start of the span's screen line
+        // to the bottom of the screen.
+        let limit = (term_height as usize - pos_y) * term_width as usize;
+
+        let text = clip(span.text, pos_x, limit, term_width);
+        let clipped = text != span.text;
+        let text = frame.span_text(text, span.pattern);
+
+        render_span_text(
+            stdout,
+            &text,
+            span.pattern,
+            focused,
+            frame.is_recent(span),
+            frame.is_marked(index),
+            frame.is_selected(span),
+            (pos_x, pos_y),
+            frame.colors,
+        );
+
+        if !focused {
+            // If not focused, render the hint (e.g. "eo") as an overlay on
+            // top of the rendered text span, aligned at its leading or the
+            // trailing edge.
+            let offset = match self.hint_alignment {
+                HintAlignment::Trailing if !clipped => {
+                    wrap::str_width(&text).saturating_sub(span.hint.len())
+                }
+                _ => 0,
+            };
+
+            // Otherwise the hint could be mistaken for the start of the text.
+            let collides = matches!(self.hint_alignment, HintAlignment::Leading)
+                && text.starts_with(span.hint.as_str());
+            let hint_style = if collides && self.colliding_hint_style.is_some() {
+                &self.colliding_hint_style
+            } else {
+                frame.hint_style
+            };
+
+            render_span_hint(
+                stdout,
+                wrap::fit(&span.hint, pos_x + offset, limit, term_width),
+                frame.typed_hint,
+                (pos_x + offset, pos_y),
+                frame.colors,
+                hint_style,
+            );
+        }
+    }
+}
+
+impl Renderer for Overlay<'_> {
+    /// This renders in 3 phases:
+    /// - all lines are rendered verbatim
+    /// - each Span's `text` is rendered as an overlay on top of it
+    /// - each Span's `hint` text is rendered as a final overlay
+    ///
+    /// Depending on the value of `self.hint_alignment`, the hint can be
+    /// rendered on the leading edge of the underlying Span's `text`, or on
+    /// the trailing edge.
+    ///
+    /// # Note
+    ///
+    /// Multibyte characters are taken into account, so that the Span's `text`
+    /// and `hint` are rendered in their proper position.
+    fn full_render(&self, stdout: &mut dyn io::Write, frame: &Frame) {
+        // 1. Trim all lines and render non-empty ones.
+        if frame.base_text {
+            let lines: Vec<&str> = frame
+                .model
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(index, &line)| self.masked_lines.get(&index).map_or(line, String::as_str))
+                .collect();
+            Overlay::render_base_text(
+                stdout,
+                &lines,
+                frame.styles,
+                &self.wrapped_lines,
+                frame.screen_size,
+                frame.scroll,
+                frame.colors,
+            );
+        }
+
+        for index in 0..frame.model.spans.len() {
+            self.render_span(stdout, frame, index);
+        }
+
+        if self.count_badges {
+            self.render_count_badges(stdout, frame);
+        }
+    }
+
+    /// Render the previous span with its hint, and render the newly focused
+    /// span without its hint. This is more efficient than a full render.
+    fn diff_render(&self, stdout: &mut dyn io::Write, frame: &Frame, old_focus_index: usize) {
+        // Render the previously focused span as non-focused
+        self.render_span(stdout, frame, old_focus_index);
+
+        // Render the newly focused span as focused
+        self.render_span(stdout, frame, frame.focus_index);
+    }
+
+    fn content_height(&self) -> usize {
+        self.wrapped_lines
+            .last()
+            .map_or(0, |line| line.pos_y + line.size)
+    }
+
+    fn span_line(&self, frame: &Frame, index: usize) -> Option<usize> {
+        let span = &frame.model.spans[index];
+        let (pos_x, pos_y) = adjusted_span_position(frame.model.lines, span, self.term_width);
+        Some(self.map_coords_to_wrapped_space(pos_x, pos_y).1)
+    }
+}
+
+/// Prefix of the count badges, see `Overlay::with_count_badges`.
+const COUNT_BADGE_PREFIX: char = '×';
+
+/// Returns the adjusted position of a given `Span` within the buffer
+/// line, in a terminal of width `term_width`.
+///
+/// This adjustment is necessary because the span's `x` is a byte offset,
+/// while the chars before the span (in the "prefix") take as many cells as
+/// their display width: multibyte chars can compound and take less space on
+/// screen when printed, for instance ´ + e = é, while CJK chars and emoji
+/// take two cells. Wide chars not fitting at the end of a screen line are
+/// moved to the next one, see `wrap::cell_at`.
+///
+/// This computation must happen before mapping the span position to the
+/// wrapped screen space.
+fn adjusted_span_position(lines: &[&str], span: &textbuf::Span, term_width: u16) -> (usize, usize) {
+    let pos_x = wrap::cell_at(lines[span.y as usize], span.x as usize, term_width);
+    let pos_y = span.y as usize;
+
+    (pos_x, pos_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::basic;
+    use crate::engine;
+    use crate::ui::colors;
+    use clap::Parser;
+
+    #[test]
+    fn test_render_all_lines() {
+        let content = "some text
+* e006b06 - (12 days ago) swapper: Make quotes
+path: /usr/local/bin/git
+
+
+path: /usr/local/bin/cargo";
+        let lines: Vec<&str> = content.split('\n').collect();
+        let wrapped_lines: Vec<WrappedLine> = vec![
+            WrappedLine {
+                pos_y: 0,
+                size: 1,
+                width: 0,
+            },
+            WrappedLine {
+                pos_y: 1,
+                size: 1,
+                width: 0,
+            },
+            WrappedLine {
+                pos_y: 2,
+                size: 1,
+                width: 0,
+            },
+            WrappedLine {
+                pos_y: 3,
+                size: 1,
+                width: 0,
+            },
+            WrappedLine {
+                pos_y: 4,
+                size: 1,
+                width: 0,
+            },
+            WrappedLine {
+                pos_y: 5,
+                size: 1,
+                width: 0,
+            },
+        ];
+
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        let mut writer = vec![];
+        Overlay::render_base_text(
+            &mut writer,
+            &lines,
+            None,
+            &wrapped_lines,
+            (80, 30),
+            0,
+            &colors,
+        );
+
+        let goto1 = cursor::Goto(1, 1);
+        let goto2 = cursor::Goto(1, 2);
+        let goto3 = cursor::Goto(1, 3);
+        let goto6 = cursor::Goto(1, 6);
+        assert_eq!(
+            writer,
+            format!(
+                "{bg}{fg}{g1}some text{g2}* e006b06 - (12 days ago) swapper: Make quotes{g3}path: /usr/local/bin/git{g6}path: /usr/local/bin/cargo{fg_reset}{bg_reset}",
+                g1 = goto1, g2 = goto2, g3 = goto3, g6 = goto6,
+                fg = color::Fg(colors.text_fg),
+                bg = color::Bg(colors.text_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+                )
+            .as_bytes()
+            );
+    }
+
+    /// Generates pseudo-random buffers mixing ASCII, wide chars, combining
+    /// marks and tabs, and checks every span is mapped inside the screen area
+    /// computed for its line.
+    #[test]
+    fn test_render_styled_base_text() {
+        let (content, styles) = textbuf::Styles::parse("\x1b[31mred\x1b[0m and plain text\nok");
+        let lines: Vec<&str> = content.split('\n').collect();
+        let wrapped_lines = compute_wrapped_lines(&lines, 10);
+        let colors = UiColors {
+            text_fg: colors::BLACK,
+            text_bg: colors::WHITE,
+            focused_fg: colors::RED,
+            focused_bg: colors::BLUE,
+            selected_fg: colors::BRIGHTGREEN,
+            selected_bg: colors::BLACK,
+            span_fg: colors::GREEN,
+            span_bg: colors::MAGENTA,
+            recent_fg: colors::BRIGHTBLUE,
+            recent_bg: colors::RESET,
+            hint_fg: colors::YELLOW,
+            hint_bg: colors::CYAN,
+            pattern_colors: vec![],
+        };
+
+        // The first screen line is scrolled past: the sequences before the
+        // rendered text are still in effect.
+        let mut writer = vec![];
+        Overlay::render_base_text(
+            &mut writer,
+            &lines,
+            Some(&styles),
+            &wrapped_lines,
+            (10, 30),
+            1,
+            &colors,
+        );
+
+        let (bg, fg) = (color::Bg(colors.text_bg), color::Fg(colors.text_fg));
+        assert_eq!(
+            String::from_utf8(writer).unwrap(),
+            format!(
+                "{bg}{fg}{g1}\x1b[31m\x1b[0main text{reset}{bg}{fg}{g2}ok{fg_reset}{bg_reset}",
+                g1 = cursor::Goto(1, 1),
+                g2 = cursor::Goto(1, 2),
+                reset = style::Reset,
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+        );
+    }
+
+    #[test]
+    fn test_spans_map_inside_wrapped_area() {
+        let tokens = [
+            "lorem", " ", "1234567", "中文", "🦀", "e\u{301}", "\t", "/usr/bin", "ｶﾀ",
+        ];
+        let mut seed: u64 = 0x2486;
+        let mut next = move |bound: usize| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (seed >> 33) as usize % bound
+        };
+
+        let config = basic::Config::parse_from(["copyrat", "-x", "digits", "-x", "path"]);
+
+        for term_width in [4u16, 7, 10, 33, 80] {
+            let content: Vec<String> = (0..100)
+                .map(|_| (0..next(40)).map(|_| tokens[next(tokens.len())]).collect())
+                .collect();
+            let lines: Vec<&str> = content.iter().map(|line| line.as_str()).collect();
+
+            let model = textbuf::Model::new(
+                &lines,
+                &config.alphabet,
+                config.use_all_patterns,
+                &config.named_patterns,
+                &config.custom_patterns,
+                config.reverse,
+                config.unique_hint,
+                None,
+                &[],
+            )
+            .unwrap();
+            assert!(!model.spans.is_empty());
+
+            let overlay = Overlay::new(model.lines, term_width, &config.hint_alignment, None);
+
+            for span in &model.spans {
+                let (pos_x, pos_y) = adjusted_span_position(model.lines, span, term_width);
+                let (pos_x, pos_y) = overlay.map_coords_to_wrapped_space(pos_x, pos_y);
+                let area = &overlay.wrapped_lines[span.y as usize];
+
+                assert!(pos_x < term_width as usize, "{span:?} at column {pos_x}");
+                assert!(
+                    area.pos_y <= pos_y && pos_y < area.pos_y + area.size,
+                    "{span:?} at line {pos_y} outside {area:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_spans_after_wide_chars() {
+        let config = basic::Config::parse_from(["copyrat", "-x", "path"]);
+        let lines = ["日本語 /usr/bin", "🦀 /tmp/a", "012345678中 /tmp/b"];
+        let model = engine::model(&lines, &config).unwrap();
+        let overlay = Overlay::new(model.lines, 10, &config.hint_alignment, None);
+
+        let positions: Vec<(usize, usize)> = model
+            .spans
+            .iter()
+            .map(|span| adjusted_span_position(model.lines, span, 10))
+            .map(|(pos_x, pos_y)| overlay.map_coords_to_wrapped_space(pos_x, pos_y))
+            .collect();
+
+        // The wide char not fitting on the 10th column is moved to the next
+        // screen line, along with the span.
+        assert_eq!(positions, [(7, 0), (3, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn test_surround_colliding_hints() {
+        let config = basic::Config::try_parse_from([
+            "copyrat",
+            "-X",
+            r"(\w+)",
+            "--surround-colliding-hints",
+        ])
+        .unwrap();
+        let lines = ["abc xyz"];
+
+        let mut model = textbuf::Model::new(
+            &lines,
+            &config.alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+        model.spans[0].hint = "ab".to_string();
+        model.spans[1].hint = "ab".to_string();
+
+        let overlay = Overlay::new(
+            model.lines,
+            80,
+            &config.hint_alignment,
+            config.colliding_hint_style(),
+        );
+        let frame = Frame {
+            model: &model,
+            focus_index: 2,
+            screen_size: (80, 30),
+            colors: &config.colors,
+            hint_style: &None,
+            recent_from: None,
+            marked: &[],
+            selected: &[],
+            scroll: 0,
+            base_text: true,
+            reveal_secrets: false,
+            typed_hint: "",
+            styles: None,
+            expanded: None,
+        };
+
+        let mut writer = vec![];
+        overlay.render_span(&mut writer, &frame, 0);
+        overlay.render_span(&mut writer, &frame, 1);
+        let output = String::from_utf8(writer).unwrap();
+
+        // Only the hint of `abc` is surrounded.
+        assert_eq!(output.matches("{ab}").count(), 1);
+        assert_eq!(output.matches("ab").count(), 3);
+    }
+
+    #[test]
+    fn test_render_count_badges() {
+        let config = basic::Config::parse_from(["copyrat", "-x", "sha", "--unique-hint"]);
+        let lines = [
+            "e006b06 and e006b06",
+            "abcdef01 then e006b06",
+            "end e006b06",
+        ];
+        let model = textbuf::Model::new(
+            &lines,
+            &config.alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+        let frame = |screen_size| Frame {
+            model: &model,
+            focus_index: 0,
+            screen_size,
+            colors: &config.colors,
+            hint_style: &None,
+            recent_from: None,
+            marked: &[],
+            selected: &[],
+            scroll: 0,
+            base_text: false,
+            reveal_secrets: false,
+            typed_hint: "",
+            styles: None,
+            expanded: None,
+        };
+        let badges = |overlay: &Overlay, screen_size| {
+            let mut writer = vec![];
+            overlay.render_count_badges(&mut writer, &frame(screen_size));
+            String::from_utf8(writer).unwrap()
+        };
+        let badge = |x, y, text| {
+            format!(
+                "{goto}{bg}{fg}{text}{fg_reset}{bg_reset}",
+                goto = cursor::Goto(x, y),
+                fg = color::Fg(config.colors.hint_fg),
+                bg = color::Bg(config.colors.hint_bg),
+                fg_reset = color::Fg(color::Reset),
+                bg_reset = color::Bg(color::Reset),
+            )
+        };
+
+        // Right after the first span, once per hint.
+        let overlay = Overlay::new(model.lines, 80, &config.hint_alignment, None);
+        assert_eq!(badges(&overlay, (80, 30)), badge(8, 1, "×4"));
+
+        // The first span fills its screen line, so the badge goes next to the
+        // second span, which wraps to the third screen line.
+        let overlay = Overlay::new(model.lines, 7, &config.hint_alignment, None);
+        assert_eq!(badges(&overlay, (7, 30)), badge(6, 3, "×4"));
+
+        // Not rendered without `--count-badges` in the renderer.
+        let mut writer = vec![];
+        overlay.full_render(&mut writer, &frame((7, 30)));
+        assert!(!String::from_utf8(writer).unwrap().contains('×'));
+
+        // Right before the span when it ends at the right edge.
+        let lines = ["see e006b06", "e006b06"];
+        let model = textbuf::Model::new(
+            &lines,
+            &config.alphabet,
+            config.use_all_patterns,
+            &config.named_patterns,
+            &config.custom_patterns,
+            config.reverse,
+            config.unique_hint,
+            None,
+            &[],
+        )
+        .unwrap();
+        let overlay = Overlay::new(model.lines, 11, &config.hint_alignment, None);
+        let mut writer = vec![];
+        overlay.render_count_badges(
+            &mut writer,
+            &Frame {
+                model: &model,
+                ..frame((11, 30))
+            },
+        );
+        assert_eq!(String::from_utf8(writer).unwrap(), badge(3, 1, "×2"));
+    }
+}
@@ -1,9 +1,135 @@
 use crate::config::extended::OutputDestination;
+use crate::json;
+use crate::textbuf::Span;
 
-/// Represents the text selected by the user, along with if it was uppercased
-/// and the output destination (Tmux buffer or Clipboard).
+/// Represents the text selected by the user, along with the name of the
+/// pattern which matched it, if it was uppercased and the output destination
+/// (Tmux buffer or Clipboard).
 pub struct Selection {
+    /// Text to copy or paste: the texts of the selected spans, joined by the
+    /// multi-select separator.
     pub text: String,
+    /// Texts of the selected spans, several of them in multi-select mode.
+    pub texts: Vec<String>,
+    /// Name of the pattern, or the distinct names separated by commas if
+    /// several spans were selected.
+    pub pattern: String,
+    /// Line and column of the first selected span, counted from 1. The
+    /// column counts characters.
+    pub position: (usize, usize),
+    /// Hint of the first selected span.
+    pub hint: String,
     pub uppercased: bool,
     pub output_destination: OutputDestination,
+    /// Action chosen in the action menu instead of copying the text.
+    pub action: Option<Action>,
+}
+
+impl Selection {
+    /// Returns the selection of the `span` alone, found in the `lines`, whose
+    /// text to copy is `text`.
+    pub fn from_span(lines: &[&str], span: &Span, text: String) -> Selection {
+        Selection {
+            texts: vec![text.clone()],
+            text,
+            pattern: span.pattern.to_string(),
+            position: span_position(lines, span),
+            hint: span.hint.clone(),
+            uppercased: false,
+            output_destination: OutputDestination::Tmux,
+            action: None,
+        }
+    }
+
+    /// Returns the selection of the whole line of the `span`, found in the
+    /// `lines`, without its trailing whitespace.
+    pub fn from_line(lines: &[&str], span: &Span) -> Selection {
+        let text = lines[span.y as usize].trim_end().to_string();
+        Selection {
+            position: (span.y as usize + 1, 1),
+            ..Selection::from_span(lines, span, text)
+        }
+    }
+
+    /// Returns the selection as a JSON object, see `--output-format json`.
+    ///
+    /// The `x` and `y` coordinates of the first selected span are counted
+    /// from 0, unlike the `position`.
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"text":{},"pattern":{},"x":{},"y":{},"hint":{}}}"#,
+            json::string(Some(&self.text)),
+            json::string(Some(&self.pattern)),
+            self.position.1 - 1,
+            self.position.0 - 1,
+            json::string(Some(&self.hint)),
+        )
+    }
+}
+
+/// Returns the line and column of the `span` found in the `lines`, counted
+/// from 1. The column counts characters.
+pub fn span_position(lines: &[&str], span: &Span) -> (usize, usize) {
+    let line = lines[span.y as usize];
+    let column = line[..span.x as usize].chars().count();
+    (span.y as usize + 1, column + 1)
+}
+
+/// Describes what to do with the selected text, other than copying (or
+/// pasting) it. Except `Pattern`, these are offered by the action menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Open the text with the system opener (URLs, files).
+    Open,
+    /// Edit the text (a path) with `$EDITOR`.
+    Edit,
+    /// Run the user-provided command on the text.
+    Run,
+    /// Run the command configured for the pattern of the text, see
+    /// `--action`.
+    Pattern,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_to_json() {
+        let selection = Selection {
+            text: "a \"b\"".to_string(),
+            texts: vec!["a \"b\"".to_string()],
+            pattern: "quoted-double".to_string(),
+            position: (2, 5),
+            hint: "sa".to_string(),
+            uppercased: false,
+            output_destination: OutputDestination::Tmux,
+            action: None,
+        };
+
+        assert_eq!(
+            selection.to_json(),
+            r#"{"text":"a \"b\"","pattern":"quoted-double","x":4,"y":1,"hint":"sa"}"#
+        );
+    }
+
+    #[test]
+    fn selection_from_span() {
+        let lines = ["ééé 10.0.0.1"];
+        let span = Span {
+            x: 7,
+            y: 0,
+            pattern: "ipv4",
+            text: "10.0.0.1",
+            hint: "a".to_string(),
+            groups: vec![],
+        };
+
+        let selection = Selection::from_span(&lines, &span, span.text.to_string());
+        assert_eq!(selection.position, (1, 5));
+        assert_eq!(
+            selection.to_json(),
+            r#"{"text":"10.0.0.1","pattern":"ipv4","x":4,"y":0,"hint":"a"}"#
+        );
+    }
 }
@@ -0,0 +1,313 @@
+//! Setup and teardown of the terminal in which the spans are presented.
+//!
+//! Once done, the user must find their terminal exactly as they left it: same
+//! content, same cursor position, and same line discipline. Switching back
+//! from the alternate screen restores the cursor position on most terminals,
+//! but not all of them, so the cursor position is also explicitly saved and
+//! restored.
+//!
+//! The keyboard protocol enabled for the session, if any, is also disabled.
+//!
+//! The terminal can also be handed over to another command, such as the
+//! pager, with `suspend`.
+
+use std::io::{self, Write};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd};
+
+use termion::{
+    clear, cursor,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+
+use super::KeyboardProtocol;
+
+/// Save the cursor position (DECSC).
+///
+/// This is more widely supported than the `CSI s` sequence written by
+/// `termion::cursor::Save`.
+const SAVE_CURSOR: &str = "\x1b7";
+
+/// Restore the cursor position saved with `SAVE_CURSOR` (DECRC).
+const RESTORE_CURSOR: &str = "\x1b8";
+
+/// Failure of the terminal in which the spans are presented.
+#[derive(thiserror::Error, Debug)]
+pub enum TerminalError {
+    #[error("Cannot open the terminal `/dev/tty`: {0}")]
+    Open(#[source] io::Error),
+
+    #[error("Cannot switch the terminal to raw mode and to the alternate screen: {0}")]
+    Setup(#[source] io::Error),
+
+    #[error("Cannot read the keys entered in the terminal: {0}")]
+    Read(#[source] io::Error),
+
+    #[error("Cannot write to the terminal: {0}")]
+    Write(#[source] io::Error),
+}
+
+/// A terminal switched to raw mode, displaying the alternate screen with a
+/// hidden cursor, and reporting the keys with a `KeyboardProtocol`.
+///
+/// The terminal is restored when the `Screen` is dropped, including when
+/// unwinding from a panic.
+pub struct Screen<W: Write + AsFd> {
+    raw: RawTerminal<W>,
+    keyboard_protocol: KeyboardProtocol,
+}
+
+impl<W: Write + AsFd> Screen<W> {
+    /// Save the cursor position, then switch the terminal `writer` to raw
+    /// mode and to the alternate screen, hide the cursor, and enable the
+    /// `keyboard_protocol`.
+    pub fn new(writer: W, keyboard_protocol: KeyboardProtocol) -> io::Result<Self> {
+        let mut raw = writer.into_raw_mode()?;
+        write!(
+            raw,
+            "{SAVE_CURSOR}{ToAlternateScreen}{}{}",
+            cursor::Hide,
+            keyboard_protocol.enable_sequence()
+        )?;
+        raw.flush()?;
+
+        Ok(Screen {
+            raw,
+            keyboard_protocol,
+        })
+    }
+}
+
+impl<W: Write + AsFd> Write for Screen<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.raw.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.raw.flush()
+    }
+}
+
+impl<W: Write + AsFd> Drop for Screen<W> {
+    /// Disable the keyboard protocol, show the cursor, switch back to the main
+    /// screen and restore the cursor position. The line discipline is then
+    /// restored by the `RawTerminal`.
+    fn drop(&mut self) {
+        let _ = write!(
+            self.raw,
+            "{}{}{ToMainScreen}{RESTORE_CURSOR}",
+            self.keyboard_protocol.disable_sequence(),
+            cursor::Show
+        );
+        let _ = self.raw.flush();
+    }
+}
+
+/// Hand the terminal `tty`, on which the `writer` writes, over to the `run`
+/// function, such as a pager, then take it back.
+///
+/// While `run` is running, the alternate screen is cleared, the cursor shown,
+/// the keyboard protocol disabled and the line discipline back to the usual
+/// one: echo, line editing and signals. A command switching to the alternate
+/// screen itself, like `less`, switches back to the main screen on exit, so
+/// the alternate screen is entered again afterwards. It must then be
+/// rendered again.
+///
+/// If `tty` is not a terminal, its attributes are left as is.
+pub fn suspend<T>(
+    writer: &mut dyn Write,
+    tty: BorrowedFd,
+    keyboard_protocol: KeyboardProtocol,
+    run: impl FnOnce() -> T,
+) -> io::Result<T> {
+    write!(
+        writer,
+        "{}{}{}{}",
+        keyboard_protocol.disable_sequence(),
+        clear::All,
+        cursor::Goto(1, 1),
+        cursor::Show
+    )?;
+    writer.flush()?;
+
+    let raw = match terminal_attr(tty) {
+        Ok(raw) => Some(raw),
+        Err(err) if err.raw_os_error() == Some(libc::ENOTTY) => None,
+        Err(err) => return Err(err),
+    };
+    if let Some(raw) = &raw {
+        set_terminal_attr(tty, &cooked_terminal_attr(*raw))?;
+    }
+    let result = run();
+    if let Some(raw) = &raw {
+        set_terminal_attr(tty, raw)?;
+    }
+
+    write!(
+        writer,
+        "{ToAlternateScreen}{}{}",
+        cursor::Hide,
+        keyboard_protocol.enable_sequence()
+    )?;
+    writer.flush()?;
+
+    Ok(result)
+}
+
+fn terminal_attr(tty: BorrowedFd) -> io::Result<libc::termios> {
+    let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+    if unsafe { libc::tcgetattr(tty.as_raw_fd(), &mut termios) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(termios)
+}
+
+fn set_terminal_attr(tty: BorrowedFd, termios: &libc::termios) -> io::Result<()> {
+    if unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSADRAIN, termios) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Returns the terminal attributes `raw`, as set by `into_raw_mode`, with the
+/// input and output processing, echo, line editing and signals turned back
+/// on.
+fn cooked_terminal_attr(mut raw: libc::termios) -> libc::termios {
+    raw.c_iflag |= libc::ICRNL | libc::IXON;
+    raw.c_oflag |= libc::OPOST | libc::ONLCR;
+    raw.c_lflag |=
+        libc::ECHO | libc::ECHOE | libc::ECHOK | libc::ICANON | libc::ISIG | libc::IEXTEN;
+    raw
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Read;
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    /// Open a pseudo-terminal, returning its master and slave sides.
+    fn open_pty() -> (File, File) {
+        let mut master = 0;
+        let mut slave = 0;
+        let ret = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        assert_eq!(ret, 0, "cannot open a pseudo-terminal");
+
+        unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) }
+    }
+
+    /// Returns the local, input and output modes of the terminal.
+    fn modes(tty: &File) -> (libc::tcflag_t, libc::tcflag_t, libc::tcflag_t) {
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        let ret = unsafe { libc::tcgetattr(tty.as_raw_fd(), &mut termios) };
+        assert_eq!(ret, 0, "cannot read the terminal attributes");
+
+        (termios.c_lflag, termios.c_iflag, termios.c_oflag)
+    }
+
+    /// Read the `len` bytes written on the slave side of the pseudo-terminal.
+    fn read_output(master: &mut File, len: usize) -> String {
+        let mut output = vec![0; len];
+        master.read_exact(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn expected_output(content: &str) -> String {
+        format!(
+            "\x1b7{ToAlternateScreen}{hide}{content}{show}{ToMainScreen}\x1b8",
+            hide = cursor::Hide,
+            show = cursor::Show,
+        )
+    }
+
+    #[test]
+    fn test_teardown_restores_terminal() {
+        let (mut master, slave) = open_pty();
+        let initial_modes = modes(&slave);
+
+        {
+            let mut screen =
+                Screen::new(slave.try_clone().unwrap(), KeyboardProtocol::Legacy).unwrap();
+            let (lflag, _, _) = modes(&slave);
+            assert_eq!(lflag & (libc::ICANON | libc::ECHO), 0, "not in raw mode");
+
+            write!(screen, "spans").unwrap();
+        }
+
+        assert_eq!(modes(&slave), initial_modes);
+
+        let expected = expected_output("spans");
+        assert_eq!(read_output(&mut master, expected.len()), expected);
+    }
+
+    #[test]
+    fn test_teardown_disables_keyboard_protocol() {
+        let (mut master, slave) = open_pty();
+
+        {
+            let mut screen = Screen::new(slave, KeyboardProtocol::Kitty).unwrap();
+            write!(screen, "spans").unwrap();
+        }
+
+        let expected = format!(
+            "\x1b7{ToAlternateScreen}{hide}\x1b[>1uspans\x1b[<u{show}{ToMainScreen}\x1b8",
+            hide = cursor::Hide,
+            show = cursor::Show,
+        );
+        assert_eq!(read_output(&mut master, expected.len()), expected);
+    }
+
+    #[test]
+    fn test_suspend_restores_raw_mode() {
+        let (mut master, slave) = open_pty();
+
+        let mut screen = Screen::new(slave.try_clone().unwrap(), KeyboardProtocol::Kitty).unwrap();
+        let raw_modes = modes(&slave);
+
+        let suspended_modes = suspend(&mut screen, slave.as_fd(), KeyboardProtocol::Kitty, || {
+            modes(&slave)
+        })
+        .unwrap();
+        let (lflag, _, oflag) = suspended_modes;
+        assert_eq!(lflag & libc::ICANON, libc::ICANON, "still in raw mode");
+        assert_eq!(oflag & libc::OPOST, libc::OPOST, "still in raw mode");
+        assert_eq!(modes(&slave), raw_modes);
+        drop(screen);
+
+        let expected = format!(
+            "\x1b7{ToAlternateScreen}{hide}\x1b[>1u\x1b[<u{clear}{home}{show}{ToAlternateScreen}{hide}\x1b[>1u\x1b[<u{show}{ToMainScreen}\x1b8",
+            clear = clear::All,
+            home = cursor::Goto(1, 1),
+            hide = cursor::Hide,
+            show = cursor::Show,
+        );
+        assert_eq!(read_output(&mut master, expected.len()), expected);
+    }
+
+    #[test]
+    fn test_teardown_on_panic() {
+        let (mut master, slave) = open_pty();
+        let initial_modes = modes(&slave);
+
+        let tty = slave.try_clone().unwrap();
+        let result = std::panic::catch_unwind(move || {
+            let _screen = Screen::new(tty, KeyboardProtocol::Legacy).unwrap();
+            panic!("while presenting");
+        });
+        assert!(result.is_err());
+
+        assert_eq!(modes(&slave), initial_modes);
+
+        let expected = expected_output("");
+        assert_eq!(read_output(&mut master, expected.len()), expected);
+    }
+}
@@ -0,0 +1,391 @@
+//! Display width of text and wrapping of lines in the terminal.
+//!
+//! Lines wider than the terminal are wrapped by the terminal onto the next
+//! screen lines. Computing where each buffer line ends up on screen requires
+//! the display width of its chars: CJK chars and most emoji take 2 cells,
+//! combining marks take none, and tabs move to the next tab stop.
+//!
+//! The width tables are a compact approximation of Unicode's East Asian Width
+//! property, which covers the chars commonly found in terminals.
+
+use std::cmp;
+
+/// Distance between two tab stops, as in most terminals.
+const TAB_WIDTH: usize = 8;
+
+/// Ranges of chars occupying no cell: combining marks, zero-width chars and
+/// variation selectors.
+const ZERO_WIDTH: [(u32, u32); 14] = [
+    (0x0300, 0x036F),
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x1160, 0x11FF),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x200B, 0x200F),
+    (0x2060, 0x2064),
+    (0x20D0, 0x20FF),
+    (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F),
+    (0xE0100, 0xE01EF),
+];
+
+/// Ranges of chars occupying two cells: CJK, Hangul, fullwidth forms and
+/// emoji.
+const DOUBLE_WIDTH: [(u32, u32); 32] = [
+    (0x1100, 0x115F),
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE30, 0xFE4F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x1F300, 0x1FAFF),
+    (0x20000, 0x3FFFD),
+];
+
+fn in_table(c: char, table: &[(u32, u32)]) -> bool {
+    let c = c as u32;
+    table
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                cmp::Ordering::Less
+            } else if start > c {
+                cmp::Ordering::Greater
+            } else {
+                cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Returns the number of terminal cells occupied by the char `c`.
+///
+/// Control chars (including tabs, see `str_width`) return 0.
+pub fn char_width(c: char) -> usize {
+    if c.is_ascii() {
+        return if c.is_ascii_control() { 0 } else { 1 };
+    }
+    if c.is_control() || in_table(c, &ZERO_WIDTH) {
+        0
+    } else if in_table(c, &DOUBLE_WIDTH) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Returns the number of terminal cells occupied by `s` if printed from the
+/// first column, taking tab stops into account.
+pub fn str_width(s: &str) -> usize {
+    s.chars().fold(0, advance)
+}
+
+/// Returns the column after printing the char `c` at column `col`, ignoring
+/// wrapping.
+fn advance(col: usize, c: char) -> usize {
+    if c == '\t' {
+        (col / TAB_WIDTH + 1) * TAB_WIDTH
+    } else {
+        col + char_width(c)
+    }
+}
+
+/// Describes where a line from the buffer is displayed on the screen and how
+/// much vertical lines it takes.
+///
+/// The `pos_y` field is the actual vertical position due to wrapped lines
+/// before this line. The `size` field is the number of screen lines occupied
+/// by this line. The `width` field is the display width of the line, without
+/// its trailing whitespaces.
+///
+/// For example, given a buffer in which
+///
+/// - the first line is smaller than the screen width,
+/// - the second line is slightly larger,
+/// - and the third line is smaller than the screen width,
+///
+/// The corresponding `WrappedLine`s are
+///
+/// - the first `WrappedLine` has `pos_y: 0` and `size: 1`
+/// - the second `WrappedLine` has `pos_y: 1` and `size: 2` (larger than screen
+///   width)
+/// - the third `WrappedLine` has `pos_y: 3` and `size: 1`
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedLine {
+    pub pos_y: usize,
+    pub size: usize,
+    pub width: usize,
+}
+
+/// Compute each line's actual y position and size if displayed in a terminal
+/// of width `term_width`.
+pub fn compute_wrapped_lines(lines: &[&str], term_width: u16) -> Vec<WrappedLine> {
+    lines
+        .iter()
+        .scan(0, |position, &line| {
+            // Save the value to return (yield is in unstable).
+            let pos_y = *position;
+
+            let (width, size) = measure(line.trim_end(), term_width as usize);
+
+            // Update the position of the next line.
+            *position += size;
+
+            Some(WrappedLine { pos_y, size, width })
+        })
+        .collect()
+}
+
+/// Returns the display width of the `line`, and the number of screen lines it
+/// occupies once wrapped in a terminal of width `term_width`.
+///
+/// If the line has n cells, on a term of width n, this does not produce an
+/// extra line; it needs to exceed the width by 1 cell. In case the width is 0,
+/// lines are not wrapped.
+fn measure(line: &str, term_width: usize) -> (usize, usize) {
+    let mut cursor = Cursor::at(0, term_width);
+    for c in line.chars() {
+        cursor.print(c);
+    }
+
+    (str_width(line), cursor.row + 1)
+}
+
+/// Returns the longest prefix of `text` which fits on screen, if printed from
+/// the cell `start` and wrapped in a terminal of width `term_width`.
+///
+/// Cells are numbered from the first cell of the first screen line, row by
+/// row; the prefix must end before the cell `limit`. For instance, a text
+/// printed from the 4th column of the last but one screen line of a 10x5
+/// terminal has `start: 3` and `limit: 20`.
+pub fn fit(text: &str, start: usize, limit: usize, term_width: u16) -> &str {
+    let mut cursor = Cursor::at(start, cmp::max(1, term_width as usize));
+
+    for (offset, c) in text.char_indices() {
+        cursor.print(c);
+        if cursor.cell() > limit {
+            return &text[..offset];
+        }
+    }
+
+    text
+}
+
+/// Returns the cell where the char at the byte `offset` of the `line` is
+/// printed, numbered like in `fit`, if the line is printed from the first
+/// cell and wrapped in a terminal of width `term_width`.
+///
+/// This is the cell after the last char if the `offset` is the end of the
+/// line.
+pub fn cell_at(line: &str, offset: usize, term_width: u16) -> usize {
+    let mut cursor = Cursor::at(0, cmp::max(1, term_width as usize));
+
+    for (index, c) in line.char_indices() {
+        let (row, col) = cursor.print(c);
+        if index >= offset {
+            return Cursor { row, col, ..cursor }.cell();
+        }
+    }
+
+    cursor.cell()
+}
+
+/// Position of the cursor printing a line in a terminal of width
+/// `term_width`, which is not wrapped if 0.
+#[derive(Clone, Copy)]
+struct Cursor {
+    term_width: usize,
+    row: usize,
+    col: usize,
+}
+
+impl Cursor {
+    /// Returns the cursor on the `cell`, numbered like in `fit`.
+    fn at(cell: usize, term_width: usize) -> Self {
+        match term_width {
+            0 => Cursor {
+                term_width,
+                row: 0,
+                col: cell,
+            },
+            _ => Cursor {
+                term_width,
+                row: cell / term_width,
+                col: cell % term_width,
+            },
+        }
+    }
+
+    /// Returns the cell of the cursor, numbered like in `fit`. After a char
+    /// wider than the terminal, such as a tab, this is the start of the next
+    /// screen line.
+    fn cell(&self) -> usize {
+        self.row * self.term_width + cmp::min(self.col, self.term_width)
+    }
+
+    /// Move the cursor after the char `c`, and return the row and column
+    /// where `c` is printed.
+    ///
+    /// A wide char which does not fit at the end of a screen line is moved to
+    /// the next one, as terminals do.
+    fn print(&mut self, c: char) -> (usize, usize) {
+        let char_width = if c == '\t' {
+            advance(self.col, c) - self.col
+        } else {
+            char_width(c)
+        };
+
+        if self.term_width > 0
+            && self.col + char_width > self.term_width
+            && char_width <= self.term_width
+        {
+            self.row += 1;
+            self.col = 0;
+        }
+        let printed = (self.row, self.col);
+        self.col += char_width;
+
+        printed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_widths() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('é'), 1);
+        assert_eq!(char_width('\u{301}'), 0); // combining acute accent
+        assert_eq!(char_width('中'), 2);
+        assert_eq!(char_width('ｱ'), 1); // halfwidth katakana
+        assert_eq!(char_width('Ａ'), 2); // fullwidth latin
+        assert_eq!(char_width('🦀'), 2);
+        assert_eq!(char_width('\u{200B}'), 0);
+        assert_eq!(char_width('\x07'), 0);
+    }
+
+    #[test]
+    fn str_widths() {
+        assert_eq!(str_width("hello"), 5);
+        assert_eq!(str_width("日本語"), 6);
+        assert_eq!(str_width("e\u{301}te\u{301}"), 3);
+        assert_eq!(str_width("\tx"), 9);
+        assert_eq!(str_width("abc\tx"), 9);
+    }
+
+    #[test]
+    fn wrap_ascii_lines() {
+        let lines = ["short", "0123456789", "01234567890", ""];
+        let wrapped = compute_wrapped_lines(&lines, 10);
+
+        assert_eq!(
+            wrapped,
+            [
+                WrappedLine {
+                    pos_y: 0,
+                    size: 1,
+                    width: 5
+                },
+                WrappedLine {
+                    pos_y: 1,
+                    size: 1,
+                    width: 10
+                },
+                WrappedLine {
+                    pos_y: 2,
+                    size: 2,
+                    width: 11
+                },
+                WrappedLine {
+                    pos_y: 4,
+                    size: 1,
+                    width: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_wide_chars() {
+        // 9 ASCII chars + 1 wide char: the wide char does not fit on the 10th
+        // column, and is moved to the second screen line.
+        let lines = ["012345678中", "中中中中中", "\t\tx"];
+        let wrapped = compute_wrapped_lines(&lines, 10);
+
+        assert_eq!(
+            wrapped,
+            [
+                WrappedLine {
+                    pos_y: 0,
+                    size: 2,
+                    width: 11
+                },
+                WrappedLine {
+                    pos_y: 2,
+                    size: 1,
+                    width: 10
+                },
+                WrappedLine {
+                    pos_y: 3,
+                    size: 2,
+                    width: 17
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fit_text() {
+        assert_eq!(fit("0123456789", 0, 20, 10), "0123456789");
+        assert_eq!(fit("0123456789", 3, 10, 10), "0123456");
+        assert_eq!(fit("0123456789", 3, 15, 10), "0123456789");
+        assert_eq!(fit("0123456789", 12, 15, 10), "012");
+        assert_eq!(fit("0123456789", 10, 10, 10), "");
+
+        // The wide char moves to the second line, which is out of the limit.
+        assert_eq!(fit("abc中", 7, 10, 10), "abc");
+        assert_eq!(fit("abc中", 7, 12, 10), "abc中");
+    }
+
+    #[test]
+    fn cells_of_wide_chars() {
+        assert_eq!(cell_at("ab cd", 3, 10), 3);
+        assert_eq!(cell_at("日本 cd", "日本 ".len(), 10), 5);
+        assert_eq!(cell_at("e\u{301} cd", "e\u{301} ".len(), 10), 2);
+        assert_eq!(cell_at("🦀 ok", "🦀 ".len(), 10), 3);
+        assert_eq!(cell_at("\tx", 1, 10), 8);
+        // The wide char does not fit on the 10th column.
+        assert_eq!(cell_at("012345678中x", 9, 10), 10);
+        assert_eq!(cell_at("012345678中x", "012345678中".len(), 10), 12);
+        assert_eq!(cell_at("abc", 3, 10), 3);
+    }
+}
@@ -0,0 +1,252 @@
+//! Self-update of the `tmux-copyrat` binary from the GitHub releases.
+//!
+//! The latest release is looked up with the GitHub API, and the artifact
+//! matching the current platform is downloaded along with its `.sha256`
+//! checksum file. Once the checksum is verified, the archive is extracted and
+//! its binary atomically replaces the current executable.
+//!
+//! Downloads and archive extraction are delegated to `curl`, `tar` and
+//! `unzip`, which are available on all supported platforms.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+pub use crate::release::CURRENT_VERSION;
+use crate::release::{asset_name, checksum_name, target_triple, BINARY_NAME};
+use crate::{Error, Result};
+
+mod sha256;
+
+/// GitHub repository publishing the releases.
+const REPOSITORY: &str = "graelo/tmux-copyrat";
+
+/// A published release.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Release {
+    /// Version of the release, without the leading `v`.
+    pub version: String,
+    /// Download URLs of the release artifacts.
+    pub asset_urls: Vec<String>,
+}
+
+impl Release {
+    /// Returns the download URL of the artifact named `name`.
+    pub fn asset_url(&self, name: &str) -> Option<&str> {
+        self.asset_urls
+            .iter()
+            .map(String::as_str)
+            .find(|url| url.rsplit('/').next() == Some(name))
+    }
+
+    /// Returns `true` if this release is more recent than the running binary.
+    pub fn is_newer(&self) -> bool {
+        parse_version(&self.version) > parse_version(CURRENT_VERSION)
+    }
+}
+
+/// Fetch the latest release from the GitHub API.
+pub fn latest_release() -> Result<Release> {
+    let url = format!("https://api.github.com/repos/{REPOSITORY}/releases/latest");
+    let output = duct::cmd!(
+        "curl",
+        "-fsSL",
+        "-H",
+        "Accept: application/vnd.github+json",
+        url
+    )
+    .read()?;
+
+    parse_release(&output)
+}
+
+/// Extract the release version and asset URLs from the GitHub API response.
+///
+/// Only these two fields are needed, so they are matched rather than parsing
+/// the whole JSON document.
+fn parse_release(json: &str) -> Result<Release> {
+    let tag_re = Regex::new(r#""tag_name"\s*:\s*"v?([^"]+)""#).unwrap();
+    let url_re = Regex::new(r#""browser_download_url"\s*:\s*"([^"]+)""#).unwrap();
+
+    let version = tag_re
+        .captures(json)
+        .map(|caps| caps[1].to_string())
+        .ok_or_else(|| Error::SelfUpdate("no tag name in the release".into()))?;
+
+    let asset_urls = url_re
+        .captures_iter(json)
+        .map(|caps| caps[1].to_string())
+        .collect();
+
+    Ok(Release {
+        version,
+        asset_urls,
+    })
+}
+
+/// Parse the numeric components of a version such as `0.5.7`, ignoring any
+/// pre-release or build suffix.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Download the `release` artifact for the current platform, verify its
+/// checksum, and replace the current executable with it.
+pub fn install(release: &Release) -> Result<PathBuf> {
+    let target = target_triple()
+        .ok_or_else(|| Error::SelfUpdate("no release artifact for this platform".into()))?;
+    let name = asset_name(target);
+    let checksum_file_name = checksum_name(&name);
+
+    let archive_url = release
+        .asset_url(&name)
+        .ok_or_else(|| Error::SelfUpdate(format!("missing artifact `{name}`")))?;
+    let checksum_url = release
+        .asset_url(&checksum_file_name)
+        .ok_or_else(|| Error::SelfUpdate(format!("missing checksum `{checksum_file_name}`")))?;
+
+    let work_dir = std::env::temp_dir().join(format!("copyrat-update-{}", std::process::id()));
+    fs::create_dir_all(&work_dir)?;
+
+    let result = download_and_replace(&work_dir, &name, archive_url, checksum_url);
+    let _ = fs::remove_dir_all(&work_dir);
+    result
+}
+
+fn download_and_replace(
+    work_dir: &Path,
+    name: &str,
+    archive_url: &str,
+    checksum_url: &str,
+) -> Result<PathBuf> {
+    let archive = work_dir.join(name);
+    duct::cmd!("curl", "-fsSL", "-o", &archive, archive_url).run()?;
+    let checksum_file = duct::cmd!("curl", "-fsSL", checksum_url).read()?;
+
+    verify_checksum(&fs::read(&archive)?, &checksum_file)?;
+
+    if name.ends_with(".zip") {
+        duct::cmd!("unzip", "-q", "-o", &archive, "-d", work_dir).run()?;
+    } else {
+        duct::cmd!("tar", "-xJf", &archive, "-C", work_dir).run()?;
+    }
+
+    // Archives contain the binary at its build path.
+    let binary = work_dir.join("target/release").join(BINARY_NAME);
+    if !binary.is_file() {
+        return Err(Error::SelfUpdate(format!("no `{BINARY_NAME}` in `{name}`")));
+    }
+
+    replace_current_exe(&binary)
+}
+
+/// Check the `data` matches the digest in the `checksum_file`, formatted as
+/// the output of `shasum -a 256`.
+fn verify_checksum(data: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| Error::SelfUpdate("empty checksum file".into()))?;
+    let actual = sha256::hex_digest(data);
+
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(Error::SelfUpdate(format!(
+            "checksum mismatch: expected {expected}, got {actual}"
+        )))
+    }
+}
+
+/// Replace the current executable by the `binary`.
+///
+/// The binary is first copied next to the current executable, then renamed
+/// over it, so that the replacement is atomic.
+fn replace_current_exe(binary: &Path) -> Result<PathBuf> {
+    let current_exe = fs::canonicalize(std::env::current_exe()?)?;
+    let staged = current_exe.with_extension("new");
+
+    fs::copy(binary, &staged)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(&staged, &current_exe)?;
+
+    Ok(current_exe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_github_release() {
+        let json = r#"{
+  "url": "https://api.github.com/repos/graelo/tmux-copyrat/releases/1",
+  "tag_name": "v0.6.0",
+  "assets": [
+    {
+      "name": "tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz",
+      "browser_download_url": "https://github.com/graelo/tmux-copyrat/releases/download/v0.6.0/tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz"
+    },
+    {
+      "name": "tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz.sha256",
+      "browser_download_url": "https://github.com/graelo/tmux-copyrat/releases/download/v0.6.0/tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz.sha256"
+    }
+  ]
+}"#;
+        let release = parse_release(json).unwrap();
+
+        assert_eq!(release.version, "0.6.0");
+        assert_eq!(release.asset_urls.len(), 2);
+
+        let name = asset_name("x86_64-unknown-linux-gnu");
+        assert!(release.asset_url(&name).unwrap().ends_with(".tar.xz"));
+        assert!(release
+            .asset_url(&format!("{name}.sha256"))
+            .unwrap()
+            .ends_with(".sha256"));
+        assert_eq!(
+            release.asset_url("tmux-copyrat-aarch64-apple-darwin.zip"),
+            None
+        );
+
+        assert!(parse_release("{}").is_err());
+    }
+
+    #[test]
+    fn compare_versions() {
+        assert!(parse_version("0.10.0") > parse_version("0.9.9"));
+        assert!(parse_version("1.0.0") > parse_version("0.99.0"));
+        assert_eq!(parse_version("0.6.0-rc.1"), parse_version("0.6.0"));
+
+        let release = |version: &str| Release {
+            version: version.to_string(),
+            asset_urls: vec![],
+        };
+        assert!(release("999.0.0").is_newer());
+        assert!(!release(CURRENT_VERSION).is_newer());
+    }
+
+    #[test]
+    fn checksum_verification() {
+        let digest = sha256::hex_digest(b"binary");
+        let checksum_file = format!("{digest}  tmux-copyrat-x86_64-unknown-linux-gnu.tar.xz\n");
+
+        assert!(verify_checksum(b"binary", &checksum_file).is_ok());
+        assert!(matches!(
+            verify_checksum(b"tampered", &checksum_file),
+            Err(Error::SelfUpdate(_))
+        ));
+        assert!(verify_checksum(b"binary", "").is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! End-to-end tests running the `copyrat` binary in a pseudo-terminal.
+//!
+//! As in real use, the buffer is piped into the binary's stdin, while the keys
+//! are typed in the terminal: the pseudo-terminal is both the controlling
+//! terminal of the binary, from which termion reads the keys, and its stdout.
+
+use std::ffi::CStr;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Written once the terminal is set up, the binary then listens to keys.
+const HIDE_CURSOR: &str = "\x1b[?25l";
+
+/// Written when the terminal is restored: show the cursor, back to the main
+/// screen, and restore the cursor position.
+const TEARDOWN: &str = "\x1b[?25h\x1b[?1049l\x1b8";
+
+/// Maximum duration of each step, so that a stuck binary fails the test
+/// instead of hanging it.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns the local, input and output modes of the terminal.
+fn modes(tty: &File) -> (libc::tcflag_t, libc::tcflag_t, libc::tcflag_t) {
+    let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+    let ret = unsafe { libc::tcgetattr(tty.as_raw_fd(), &mut termios) };
+    assert_eq!(ret, 0, "cannot read the terminal attributes");
+
+    (termios.c_lflag, termios.c_iflag, termios.c_oflag)
+}
+
+/// Prevent the `file` from leaking into the binaries spawned by concurrent
+/// tests, which would keep the pseudo-terminal open.
+fn set_cloexec(file: &File) {
+    let ret = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) };
+    assert_eq!(ret, 0, "cannot set close-on-exec");
+}
+
+/// The `copyrat` binary running in a pseudo-terminal.
+struct Session {
+    child: Child,
+    master: File,
+    slave_path: PathBuf,
+    initial_modes: (libc::tcflag_t, libc::tcflag_t, libc::tcflag_t),
+    chunks: mpsc::Receiver<Vec<u8>>,
+    output: String,
+}
+
+impl Session {
+    /// Run `copyrat` with the `args` in a 80x24 pseudo-terminal, piping the
+    /// `buffer` into its stdin.
+    fn spawn(args: &[&str], buffer: &str) -> Session {
+        let mut master_fd = 0;
+        let mut slave_fd = 0;
+        let winsize = libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let ret = unsafe {
+            libc::openpty(
+                &mut master_fd,
+                &mut slave_fd,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &winsize,
+            )
+        };
+        assert_eq!(ret, 0, "cannot open a pseudo-terminal");
+
+        let (master, slave) =
+            unsafe { (File::from_raw_fd(master_fd), File::from_raw_fd(slave_fd)) };
+        set_cloexec(&master);
+        set_cloexec(&slave);
+
+        let slave_path = unsafe { CStr::from_ptr(libc::ttyname(slave_fd)) }
+            .to_str()
+            .unwrap()
+            .into();
+        let initial_modes = modes(&slave);
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_copyrat"));
+        command
+            .args(args)
+            .env_remove("TMUX")
+            // Keep the selections out of the history of the user.
+            .env("XDG_STATE_HOME", std::env::temp_dir())
+            .stdin(Stdio::piped())
+            .stdout(slave.try_clone().unwrap())
+            .stderr(Stdio::null());
+
+        // Make the pseudo-terminal the controlling terminal of the binary.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setsid() < 0 || libc::ioctl(slave_fd, libc::TIOCSCTTY, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let mut child = command.spawn().unwrap();
+
+        // Only the binary must keep the pseudo-terminal open, so that reading
+        // the master fails once it exits.
+        drop(command);
+        drop(slave);
+
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(buffer.as_bytes()).unwrap();
+        drop(stdin);
+
+        let (sender, chunks) = mpsc::channel();
+        let mut reader = master.try_clone().unwrap();
+        thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            while let Ok(len @ 1..) = reader.read(&mut chunk) {
+                if sender.send(chunk[..len].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Session {
+            child,
+            master,
+            slave_path,
+            initial_modes,
+            chunks,
+            output: String::new(),
+        }
+    }
+
+    /// Wait until the binary writes the `text`.
+    fn expect(&mut self, text: &str) {
+        let deadline = Instant::now() + TIMEOUT;
+
+        while !self.output.contains(text) {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.chunks.recv_timeout(timeout) {
+                Ok(chunk) => self.output.push_str(&String::from_utf8_lossy(&chunk)),
+                Err(_) => panic!("expected {text:?}, received {:?}", self.output),
+            }
+        }
+    }
+
+    /// Type the `keys` in the terminal.
+    fn send(&mut self, keys: &str) {
+        self.master.write_all(keys.as_bytes()).unwrap();
+    }
+
+    /// Wait for the binary to exit, and return its exit status and its whole
+    /// output.
+    fn finish(mut self) -> (ExitStatus, String) {
+        let deadline = Instant::now() + TIMEOUT;
+
+        let status = loop {
+            if let Some(status) = self.child.try_wait().unwrap() {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                self.child.kill().unwrap();
+                panic!("still running, received {:?}", self.output);
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        while let Ok(chunk) = self.chunks.recv_timeout(TIMEOUT) {
+            self.output.push_str(&String::from_utf8_lossy(&chunk));
+        }
+
+        // The terminal modes are shared by all the file descriptors of the
+        // pseudo-terminal.
+        let slave = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(&self.slave_path)
+            .unwrap();
+        assert_eq!(
+            modes(&slave),
+            self.initial_modes,
+            "the terminal modes were not restored"
+        );
+
+        (status, self.output)
+    }
+}
+
+#[test]
+fn select_focused_span() {
+    let mut session = Session::spawn(
+        &["-x", "ipv4"],
+        "lorem 127.0.0.1 lorem\nlorem 10.0.0.1 lorem\n",
+    );
+    session.expect(HIDE_CURSOR);
+    session.send("y");
+
+    let (status, output) = session.finish();
+
+    assert_eq!(status.code(), Some(0));
+    assert!(
+        output.ends_with(&format!("{TEARDOWN}127.0.0.1\r\n")),
+        "{output:?}"
+    );
+}
+
+#[test]
+fn select_span_by_hint() {
+    let mut session = Session::spawn(
+        &["-x", "ipv4", "-k", "qwerty"],
+        "lorem 127.0.0.1 lorem\nlorem 10.0.0.1 lorem\n",
+    );
+    session.expect(HIDE_CURSOR);
+    session.send("s");
+
+    let (status, output) = session.finish();
+
+    assert_eq!(status.code(), Some(0));
+    assert!(
+        output.ends_with(&format!("{TEARDOWN}10.0.0.1\r\n")),
+        "{output:?}"
+    );
+}
+
+#[test]
+fn focus_next_span_then_select() {
+    let mut session = Session::spawn(
+        &["-x", "ipv4"],
+        "lorem 127.0.0.1 lorem\nlorem 10.0.0.1 lorem\n",
+    );
+    session.expect(HIDE_CURSOR);
+    session.send("n");
+    session.send("\r");
+
+    let (status, output) = session.finish();
+
+    assert_eq!(status.code(), Some(0));
+    assert!(
+        output.ends_with(&format!("{TEARDOWN}10.0.0.1\r\n")),
+        "{output:?}"
+    );
+}
+
+#[test]
+fn cancel_with_esc() {
+    let mut session = Session::spawn(&["-x", "ipv4"], "lorem 127.0.0.1 lorem\n");
+    session.expect(HIDE_CURSOR);
+    session.send("\x1b");
+
+    let (status, output) = session.finish();
+
+    assert_eq!(status.code(), Some(1));
+    assert!(output.ends_with(TEARDOWN), "{output:?}");
+}
+
+#[test]
+fn exit_without_match() {
+    let session = Session::spawn(&["-x", "ipv4"], "lorem ipsum\n");
+
+    let (status, output) = session.finish();
+
+    assert_eq!(status.code(), Some(1));
+    assert_eq!(output, "");
+}